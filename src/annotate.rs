@@ -0,0 +1,151 @@
+//! Annotation of signal bitmaps found in text: the `SigPnd:`, `ShdPnd:`,
+//! `SigBlk:`, `SigIgn:` and `SigCgt:` fields of `/proc/<pid>/status`, as
+//! found in logs or pasted status dumps, are followed by their signals.
+use crate::signals::{BitmapType, SigSet};
+
+// Number of hex digits of a bitmap in `/proc/<pid>/status`.
+const MASK_DIGITS: usize = 16;
+
+// Bitmap types whose fields are annotated.
+static MASK_TYPES: &[BitmapType] = &[
+    BitmapType::SigPnd,
+    BitmapType::ShdPnd,
+    BitmapType::SigBlk,
+    BitmapType::SigIgn,
+    BitmapType::SigCgt,
+];
+
+// Return the offset of the first field label in the text (at the start
+// of a word), and the length and bitmap type of the label.
+fn next_label(text: &str) -> Option<(usize, usize, &'static BitmapType)> {
+    MASK_TYPES
+        .iter()
+        .filter_map(|typ| {
+            let lbl: String = typ.to_string();
+            text.match_indices(lbl.as_str())
+                .find(|(pos, _)| {
+                    !text[..*pos].ends_with(|c: char| c.is_ascii_alphanumeric())
+                })
+                .map(|(pos, _)| (pos, lbl.len(), typ))
+        })
+        .min_by_key(|(pos, ..)| *pos)
+}
+
+// Return the note on signals in a bitmap that can't be in it on a live
+// system (see `BitmapType::impossible`), or an empty string if none.
+fn impossible_note(typ: &BitmapType, set: &SigSet) -> String {
+    let set: SigSet = set.intersection(&typ.impossible());
+    let state: &str = match typ {
+        BitmapType::SigBlk => "blocked",
+        BitmapType::SigIgn => "ignored",
+        BitmapType::SigCgt => "caught",
+        BitmapType::SigPnd | BitmapType::ShdPnd => return String::new(),
+    };
+
+    match set.is_empty() {
+        true => String::new(),
+        false => format!("; {} can't be {}", set.to_names().join(", "), state),
+    }
+}
+
+/// Returns a line of text with the signals of each bitmap field in it
+/// (e.g. `SigBlk:` followed by whitespace and 16 hex digits, as in
+/// `/proc/<pid>/status`) appended in angle brackets; other text is
+/// left as is. Signals that can't be in the bitmap on a live system
+/// (see [`BitmapType::impossible`]) are pointed out, since they mean
+/// the text is corrupt or from a kernel with other signal numbers.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::annotate::annotate_masks;
+/// assert_eq!(
+///     annotate_masks("worker 42 stuck, SigBlk:\t0000000000004001, retrying"),
+///     "worker 42 stuck, SigBlk:\t0000000000004001 <HUP, TERM>, retrying",
+/// );
+/// assert_eq!(
+///     annotate_masks("SigIgn:\t0000000000001100"),
+///     "SigIgn:\t0000000000001100 <KILL, PIPE; KILL can't be ignored>",
+/// );
+/// ````
+pub fn annotate_masks(line: &str) -> String {
+    let mut out: String = String::with_capacity(line.len());
+    let mut rest: &str = line;
+
+    while let Some((pos, len, typ)) = next_label(rest) {
+        let val: &str = rest[pos + len..].trim_start_matches([' ', '\t']);
+        let end: usize = rest.len() - val.len() + MASK_DIGITS;
+        let mask: Option<u64> = val
+            .get(..MASK_DIGITS)
+            .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .filter(|_| {
+                !val[MASK_DIGITS..].starts_with(|c: char| c.is_ascii_hexdigit())
+            })
+            .and_then(|hex| u64::from_str_radix(hex, 16).ok());
+
+        match mask {
+            Some(map) => {
+                out.push_str(&rest[..end]);
+                let set: SigSet = SigSet::from_bits(map);
+
+                out.push_str(&match set.to_names() {
+                    names if names.is_empty() => String::from(" <NONE>"),
+                    names => format!(
+                        " <{}{}>",
+                        names.join(", "),
+                        impossible_note(typ, &set)
+                    ),
+                });
+                rest = &rest[end..];
+            }
+            None => {
+                out.push_str(&rest[..pos + len]);
+                rest = &rest[pos + len..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate_masks() {
+        let tests: Vec<(&str, &str)> = vec![
+            ("", ""),
+            ("no masks here", "no masks here"),
+            (
+                "SigPnd:\t0000000000000000",
+                "SigPnd:\t0000000000000000 <NONE>",
+            ),
+            (
+                "ShdPnd: 0000000000000100 SigCgt:0000000000010002",
+                "ShdPnd: 0000000000000100 <KILL> SigCgt:0000000000010002 \
+                 <INT, CHLD>",
+            ),
+            (
+                "SigIgn:\t0000000000001000\tSigIgn:\t00000000000010",
+                "SigIgn:\t0000000000001000 <PIPE>\tSigIgn:\t00000000000010",
+            ),
+            (
+                "SigBlk:\t0000000000044000 SigCgt: 0000000000000100",
+                "SigBlk:\t0000000000044000 <TERM, STOP; STOP can't be blocked> \
+                 SigCgt: 0000000000000100 <KILL; KILL can't be caught>",
+            ),
+            ("SigBlk: 00000000000000001", "SigBlk: 00000000000000001"),
+            ("SigBlk: 000000000000000g", "SigBlk: 000000000000000g"),
+            ("XSigBlk: 0000000000000001", "XSigBlk: 0000000000000001"),
+            (
+                "PID: 42     SigBlk: 1  [0x0000000000004000]: TERM",
+                "PID: 42     SigBlk: 1  [0x0000000000004000]: TERM",
+            ),
+        ];
+
+        for test in tests {
+            assert_eq!(annotate_masks(test.0), test.1);
+        }
+    }
+}
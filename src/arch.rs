@@ -0,0 +1,489 @@
+//! Signal tables for other architectures. Signal numbers differ across
+//! architectures (e.g. `USR1` is 10 on x86 and ARM, 16 on MIPS and
+//! PA-RISC, and 30 on SPARC and Alpha), and the built-in table is that
+//! of x86 and ARM; tables for the others can be built in code or loaded
+//! from a file, and registered by name. The table of the host can be
+//! read from the installed kernel headers.
+use crate::{
+    error::Error,
+    signals::{sigabbrev_np, signal_number, NR_SIGS, RTMIN_RANGE},
+};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::PathBuf,
+    sync::{PoisonError, RwLock},
+};
+
+// Directory of the installed headers.
+const INCLUDE_DIR: &str = "/usr/include";
+
+// Kernel header defining the signal numbers (under `INCLUDE_DIR`, or a
+// multiarch directory in it, e.g. `x86_64-linux-gnu`).
+const SIGNAL_HEADER: &str = "asm/signal.h";
+
+// Macros in the signal headers that aren't signals.
+static NON_SIGS: &[&str] = &["RTMIN", "RTMAX", "STKSZ"];
+
+// Tables registered by name.
+static REGISTRY: RwLock<BTreeMap<String, ArchTable>> =
+    RwLock::new(BTreeMap::new());
+
+/// A difference between the built-in table and the signal header of the
+/// host (see [`self_test`]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Mismatch {
+    /// A signal (number) the built-in table names differently (the
+    /// first name) than the host (the second), and not as an alias.
+    Name(u8, String, String),
+
+    /// The first realtime signal of the built-in table (the first
+    /// number) isn't that of the host (`SIGRTMIN`, the second).
+    RtMin(u8, u8),
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mismatch::Name(idx, name, host) => write!(
+                f,
+                "signal {} is {} here, but {} on the host",
+                idx, name, host
+            ),
+            Mismatch::RtMin(idx, host) => write!(
+                f,
+                "realtime signals start at {} here, but at {} on the host",
+                idx, host
+            ),
+        }
+    }
+}
+
+/// A table of signal names by signal number, for an architecture.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::arch::ArchTable;
+/// let table: ArchTable = ArchTable::new([(1, "HUP"), (16, "USR1")]);
+/// assert_eq!(table.interpret(&0x8001), vec!["HUP", "USR1"]);
+/// ````
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArchTable {
+    names: BTreeMap<u8, String>,
+
+    // First realtime signal (`SIGRTMIN`), for tables read from a header;
+    // the signals from it on are defined without being named.
+    rt_min: Option<u8>,
+}
+
+impl ArchTable {
+    /// Returns a table of signal numbers and names (without the `SIG`
+    /// prefix); numbers beyond 64 are left out, since they don't fit a
+    /// signal bitmap.
+    pub fn new<I, S>(names: I) -> ArchTable
+    where
+        I: IntoIterator<Item = (u8, S)>,
+        S: Into<String>,
+    {
+        ArchTable {
+            names: names
+                .into_iter()
+                .filter(|(idx, _)| (0x1..=NR_SIGS).contains(idx))
+                .map(|(idx, name)| (idx, name.into()))
+                .collect(),
+            rt_min: None,
+        }
+    }
+
+    /// Returns the table in a text file with a signal per line: its
+    /// number and name, separated by whitespace (e.g. `16 SIGUSR1`);
+    /// blank lines and comments (`#`) are skipped, and the `SIG` prefix
+    /// is dropped.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidEntry`] for a line that isn't a signal number
+    /// (1 to 64) and a name.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::arch::ArchTable;
+    /// let table: ArchTable =
+    ///     ArchTable::parse("# PA-RISC\n1 SIGHUP\n16 SIGUSR1\n").unwrap();
+    /// assert_eq!(table.name(&16), "USR1");
+    /// ````
+    pub fn parse(text: &str) -> Result<ArchTable, Error> {
+        let mut names: BTreeMap<u8, String> = BTreeMap::new();
+
+        for line in text.lines() {
+            let line: &str = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let idx: Option<u8> = fields[0]
+                .parse::<u8>()
+                .ok()
+                .filter(|idx| (0x1..=NR_SIGS).contains(idx));
+
+            match (idx, &fields[1..]) {
+                (Some(idx), [name]) => {
+                    let name: &str = name.strip_prefix("SIG").unwrap_or(name);
+                    names.insert(idx, name.to_ascii_uppercase());
+                }
+                _ => return Err(Error::InvalidEntry(line.to_string())),
+            }
+        }
+
+        Ok(ArchTable {
+            names,
+            rt_min: None,
+        })
+    }
+
+    /// Returns the table in a C header defining signal numbers, such as
+    /// the kernel's `asm/signal.h`: the `#define SIG<NAME> <number>`
+    /// lines. Aliases defined as other signals (e.g. `SIGPOLL SIGIO`)
+    /// are skipped, and of those defined as the same number (e.g.
+    /// `SIGABRT` and `SIGIOT`), the first one is kept. The realtime
+    /// signals, from `SIGRTMIN` on, are defined but left unnamed.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::arch::ArchTable;
+    /// let table: ArchTable = ArchTable::from_header(
+    ///     "#define SIGABRT\t 6\n#define SIGIOT\t 6\n#define SIGIO\t29\n\
+    ///      #define SIGPOLL\tSIGIO\n#define SIGRTMIN\t32\n",
+    /// );
+    /// assert_eq!(table.interpret(&0x1000_0020), vec!["ABRT", "IO"]);
+    /// ````
+    pub fn from_header(text: &str) -> ArchTable {
+        let mut names: BTreeMap<u8, String> = BTreeMap::new();
+        let mut rt_min: Option<u8> = None;
+
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (name, num): (&str, &str) = match fields[..] {
+                ["#define", name, num, ..] => (name, num),
+                _ => continue,
+            };
+            let name: &str = match name.strip_prefix("SIG") {
+                Some("RTMIN") => {
+                    rt_min = rt_min.or(num.parse::<u8>().ok());
+                    continue;
+                }
+                Some(name)
+                    if !name.is_empty()
+                        && !NON_SIGS.contains(&name)
+                        && name.chars().all(|c| c.is_ascii_alphanumeric()) =>
+                {
+                    name
+                }
+                _ => continue,
+            };
+
+            if let Some(idx) = num
+                .parse::<u8>()
+                .ok()
+                .filter(|idx| (0x1..=NR_SIGS).contains(idx))
+            {
+                names.entry(idx).or_insert_with(|| name.to_string());
+            }
+        }
+
+        ArchTable { names, rt_min }
+    }
+
+    /// Returns the name of a signal, or its number if it isn't in the
+    /// table.
+    pub fn name(&self, idx: &u8) -> String {
+        match self.names.get(idx) {
+            Some(name) => name.clone(),
+            None => idx.to_string(),
+        }
+    }
+
+    /// Returns the names of the signals in a bitmap (bit `n` is signal
+    /// `n + 1`), as with [`crate::interpret`].
+    pub fn interpret(&self, map: &u64) -> Vec<String> {
+        (0x1..=NR_SIGS)
+            .filter(|idx| map & (0x1_u64 << (idx - 1)) != 0)
+            .map(|idx| self.name(&idx))
+            .collect()
+    }
+
+    /// Returns the signals in a bitmap that the table doesn't define,
+    /// which [`ArchTable::interpret`] names by number; e.g. from a
+    /// corrupted capture, or one decoded with the wrong table.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::arch::ArchTable;
+    /// let table: ArchTable = ArchTable::new([(1, "HUP"), (16, "USR1")]);
+    /// assert_eq!(table.undefined(&0x8003), vec![2]);
+    /// ````
+    pub fn undefined(&self, map: &u64) -> Vec<u8> {
+        (0x1..=NR_SIGS)
+            .filter(|idx| map & (0x1_u64 << (idx - 1)) != 0)
+            .filter(|idx| !self.names.contains_key(idx))
+            .filter(|idx| self.rt_min.is_none_or(|rt_min| *idx < rt_min))
+            .collect()
+    }
+}
+
+/// Returns the table of the host, read from the installed kernel headers
+/// (`asm/signal.h` in `/usr/include`, or in a multiarch directory in
+/// it), so that signals are named as on the running system rather than
+/// after the built-in table.
+///
+/// # Errors
+///
+/// An error of kind `NotFound` if no header defining signals is
+/// installed (e.g. without the kernel headers package).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::arch::host_table;
+/// if let Ok(table) = host_table() {
+///     assert_eq!(table.name(&9), "KILL");
+/// }
+/// ````
+pub fn host_table() -> Result<ArchTable, io::Error> {
+    host_header().map(|text| ArchTable::from_header(&text))
+}
+
+// Return the contents of the kernel header defining the signals of the
+// host (see `host_table`).
+fn host_header() -> Result<String, io::Error> {
+    let mut paths: Vec<PathBuf> = vec![PathBuf::from(INCLUDE_DIR)];
+
+    if let Ok(entries) = fs::read_dir(INCLUDE_DIR) {
+        let mut dirs: Vec<PathBuf> = entries
+            .map_while(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.to_string_lossy().contains("-linux-"))
+            .collect();
+        dirs.sort();
+        paths.extend(dirs);
+    }
+
+    paths
+        .iter()
+        .filter_map(|dir| fs::read_to_string(dir.join(SIGNAL_HEADER)).ok())
+        .find(|text| !ArchTable::from_header(text).names.is_empty())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no {} in {}", SIGNAL_HEADER, INCLUDE_DIR),
+            )
+        })
+}
+
+/// Returns the differences between the built-in table and a C header
+/// defining signal numbers (see [`ArchTable::from_header`]): standard
+/// signals named differently (aliases, e.g. `IOT` for `ABRT`, aside),
+/// and the first realtime signal (`SIGRTMIN`) if the header defines
+/// it. Signals the header doesn't define are skipped.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::arch::{compare_header, Mismatch};
+/// assert_eq!(
+///     compare_header("#define SIGHUP\t 1\n#define SIGUSR1\t16\n#define SIGRTMIN\t32\n"),
+///     vec![Mismatch::Name(16, String::from("STKFLT"), String::from("USR1"))],
+/// );
+/// ````
+pub fn compare_header(text: &str) -> Vec<Mismatch> {
+    let table: ArchTable = ArchTable::from_header(text);
+    let mut out: Vec<Mismatch> = table
+        .names
+        .iter()
+        .filter(|(idx, _)| **idx < RTMIN_RANGE.start)
+        .filter(|(idx, name)| {
+            sigabbrev_np(idx) != **name && signal_number(name) != Some(**idx)
+        })
+        .map(|(idx, name)| {
+            Mismatch::Name(*idx, sigabbrev_np(idx), name.clone())
+        })
+        .collect();
+
+    if let Some(idx) = table.rt_min.filter(|idx| *idx != RTMIN_RANGE.start) {
+        out.push(Mismatch::RtMin(RTMIN_RANGE.start, idx));
+    }
+
+    out
+}
+
+/// Returns the differences between the built-in table and that of the
+/// host, read from the installed kernel headers (see [`host_table`] and
+/// [`compare_header`]), so that decoded names can be trusted to match
+/// the running system. The C library isn't consulted, since the crate
+/// has no bindings to it.
+///
+/// # Errors
+///
+/// An error of kind `NotFound` if no header defining signals is
+/// installed (e.g. without the kernel headers package).
+pub fn self_test() -> Result<Vec<Mismatch>, io::Error> {
+    host_header().map(|text| compare_header(&text))
+}
+
+/// Registers a table under the name of an architecture, replacing the
+/// table registered under the name before, if any.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::arch::{arch_table, register_arch_table, ArchTable};
+/// register_arch_table("sparc", ArchTable::new([(30, "USR1")]));
+/// assert_eq!(arch_table("sparc").unwrap().name(&30), "USR1");
+/// ````
+pub fn register_arch_table(name: &str, table: ArchTable) {
+    REGISTRY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(name.to_string(), table);
+}
+
+/// Returns the table registered under the name of an architecture.
+///
+/// # Errors
+///
+/// [`Error::UnknownArch`] if no table is registered under the name.
+pub fn arch_table(name: &str) -> Result<ArchTable, Error> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(name)
+        .cloned()
+        .ok_or_else(|| Error::UnknownArch(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signal numbers and names of a table.
+    type Entries = Vec<(u8, &'static str)>;
+
+    #[test]
+    fn test_arch_table_parse() {
+        let tests: Vec<(&str, Result<Entries, Error>)> = vec![
+            ("", Ok(vec![])),
+            (
+                "# MIPS\n\n1 SIGHUP\n  16\tusr1  # comment\n65 SIGBOGUS",
+                Err(Error::InvalidEntry(String::from("65 SIGBOGUS"))),
+            ),
+            (
+                "1 SIGHUP\n16 usr1 # comment\n\n18 SIGCHLD\n",
+                Ok(vec![(1, "HUP"), (16, "USR1"), (18, "CHLD")]),
+            ),
+            ("HUP 1", Err(Error::InvalidEntry(String::from("HUP 1")))),
+            ("1", Err(Error::InvalidEntry(String::from("1")))),
+            ("1 HUP X", Err(Error::InvalidEntry(String::from("1 HUP X")))),
+        ];
+
+        for test in tests {
+            assert_eq!(ArchTable::parse(test.0), test.1.map(ArchTable::new));
+        }
+    }
+
+    #[test]
+    fn test_arch_table_from_header() {
+        let text: &str = "#ifndef _ASM_X86_SIGNAL_H
+#define _NSIG\t\t64
+#define SIGHUP\t\t 1
+#define SIGABRT\t\t 6
+#define SIGIOT\t\t 6
+#define SIGIO\t\t29
+#define SIGPOLL\t\tSIGIO
+/*
+#define SIGLOST\t\t29
+*/
+#define SIGPWR\t\t30
+#define SIGRTMIN\t32
+#define SIGRTMAX\t_NSIG
+#define SIG_BLOCK\t0
+#define SIGSTKSZ\t8192
+#define SIG\t\t7
+#define SIGBOGUS
+";
+
+        assert_eq!(
+            ArchTable::from_header(text),
+            ArchTable {
+                rt_min: Some(32),
+                ..ArchTable::new([
+                    (1, "HUP"),
+                    (6, "ABRT"),
+                    (29, "IO"),
+                    (30, "PWR"),
+                ])
+            }
+        );
+    }
+
+    #[test]
+    fn test_arch_table_undefined() {
+        let table: ArchTable = ArchTable::new([(1, "HUP"), (16, "USR1")]);
+        let header: ArchTable = ArchTable::from_header(
+            "#define SIGHUP\t\t 1\n#define SIGRTMIN\t32\n",
+        );
+        let empty: ArchTable = ArchTable::default();
+        let tests: Vec<(&ArchTable, u64, Vec<u8>)> = vec![
+            (&table, 0x0, vec![]),
+            (&table, 0x8001, vec![]),
+            (&table, 0x8000_0000_0000_8003, vec![2, 64]),
+            (&header, 0x8000_0001_0000_0001, vec![]),
+            (&header, 0x8000_0000_c000_0003, vec![2, 31]),
+            (&empty, 0x5, vec![1, 3]),
+        ];
+
+        for test in tests {
+            assert_eq!(test.0.undefined(&test.1), test.2);
+        }
+    }
+
+    #[test]
+    fn test_compare_header() {
+        let tests: Vec<(&str, Vec<Mismatch>)> = vec![
+            ("", vec![]),
+            (
+                "#define SIGHUP\t\t 1\n#define SIGIOT\t\t 6\n\
+                 #define SIGCHLD\t\t17\n#define SIGRTMIN\t32\n",
+                vec![],
+            ),
+            (
+                "#define SIGUSR1\t\t30\n#define SIGRTMIN\t33\n\
+                 #define SIGRTMAX\t_NSIG\n",
+                vec![
+                    Mismatch::Name(
+                        30,
+                        String::from("PWR"),
+                        String::from("USR1"),
+                    ),
+                    Mismatch::RtMin(32, 33),
+                ],
+            ),
+        ];
+
+        for test in tests {
+            assert_eq!(compare_header(test.0), test.1);
+        }
+    }
+
+    #[test]
+    fn test_arch_table_registry() {
+        assert_eq!(
+            arch_table("vax"),
+            Err(Error::UnknownArch(String::from("vax")))
+        );
+
+        register_arch_table("parisc", ArchTable::new([(16, "USR1")]));
+        register_arch_table("parisc", ArchTable::new([(17, "USR2")]));
+
+        let table: ArchTable = arch_table("parisc").unwrap();
+        assert_eq!(table.interpret(&0x18000), vec!["16", "USR2"]);
+    }
+}
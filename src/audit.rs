@@ -0,0 +1,115 @@
+//! Auditing of the signal bitmaps processes start with: processes are
+//! tracked across scans of `/proc`, to tell the ones started (forked)
+//! and those running a new program (exec'd) since the previous scan.
+use std::collections::HashMap;
+
+/// What happened to a process since the previous scan.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// The process started.
+    Spawn,
+
+    /// The process runs a different program.
+    Exec,
+}
+
+/// Processes seen in the previous scan, by PID: their start time (to
+/// tell reused PIDs) and program (e.g. the path of the executable).
+#[derive(Clone, Debug, Default)]
+pub struct Tracker {
+    seen: HashMap<u32, (u64, String)>,
+    primed: bool,
+}
+
+impl Tracker {
+    /// Records a scan of the processes, given their PIDs, start times
+    /// and programs, returning what happened since the previous scan in
+    /// order of the PIDs. The first scan is the baseline, and returns
+    /// nothing; processes that start and exit between two scans are not
+    /// seen.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::audit::{Event, Tracker};
+    /// let mut tracker: Tracker = Tracker::default();
+    /// tracker.update(vec![(1, 10, String::from("/sbin/init"))]);
+    /// assert_eq!(
+    ///     tracker.update(vec![
+    ///         (1, 10, String::from("/sbin/init")),
+    ///         (42, 20, String::from("/usr/bin/app")),
+    ///     ]),
+    ///     vec![(42, Event::Spawn)],
+    /// );
+    /// ````
+    pub fn update<I>(&mut self, procs: I) -> Vec<(u32, Event)>
+    where
+        I: IntoIterator<Item = (u32, u64, String)>,
+    {
+        let mut seen: HashMap<u32, (u64, String)> = HashMap::new();
+        let mut out: Vec<(u32, Event)> = Vec::new();
+
+        for (pid, start, prog) in procs {
+            let event: Option<Event> = match self.seen.get(&pid) {
+                _ if !self.primed => None,
+                Some((old, _)) if *old != start => Some(Event::Spawn),
+                Some((_, old)) if *old != prog => Some(Event::Exec),
+                Some(_) => None,
+                None => Some(Event::Spawn),
+            };
+
+            if let Some(event) = event {
+                out.push((pid, event));
+            }
+
+            seen.insert(pid, (start, prog));
+        }
+
+        self.seen = seen;
+        self.primed = true;
+        out.sort_unstable_by_key(|(pid, _)| *pid);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // PIDs, start times and programs of a scan.
+    type Scan = Vec<(u32, u64, &'static str)>;
+
+    #[test]
+    fn test_tracker_update() {
+        let tests: Vec<(Scan, Vec<(u32, Event)>)> = vec![
+            (vec![(1, 10, "/sbin/init"), (7, 15, "/bin/sh")], vec![]),
+            (
+                vec![
+                    (7, 15, "/usr/bin/app"),
+                    (1, 10, "/sbin/init"),
+                    (3, 30, "/bin/sh"),
+                ],
+                vec![(3, Event::Spawn), (7, Event::Exec)],
+            ),
+            (vec![(1, 10, "/sbin/init"), (7, 15, "/usr/bin/app")], vec![]),
+            (
+                vec![(1, 10, "/sbin/init"), (3, 40, "/bin/sh")],
+                vec![(3, Event::Spawn)],
+            ),
+            (
+                vec![(1, 10, "/sbin/init"), (3, 50, "/bin/sh")],
+                vec![(3, Event::Spawn)],
+            ),
+        ];
+        let mut tracker: Tracker = Tracker::default();
+
+        for test in tests {
+            let procs = test
+                .0
+                .into_iter()
+                .map(|(pid, start, prog)| (pid, start, prog.to_string()));
+
+            assert_eq!(tracker.update(procs), test.1);
+        }
+    }
+}
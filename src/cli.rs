@@ -0,0 +1,2267 @@
+//! Command line interface.
+use crate::{
+    annotate::annotate_masks,
+    arch::{host_table, self_test, ArchTable, Mismatch},
+    audit::{Event, Tracker},
+    doctor::{diagnose, Finding, Health, Severity},
+    error::Error as SigError,
+    features::FEATURES,
+    fleet::{merge_users, parse_users, FleetUsers},
+    format::{
+        format_alert, format_audit, format_binary, format_bits, format_compact,
+        format_compact_ruler, format_decoded, format_decoded_arch,
+        format_default, format_delta, format_finding, format_finding_diff,
+        format_fleet, format_group_summary, format_killable, format_legend,
+        format_mask_group, format_meta, format_outcome, format_row,
+        format_rt_order, format_rt_usage, format_scan_diff, format_scan_json,
+        format_scan_json_v1, format_self_test, format_tally, format_text,
+        format_who, format_who_json, format_who_json_v1, render_null,
+        render_text,
+    },
+    gdb::annotate,
+    meta::{user_names, Field, MetaCache},
+    policy::{check_policy, parse_policy},
+    procfs::{
+        port_owners, proc_bitmap, proc_cgroup, proc_exe, proc_health,
+        proc_matching, proc_name, proc_report, proc_running, proc_signalfds,
+        proc_signals, proc_start_time, proc_status, proc_uid, proc_verify,
+        scan_processes, Target,
+    },
+    report::{Report, Warning},
+    sample::Tally,
+    scan::{
+        dedupe, finding_records, killable, parse_snapshot, rt_usage, scan_diff,
+        summarize_quiet, who, FindingDiff, GroupSummary, IgnoreList, KillGroup,
+        MaskGroup, ScanDiff, SignalUsers, Snapshot,
+    },
+    session::Frame,
+    signals::{
+        normalize_signal_name, parse_bytes, preset, simulate, BitmapType,
+        Condition, Endian, NameStyle, ProcessSignals, RtStyle, SigSet,
+        SigSetDiff, WordOrder,
+    },
+    sink::{FileSink, OutputSink, WebhookSink},
+    unit::{check_unit, parse_unit, unit_name, UnitSignals},
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::RandomState, BTreeMap, BTreeSet, HashMap},
+    fs,
+    hash::{BuildHasher, Hasher},
+    io::{self, BufRead, Error, ErrorKind},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+// Fields of the rows of a scan as tab- or comma-separated values,
+// unless picked.
+static OUTPUT_FIELDS: &[OutputField] = &[
+    OutputField::Pid,
+    OutputField::Comm,
+    OutputField::Map,
+    OutputField::Count,
+    OutputField::Signals,
+];
+
+/// Interpret signal bitmaps for a process.
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about,
+    long_about,
+    args_conflicts_with_subcommands = true,
+    arg_required_else_help = true,
+    disable_version_flag = true
+)]
+pub struct Cli {
+    /// The command to run, if not displaying the bitmap of a process.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Print version.
+    #[arg(short = 'V', long)]
+    pub version: bool,
+
+    /// With `--version`, also list the features compiled in and what the
+    /// host supports.
+    #[arg(long, requires = "version")]
+    pub verbose: bool,
+
+    /// Read the status file of a process from a path other than
+    /// `/proc/{pid}/status` (`{pid}` is replaced with the PID), e.g. on
+    /// systems or containers exposing `/proc` elsewhere.
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    pub status_path: Option<String>,
+
+    /// Arguments for displaying the bitmap of a process.
+    #[command(flatten)]
+    pub args: Option<SigBitmapArgs>,
+}
+
+/// Subcommands.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Report what would happen if a signal were sent to a process.
+    Simulate(SimulateArgs),
+
+    /// Compose a signal bitmap from signal names and presets.
+    Compose(ComposeArgs),
+
+    /// Decode a raw `sigset_t` from a byte dump.
+    Decode(DecodeArgs),
+
+    /// Summarize the signal health of a process.
+    Doctor(DoctorArgs),
+
+    /// Verify the signal bitmaps of a process against a policy.
+    Verify(VerifyArgs),
+
+    /// Report on the signal bitmaps of every process.
+    Scan(ScanArgs),
+
+    /// Sample the signal bitmap of a process over time.
+    Sample(SampleArgs),
+
+    /// List the processes catching, blocking or ignoring a signal.
+    Who(WhoArgs),
+
+    /// Record the bitmaps processes start with, as they start.
+    Audit(AuditArgs),
+
+    /// Replay a watch session recorded with --record.
+    Replay(ReplayArgs),
+
+    /// Merge the outputs of `who --json` collected from several hosts.
+    Merge(MergeArgs),
+
+    /// Show which signal each glyph of `scan --format compact` stands
+    /// for.
+    Legend,
+
+    /// Check the built-in signal table against the host's kernel
+    /// headers.
+    #[command(name = "selftest")]
+    SelfTest,
+
+    /// Annotate the signal bitmaps found in text.
+    ///
+    /// Reads text from the standard input, e.g. logs or pasted
+    /// `/proc/<pid>/status` dumps, appending the signals to each
+    /// bitmap field (`SigBlk:` and friends).
+    Annotate,
+
+    /// Annotate the signal sets in gdb output.
+    ///
+    /// Reads gdb output from the standard input, e.g. with
+    /// `pipe print $_siginfo | sig-bitmap gdb-filter`.
+    GdbFilter,
+}
+
+/// Arguments for composing a signal bitmap.
+#[derive(Args, Debug)]
+pub struct ComposeArgs {
+    /// Signals to add (`NAME`, `+NAME`) or remove (`-NAME`), and presets
+    /// to add (`@NAME`) or remove (`-@NAME`), applied left to right;
+    /// presets: @all, @standard, @all-rt, @termination, @job-control.
+    #[arg(required = true, allow_hyphen_values = true)]
+    pub specs: Vec<String>,
+}
+
+/// Arguments for decoding a raw `sigset_t`.
+#[derive(Args, Debug)]
+pub struct DecodeArgs {
+    /// Hex bytes of the `sigset_t`, e.g. `'00 00 00 00 00 00 40 01'` or
+    /// the output of gdb's `x/8xb`.
+    #[arg(short, long, required_unless_present = "file")]
+    pub bytes: Option<String>,
+
+    /// Binary file holding the `sigset_t`.
+    #[arg(short, long, conflicts_with = "bytes")]
+    pub file: Option<PathBuf>,
+
+    /// Byte order of the `sigset_t`.
+    #[arg(short, long, value_enum, default_value_t = Endian::Little)]
+    pub endian: Endian,
+
+    /// Read the `sigset_t` as two 32-bit words in this order (e.g. from
+    /// a 32-bit system), rather than as a single 64-bit word.
+    #[arg(short, long, value_enum)]
+    pub word_order: Option<WordOrder>,
+
+    /// Name the signals after the table of another architecture, read
+    /// from a file with a signal number and name per line (e.g.
+    /// `16 SIGUSR1`).
+    #[arg(short, long)]
+    pub arch_table: Option<PathBuf>,
+
+    /// Name the signals after the kernel headers installed on the host
+    /// (`asm/signal.h`), rather than the built-in table.
+    #[arg(short = 'H', long, conflicts_with = "arch_table")]
+    pub host_table: bool,
+
+    /// Reverse the bytes of the bitmap once read, e.g. for one that
+    /// looks byte-swapped (which is pointed out otherwise).
+    #[arg(short = 'S', long)]
+    pub assume_swapped: bool,
+
+    /// Fail, rather than name them by number, if signals the table
+    /// doesn't define are set (e.g. in a corrupted capture); every
+    /// signal up to 64 is defined in the built-in table.
+    #[arg(long)]
+    pub strict: bool,
+}
+
+/// Arguments for summarizing the signal health of a process.
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// PID of the process.
+    #[arg(short, long)]
+    pub pid: u32,
+
+    /// Also cross-check the signal settings of the systemd unit file
+    /// running the process (KillSignal=, IgnoreSIGPIPE=, ...).
+    #[arg(short, long)]
+    pub unit: Option<PathBuf>,
+
+    /// Post the findings to an HTTP webhook (`http://` only) as a JSON
+    /// alert with the process and its decoded bitmaps, if any are at
+    /// least as severe as `--alert-severity`.
+    #[arg(long, value_name = "URL")]
+    pub alert_webhook: Option<String>,
+
+    /// Least severity of the findings that fire `--alert-webhook`.
+    #[arg(long, value_enum, default_value_t = Severity::Warning)]
+    pub alert_severity: Severity,
+}
+
+/// Arguments for verifying a process against a signal policy.
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// PID of the process.
+    #[arg(short, long)]
+    pub pid: u32,
+
+    /// Policy file declaring the signals that must (require) or must
+    /// not (forbid) be pending, blocked, ignored or caught.
+    #[arg(long)]
+    pub policy: PathBuf,
+}
+
+/// Arguments for reporting on every process.
+#[derive(Args, Debug)]
+pub struct ScanArgs {
+    /// Report to produce, instead of listing the bitmap of each
+    /// process.
+    #[arg(short, long, value_enum)]
+    pub report: Option<ScanReport>,
+
+    /// Type of bitmap to list.
+    #[arg(short, long, value_enum, default_value_t=BitmapType::SigPnd)]
+    pub map: BitmapType,
+
+    /// Group processes with identical bitmaps, listing each bitmap once
+    /// with the PIDs sharing it.
+    #[arg(short, long, conflicts_with = "report")]
+    pub dedupe: bool,
+
+    /// Summarize the bitmaps and findings per group of processes, with
+    /// the number of processes sharing each.
+    #[arg(short, long, value_enum, conflicts_with_all = ["report", "dedupe"])]
+    pub group_by: Option<GroupBy>,
+
+    /// With `--group-by`, compare the findings against those kept in a
+    /// file by the previous run, listing only the ones that appeared or
+    /// cleared (and exiting with 2 if any did); for scheduled audits.
+    #[arg(short, long, requires = "group_by")]
+    pub state: Option<PathBuf>,
+
+    /// With `--group-by`, leave out the findings of the processes
+    /// matching a pattern in a file (a command name, or a cgroup if it
+    /// starts with `/`, per line; `*` and `?` are wildcards), e.g.
+    /// known-good daemons that block signals on purpose.
+    #[arg(short, long, value_name = "FILE", requires = "group_by")]
+    pub ignore_file: Option<PathBuf>,
+
+    /// Also include kernel threads.
+    #[arg(short, long)]
+    pub kernel_threads: bool,
+
+    /// How to list the bitmap of each process.
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = ScanFormat::Text,
+        conflicts_with_all = ["report", "dedupe", "group_by"]
+    )]
+    pub format: ScanFormat,
+
+    /// Also show metadata of each process listed, e.g. `comm,user`.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "FIELD",
+        conflicts_with_all = ["report", "dedupe", "group_by", "format"]
+    )]
+    pub meta: Vec<Field>,
+
+    /// Fields of each row with `--format tsv` or `csv`, e.g.
+    /// `pid,user,signals` (default: `pid,comm,map,count,signals`).
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "FIELD",
+        conflicts_with_all = ["report", "dedupe", "group_by"]
+    )]
+    pub output_fields: Vec<OutputField>,
+
+    /// Compare against a scan saved with `--format json`, listing the
+    /// processes whose bitmap changed, appeared or disappeared since
+    /// (and exiting with 2 if any did); e.g. around a deployment.
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "report", "dedupe", "group_by", "format", "meta", "output_fields"
+        ]
+    )]
+    pub baseline: Option<PathBuf>,
+
+    /// Append the output to a file instead of the standard output.
+    #[arg(short, long, conflicts_with_all = ["syslog", "webhook"])]
+    pub output: Option<PathBuf>,
+
+    /// Send the output to the local syslog daemon instead, a message per
+    /// line.
+    #[arg(long, conflicts_with = "webhook")]
+    pub syslog: bool,
+
+    /// Post the output to an HTTP webhook instead (`http://` only): JSON
+    /// as is, text wrapped as `{"text": ...}` for Slack-compatible
+    /// receivers.
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+}
+
+/// Reports over every process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ScanReport {
+    /// Processes a `TERM` ends, by cgroup, versus those that handle it or
+    /// need a `KILL`.
+    Killable,
+
+    /// Processes catching or blocking each realtime signal.
+    RtUsage,
+}
+
+/// How to list the bitmap of each process in a scan.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ScanFormat {
+    /// A line with the signals by name.
+    #[default]
+    Text,
+
+    /// A short line with a glyph per signal, at a fixed position (see
+    /// the `legend` command).
+    Compact,
+
+    /// Tab-separated values: a header, then a row per process with the
+    /// fields picked by `--output-fields`.
+    Tsv,
+
+    /// Comma-separated values, as with `tsv`; values with commas are
+    /// quoted.
+    Csv,
+
+    /// A JSON object per process, with all of its bitmaps whatever the
+    /// `--map`, e.g. to save as a baseline for `--baseline`.
+    Json,
+
+    /// A JSON object per process as with `json`, in version 1 of its
+    /// structure, for scripts written against it.
+    #[value(name = "json-v1")]
+    JsonV1,
+}
+
+/// How to list the processes using a signal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum WhoFormat {
+    /// A table with the processes catching, blocking and ignoring it.
+    #[default]
+    Table,
+
+    /// A JSON object per signal, in the current version of its structure.
+    Json,
+
+    /// A JSON object per signal, in version 1 of its structure (without
+    /// `schema_version`), for scripts written against it.
+    #[value(name = "json-v1")]
+    JsonV1,
+}
+
+/// A field of the rows of a scan as tab- or comma-separated values
+/// (`--format tsv` or `csv`); the name of each is its header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputField {
+    /// PID of the process.
+    Pid,
+
+    /// The command name of the process.
+    Comm,
+
+    /// The command line of the process (truncated).
+    Cmdline,
+
+    /// The (real) user running the process, by name if known.
+    User,
+
+    /// The cgroup of the process.
+    Cgroup,
+
+    /// When the process started, in seconds after boot.
+    Start,
+
+    /// Type of the bitmap (e.g. `SigBlk`).
+    Map,
+
+    /// Number of signals in the bitmap.
+    Count,
+
+    /// The bitmap, in hex.
+    Bits,
+
+    /// The signals in the bitmap, by name.
+    Signals,
+}
+
+/// What to group processes by in a scan summary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    /// The cgroup of the process.
+    Cgroup,
+
+    /// The systemd unit (service or scope) of the process.
+    Unit,
+
+    /// The (real) user running the process.
+    User,
+
+    /// The command name of the process.
+    Comm,
+}
+
+/// Arguments for sampling the signal bitmap of a process.
+#[derive(Args, Debug)]
+pub struct SampleArgs {
+    /// PID of the process.
+    #[arg(short, long)]
+    pub pid: u32,
+
+    /// Type of bitmap to sample.
+    #[arg(short, long, value_enum, default_value_t=BitmapType::SigPnd)]
+    pub map: BitmapType,
+
+    /// How long to sample for (e.g. `10s`, `1m`; milliseconds if no unit
+    /// is given).
+    #[arg(short, long, default_value = "10s", value_parser = parse_millis)]
+    pub duration: u64,
+
+    /// Interval between samples (e.g. `100ms`; milliseconds if no unit is
+    /// given).
+    #[arg(short, long, default_value = "100ms", value_parser = parse_millis)]
+    pub interval: u64,
+}
+
+/// Arguments for auditing the bitmaps processes start with.
+#[derive(Args, Debug)]
+pub struct AuditArgs {
+    /// Interval between scans of the processes (e.g. `1s`, `500ms`;
+    /// milliseconds if no unit is given); processes that start and exit
+    /// in between are missed.
+    #[arg(short, long, default_value = "1s", value_parser = parse_millis)]
+    pub interval: u64,
+
+    /// Only record processes with findings (e.g. fault signals blocked,
+    /// termination signals ignored).
+    #[arg(short, long)]
+    pub findings_only: bool,
+
+    /// Also include kernel threads.
+    #[arg(short, long)]
+    pub kernel_threads: bool,
+
+    /// Append the output to a file instead of the standard output.
+    #[arg(short, long, conflicts_with_all = ["syslog", "webhook"])]
+    pub output: Option<PathBuf>,
+
+    /// Send the output to the local syslog daemon instead, a message per
+    /// line.
+    #[arg(long, conflicts_with = "webhook")]
+    pub syslog: bool,
+
+    /// Post the output to an HTTP webhook instead (`http://` only): JSON
+    /// as is, text wrapped as `{"text": ...}` for Slack-compatible
+    /// receivers.
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// Post the findings to an HTTP webhook (`http://` only) as a JSON
+    /// alert with the process and its decoded bitmaps, if any are at
+    /// least as severe as `--alert-severity`.
+    #[arg(long, value_name = "URL")]
+    pub alert_webhook: Option<String>,
+
+    /// Least severity of the findings that fire `--alert-webhook`.
+    #[arg(long, value_enum, default_value_t = Severity::Warning)]
+    pub alert_severity: Severity,
+}
+
+/// Arguments for replaying a recorded watch session.
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Session recorded with `--watch --record`.
+    pub session: PathBuf,
+
+    /// Speed of the replay relative to the session (e.g. `10` for ten
+    /// times as fast), or `0` to replay it without waiting.
+    #[arg(short, long, default_value_t = 1.0, value_parser = parse_speed)]
+    pub speed: f64,
+}
+
+/// Arguments for merging the outputs of `who` from several hosts.
+#[derive(Args, Debug)]
+pub struct MergeArgs {
+    /// Outputs of `who --json`, one per host, named after the host
+    /// (e.g. `web1.json`).
+    #[arg(required = true)]
+    pub reports: Vec<PathBuf>,
+}
+
+/// Arguments for listing the processes using a signal.
+#[derive(Args, Debug)]
+pub struct WhoArgs {
+    /// Signal to look up (name or number, e.g. `USR1`, `SIGUSR1`, `10`);
+    /// may be given several times.
+    #[arg(short, long, required = true, value_parser = parse_signal)]
+    pub signal: Vec<u8>,
+
+    /// How to list the processes using each signal.
+    #[arg(short, long, value_enum, default_value_t = WhoFormat::Table)]
+    pub format: WhoFormat,
+
+    /// Print a JSON object per signal instead of a table (same as
+    /// `--format json`).
+    #[arg(short, long, conflicts_with = "format")]
+    pub json: bool,
+
+    /// Also include kernel threads.
+    #[arg(short, long)]
+    pub kernel_threads: bool,
+}
+
+/// Arguments for simulating the delivery of a signal.
+#[derive(Args, Debug)]
+pub struct SimulateArgs {
+    /// PID of the process.
+    #[arg(short, long)]
+    pub pid: u32,
+
+    /// Signal to send (name or number, e.g. `TERM`, `SIGUSR1`, `15`).
+    #[arg(short, long, value_parser = parse_signal)]
+    pub signal: u8,
+}
+
+/// Display the signal bitmap of a process.
+#[derive(Args, Debug, Default)]
+pub struct SigBitmapArgs {
+    /// PID of the process, or a PID with the command name it should run,
+    /// as copied from kernel logs or the journal (`nginx(1234)`,
+    /// `nginx[1234]` or `1234/nginx`), warning if it runs another one.
+    #[arg(
+        short = 'p',
+        long = "pid",
+        id = "pid",
+        value_name = "PID",
+        required_unless_present_any = ["port", "pids", "name", "exe", "version"],
+        value_parser = parse_target
+    )]
+    pub target: Option<Target>,
+
+    /// PID of the process, picked by `target` or `port`; see
+    /// [`sig_resolve`].
+    #[arg(skip)]
+    pub pid: u32,
+
+    /// Watch several processes at once (comma-separated PIDs).
+    #[arg(long, value_delimiter = ',', requires = "watch", conflicts_with_all = ["pid", "port"])]
+    pub pids: Vec<u32>,
+
+    /// Watch every process whose command name matches a pattern (`*`
+    /// and `?` are wildcards), including ones started while watching.
+    #[arg(short, long, requires = "watch", conflicts_with_all = ["pid", "port", "pids"])]
+    pub name: Option<String>,
+
+    /// Watch every process running an executable (symbolic links are
+    /// resolved), including ones started while watching; more precise
+    /// than `--name` when several binaries share a command name.
+    #[arg(short, long, requires = "watch", conflicts_with_all = ["pid", "port", "pids", "name"])]
+    pub exe: Option<PathBuf>,
+
+    /// Pick the process listening on a TCP or UDP port instead of a PID;
+    /// the lowest PID if several processes share the port.
+    #[arg(short = 'P', long, conflicts_with = "pid")]
+    pub port: Option<u16>,
+
+    /// Type of bitmap to interpret.
+    #[arg(short, long, value_enum, default_value_t=BitmapType::SigPnd)]
+    pub map: BitmapType,
+
+    /// Show the signals pending for the thread (`SigPnd`) and for the
+    /// whole process (`ShdPnd`) together, each marked with where it is
+    /// pending.
+    #[arg(long, conflicts_with_all = ["map", "null", "compare_parent"])]
+    pub pending: bool,
+
+    /// Terminate records with NUL and separate fields with tabs.
+    #[arg(short('0'), long)]
+    pub null: bool,
+
+    /// Name signals with the `SIG` prefix (e.g. `SIGTERM`).
+    #[arg(long)]
+    pub sig_prefix: bool,
+
+    /// How to name realtime signals.
+    #[arg(long, value_enum, default_value_t = RtStyle::Relative)]
+    pub rt_style: RtStyle,
+
+    /// Name signals in lowercase (e.g. `term`).
+    #[arg(long)]
+    pub lowercase: bool,
+
+    /// Also show the parent's bitmap and what the process changed.
+    #[arg(long, conflicts_with = "null")]
+    pub compare_parent: bool,
+
+    /// Also show the bitmap in binary, grouped by 8 bits.
+    #[arg(long, conflicts_with = "null")]
+    pub show_binary: bool,
+
+    /// Also show the bitmap as a grid of bits, with the signal name
+    /// under each set bit.
+    #[arg(short, long, conflicts_with = "null")]
+    pub bits: bool,
+
+    /// Also show the signals the process reads through signalfd
+    /// descriptors, which it blocks on purpose (e.g. event loops)
+    /// rather than handling them with a signal handler.
+    #[arg(long, conflicts_with = "null")]
+    pub signalfd: bool,
+
+    /// Also number the pending realtime signals in the order they are
+    /// delivered, noting that they queue.
+    #[arg(long, conflicts_with = "null")]
+    pub rt_order: bool,
+
+    /// Also show the state of the process (a stopped process acts on no
+    /// signal but KILL), its file mode creation mask, and its tracer
+    /// (e.g. a debugger, which may intercept signals).
+    #[arg(short = 'x', long, conflicts_with = "null")]
+    pub extended: bool,
+
+    /// Keep displaying the bitmap whenever it changes, along with the
+    /// signals added and removed.
+    #[arg(short, long, conflicts_with = "compare_parent")]
+    pub watch: bool,
+
+    /// Stop watching (exiting with 0) once a condition holds, e.g.
+    /// `blk:!TERM` for TERM not blocked; `pnd`, `shd`, `blk`, `ign` or
+    /// `cgt`, a `:` and a signal, negated with `!`. Given several times,
+    /// all of them must hold (for every process watched).
+    #[arg(
+        short,
+        long,
+        value_name = "COND",
+        requires = "watch",
+        value_parser = parse_condition
+    )]
+    pub until: Vec<Condition>,
+
+    /// Interval between samples when watching (milliseconds).
+    #[arg(short, long, default_value_t = 1000, requires = "watch")]
+    pub interval: u64,
+
+    /// Add a random delay of up to this many milliseconds to each
+    /// interval when watching, so that watchers started together (e.g.
+    /// on every host of a fleet) don't read `/proc` in lockstep.
+    #[arg(long, value_name = "MS", default_value_t = 0, requires = "watch")]
+    pub jitter: u64,
+
+    /// Read at most this many processes on each interval when watching
+    /// several, the others taking their turn on the following intervals,
+    /// so that watching thousands of processes doesn't cause a storm of
+    /// `/proc` reads.
+    #[arg(long, value_name = "N", requires = "watch")]
+    pub budget: Option<NonZeroUsize>,
+
+    /// Also show metadata of each process watched, e.g. `comm,user`.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "FIELD",
+        requires = "watch",
+        conflicts_with = "null"
+    )]
+    pub meta: Vec<Field>,
+
+    /// Append the reports written when watching to a file, as JSON
+    /// lines, to replay them later (see the `replay` command).
+    #[arg(short, long, value_name = "FILE", requires = "watch")]
+    pub record: Option<PathBuf>,
+
+    /// Append the output to a file instead of the standard output.
+    #[arg(short, long, conflicts_with_all = ["syslog", "webhook"])]
+    pub output: Option<PathBuf>,
+
+    /// Send the output to the local syslog daemon instead, a message per
+    /// line.
+    #[arg(long, conflicts_with = "webhook")]
+    pub syslog: bool,
+
+    /// Post the output to an HTTP webhook instead (`http://` only): JSON
+    /// as is, text wrapped as `{"text": ...}` for Slack-compatible
+    /// receivers.
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// Exit with 2, naming the signals on the standard error, if any
+    /// are in the bitmap (e.g. to check that a container starts with
+    /// nothing blocked or ignored); a bitmap that can't be read fails
+    /// too.
+    #[arg(long, conflicts_with = "watch")]
+    pub assert_empty: bool,
+}
+
+// Parse a duration for the command line, in milliseconds.
+fn parse_millis(text: &str) -> Result<u64, String> {
+    let (num, mul): (&str, u64) = match text {
+        _ if text.ends_with("ms") => (&text[..text.len() - 2], 1),
+        _ if text.ends_with('s') => (&text[..text.len() - 1], 1000),
+        _ if text.ends_with('m') => (&text[..text.len() - 1], 60_000),
+        _ => (text, 1),
+    };
+
+    num.parse::<u64>()
+        .ok()
+        .and_then(|num| num.checked_mul(mul))
+        .filter(|ms| *ms != 0)
+        .ok_or(format!("invalid duration: {}", text))
+}
+
+// Parse a target process for the command line.
+fn parse_target(text: &str) -> Result<Target, String> {
+    Target::parse(text).map_err(|err| err.to_string())
+}
+
+// Parse a watch condition for the command line.
+fn parse_condition(text: &str) -> Result<Condition, String> {
+    Condition::parse(text).map_err(|err| err.to_string())
+}
+
+// Parse a replay speed for the command line.
+fn parse_speed(text: &str) -> Result<f64, String> {
+    text.parse::<f64>()
+        .ok()
+        .filter(|speed| speed.is_finite() && *speed >= 0.0)
+        .ok_or(format!("invalid speed: {}", text))
+}
+
+// Parse a signal name for the command line.
+fn parse_signal(name: &str) -> Result<u8, String> {
+    normalize_signal_name(name)
+        .map(|sig| sig.number())
+        .map_err(|err| err.to_string())
+}
+
+// Return the piece of metadata a field of a row is, if any.
+fn meta_field(fld: &OutputField) -> Option<Field> {
+    match fld {
+        OutputField::Comm => Some(Field::Comm),
+        OutputField::Cmdline => Some(Field::Cmdline),
+        OutputField::User => Some(Field::User),
+        OutputField::Cgroup => Some(Field::Cgroup),
+        OutputField::Start => Some(Field::Start),
+        OutputField::Pid
+        | OutputField::Map
+        | OutputField::Count
+        | OutputField::Bits
+        | OutputField::Signals => None,
+    }
+}
+
+// Return the style of signal names picked by `args`.
+fn name_style(args: &SigBitmapArgs) -> NameStyle {
+    NameStyle {
+        prefix: args.sig_prefix,
+        rt: args.rt_style,
+        lowercase: args.lowercase,
+    }
+}
+
+/// Displays what would happen if a signal were sent to a process
+/// right now; see [`simulate`] for the rules.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the process
+///   ID (PID) and the signal number.
+///
+/// # Returns
+///
+/// An error if the signal bitmaps of the process can't be read.
+///
+/// # Example
+/// ```
+/// // Check what a `TERM` would do to a process with PID: 42.
+/// use sig_bitmap::{sig_simulate, SimulateArgs};
+/// let args: SimulateArgs = SimulateArgs { pid: 42, signal: 15 };
+/// let _ = sig_simulate(&args);
+/// ````
+pub fn sig_simulate(args: &SimulateArgs) -> Result<(), Error> {
+    let sigs: ProcessSignals = proc_signals(&args.pid)?;
+
+    println!(
+        "{}",
+        format_outcome(&args.pid, &args.signal, &simulate(&args.signal, &sigs))
+    );
+
+    Ok(())
+}
+
+/// Displays the hex bitmap composed from signal names and presets;
+/// see [`SigSet::compose`] for the syntax.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the list of
+///   specifications.
+///
+/// # Returns
+///
+/// An error if a specification doesn't match a signal or a preset.
+///
+/// # Example
+/// ```
+/// // Print the bitmap of every signal except `KILL` and `STOP`.
+/// use sig_bitmap::{sig_compose, ComposeArgs};
+/// let specs: Vec<String> = vec!["@all".into(), "-KILL".into(), "-STOP".into()];
+/// sig_compose(&ComposeArgs { specs }).unwrap();
+/// ````
+pub fn sig_compose(args: &ComposeArgs) -> Result<(), SigError> {
+    let set: SigSet = SigSet::compose(&args.specs)?;
+
+    println!("0x{:016x}", set.bits());
+
+    Ok(())
+}
+
+/// Displays the signals in a raw `sigset_t`, given as hex bytes or read
+/// from a binary file; see [`parse_bytes`] for the accepted text, and
+/// [`SigSet::from_bytes`] and [`SigSet::from_words`] for the layouts.
+/// The signals are named after the table of another architecture if a
+/// table file is given (see [`ArchTable::parse`] for the format), or
+/// after the installed kernel headers with `host_table` set (see
+/// [`host_table`]). A bitmap that looks byte-swapped (see
+/// [`SigSet::looks_swapped`]) is pointed out on the standard error,
+/// unless `assume_swapped` is set to decode it swapped. With `strict`
+/// set, signals the table doesn't define (see [`ArchTable::undefined`])
+/// are an error.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the bytes (or the
+///   path to the file), their byte order and word order, whether to
+///   swap them, the table to name the signals after, and whether to
+///   reject signals it doesn't define.
+///
+/// # Returns
+///
+/// An error if a file can't be read, if the bytes aren't a valid
+/// `sigset_t`, if the table file is malformed, if the kernel headers
+/// aren't installed, or with `strict` set, if the table doesn't define
+/// some of the signals.
+///
+/// # Example
+/// ```
+/// // Decode a big-endian dump holding `HUP` and `TERM`.
+/// use sig_bitmap::{sig_decode, DecodeArgs, Endian};
+/// let args: DecodeArgs = DecodeArgs {
+///     bytes: Some("00 00 00 00 00 00 40 01".into()),
+///     file: None,
+///     endian: Endian::Big,
+///     word_order: None,
+///     arch_table: None,
+///     host_table: false,
+///     assume_swapped: false,
+///     strict: true,
+/// };
+/// sig_decode(&args).unwrap();
+/// ````
+pub fn sig_decode(args: &DecodeArgs) -> Result<(), Error> {
+    let bytes: Vec<u8> = match (&args.bytes, &args.file) {
+        (_, Some(path)) => fs::read(path)?,
+        (Some(text), None) => parse_bytes(text)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?,
+        (None, None) => Vec::new(),
+    };
+    let set: SigSet = match &args.word_order {
+        Some(order) => SigSet::from_words(&bytes, &args.endian, order),
+        None => SigSet::from_bytes(&bytes, &args.endian),
+    }
+    .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+    let set: SigSet = match args.assume_swapped {
+        true => set.swapped(),
+        false => set,
+    };
+
+    if !args.assume_swapped && set.looks_swapped() {
+        eprintln!(
+            "sig-bitmap: looks byte-swapped (see --assume-swapped): {}",
+            format_decoded(&set.swapped().bits())
+        );
+    }
+
+    let table: Option<ArchTable> = match (&args.arch_table, args.host_table) {
+        (Some(path), _) => Some(
+            ArchTable::parse(&fs::read_to_string(path)?)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?,
+        ),
+        (None, true) => Some(host_table()?),
+        (None, false) => None,
+    };
+    let undefined: Vec<u8> = match &table {
+        Some(table) if args.strict => table.undefined(&set.bits()),
+        _ => Vec::new(),
+    };
+
+    if !undefined.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "signals not in the table: {}",
+                undefined
+                    .iter()
+                    .map(u8::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        ));
+    }
+
+    match &table {
+        Some(table) => {
+            println!("{}", format_decoded_arch(&set.bits(), table))
+        }
+        None => println!("{}", format_decoded(&set.bits())),
+    }
+
+    Ok(())
+}
+
+/// Displays the version, and with `verbose` set, the optional features
+/// compiled in (see [`FEATURES`]), followed by what the host supports:
+/// reading `/proc`, and the signal table in its kernel headers (see
+/// [`host_table`]).
+///
+/// # Arguments
+///
+/// * `verbose` - Whether to list the features and host support.
+///
+/// # Example
+/// ```
+/// // Print what this build can do.
+/// use sig_bitmap::sig_version;
+/// sig_version(true);
+/// ````
+pub fn sig_version(verbose: bool) {
+    println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+
+    if !verbose {
+        return;
+    }
+
+    let yes = |on: bool| -> &str {
+        match on {
+            true => "yes",
+            false => "no",
+        }
+    };
+
+    println!("Features:");
+    for ftr in FEATURES {
+        println!("    {:<8} {:<4} {}", ftr.name, yes(ftr.enabled), ftr.about);
+    }
+
+    let host: [(&str, bool, &str); 2] = [
+        (
+            "procfs",
+            fs::metadata("/proc/self/status").is_ok(),
+            "Signal bitmaps in /proc/<pid>/status",
+        ),
+        (
+            "headers",
+            host_table().is_ok(),
+            "Signal table in the kernel headers (decode --host-table)",
+        ),
+    ];
+
+    println!("Host:");
+    for (name, on, about) in host {
+        println!("    {:<8} {:<4} {}", name, yes(on), about);
+    }
+}
+
+/// Displays the legend of the compact view of a scan (see
+/// [`format_legend`]): which signal each glyph stands for.
+pub fn sig_legend() {
+    println!("{}", format_legend());
+}
+
+/// Displays the differences between the built-in signal table and that
+/// of the host (see [`self_test`]), so that users can tell whether the
+/// decoded names match their platform.
+///
+/// # Returns
+///
+/// Whether there are any differences, or an error if the kernel headers
+/// aren't installed.
+pub fn sig_self_test() -> Result<bool, Error> {
+    let diffs: Vec<Mismatch> = self_test()?;
+
+    println!("{}", format_self_test(&diffs));
+    Ok(!diffs.is_empty())
+}
+
+/// Copies text from the standard input to the standard output, with
+/// the signals of the bitmap fields in it appended to them; see
+/// [`annotate_masks`].
+///
+/// # Returns
+///
+/// An error if the standard input can't be read.
+///
+/// # Example
+/// ```
+/// // Annotate a log file.
+/// // sig-bitmap annotate < app.log
+/// ````
+pub fn sig_annotate() -> Result<(), Error> {
+    for line in io::stdin().lock().lines() {
+        println!("{}", annotate_masks(&line?));
+    }
+
+    Ok(())
+}
+
+/// Copies gdb output from the standard input to the standard output,
+/// with the signals of the signal sets and numbers in it appended to
+/// them; see [`annotate`].
+///
+/// # Returns
+///
+/// An error if the standard input can't be read.
+///
+/// # Example
+/// ```
+/// // In `.gdbinit`, print a signal set with its signals.
+/// // define psig
+/// //   pipe print $arg0 | sig-bitmap gdb-filter
+/// // end
+/// ````
+pub fn sig_gdb_filter() -> Result<(), Error> {
+    for line in io::stdin().lock().lines() {
+        println!("{}", annotate(&line?));
+    }
+
+    Ok(())
+}
+
+/// Displays the standard signals of a process left to their default
+/// action (see [`ProcessSignals::default_disposition`]), followed by
+/// the findings of the signal health checks, most severe first, or `OK`
+/// if there are none; see [`diagnose`] for the checks, and
+/// [`check_unit`] for those of a systemd unit file. With
+/// `alert_webhook`, the findings at least as severe as `alert_severity`
+/// are also posted to the webhook, if there are any (see
+/// [`format_alert`]).
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the process ID (PID),
+///   and optionally, the path to a unit file and the webhook to alert.
+///
+/// # Returns
+///
+/// An error if the signal bitmaps of the process or the unit file can't
+/// be read, if a signal setting in the unit file is invalid, or if the
+/// alert can't be posted to the webhook.
+///
+/// # Example
+/// ```
+/// // Check the signal health of a process with PID: 42.
+/// use sig_bitmap::{doctor::Severity, sig_doctor, DoctorArgs};
+/// let _ = sig_doctor(&DoctorArgs {
+///     pid: 42,
+///     unit: None,
+///     alert_webhook: None,
+///     alert_severity: Severity::Warning,
+/// });
+/// ````
+pub fn sig_doctor(args: &DoctorArgs) -> Result<(), Error> {
+    let health: Health = proc_health(&args.pid)?;
+    let mut findings: Vec<Finding> = diagnose(&health);
+
+    if let Some(path) = &args.unit {
+        let unit: UnitSignals = parse_unit(&fs::read_to_string(path)?)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+
+        findings.extend(check_unit(&unit, &health.signals));
+        findings.sort_by_key(|fnd| Reverse(fnd.severity));
+    }
+
+    // Realtime signals are left out; they are rarely sent unprompted.
+    let dfl: SigSet = health
+        .signals
+        .default_disposition()
+        .intersection(&preset("standard").unwrap_or_default());
+    println!("{}", format_default(&args.pid, &dfl.bits()));
+
+    if findings.is_empty() {
+        println!("PID: {:<6} OK", args.pid);
+    }
+
+    for fnd in &findings {
+        println!("{}", format_finding(&args.pid, fnd));
+    }
+
+    if let Some(url) = &args.alert_webhook {
+        alert(
+            &mut WebhookSink::new(url)?,
+            &args.alert_severity,
+            &proc_start_time(&args.pid).unwrap_or_default(),
+            &program(&args.pid).unwrap_or_else(|| "?".into()),
+            &health.signals,
+            &findings,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Verifies the signal bitmaps of a process against a policy file (see
+/// [`parse_policy`]), displaying each violation as a critical finding.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the process ID (PID)
+///   and the path to the policy file.
+///
+/// # Returns
+///
+/// Whether the process violates the policy, or an error if the policy
+/// file can't be read or is invalid, or if the signal bitmaps of the
+/// process can't be read.
+///
+/// # Example
+/// ```
+/// // Verify a process with PID: 42 against a policy.
+/// use sig_bitmap::{sig_verify, VerifyArgs};
+/// let args: VerifyArgs = VerifyArgs {
+///     pid: 42,
+///     policy: "policy.toml".into(),
+/// };
+/// let _ = sig_verify(&args);
+/// ````
+pub fn sig_verify(args: &VerifyArgs) -> Result<bool, Error> {
+    let policy: Vec<Condition> =
+        parse_policy(&fs::read_to_string(&args.policy)?)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+    let findings: Vec<Finding> =
+        check_policy(&policy, &proc_signals(&args.pid)?);
+
+    if findings.is_empty() {
+        println!("PID: {:<6} OK", args.pid);
+    }
+
+    for fnd in &findings {
+        println!("{}", format_finding(&args.pid, fnd));
+    }
+
+    Ok(!findings.is_empty())
+}
+
+/// Samples the specified type of signal bitmap of a process every
+/// `interval` milliseconds for `duration` milliseconds (or until the
+/// process exits), then displays the signals seen in any sample, how
+/// many samples each was seen in, and roughly for how long; see
+/// [`format_tally`]. Unlike a single snapshot, this catches signals
+/// that are pending only briefly.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the process ID (PID),
+///   the signal bitmap type, the duration and the sampling interval.
+///
+/// # Returns
+///
+/// An error if the signal bitmaps of the process can't be read at the
+/// start.
+///
+/// # Example
+/// ```
+/// // Sample the pending signals of this process for a second.
+/// use sig_bitmap::{sig_sample, BitmapType, SampleArgs};
+/// let args: SampleArgs = SampleArgs {
+///     pid: std::process::id(),
+///     map: BitmapType::SigPnd,
+///     duration: 1000,
+///     interval: 100,
+/// };
+/// sig_sample(&args).unwrap();
+/// ````
+pub fn sig_sample(args: &SampleArgs) -> Result<(), Error> {
+    let mut tally: Tally = Tally::default();
+    let start: Instant = Instant::now();
+    let mut sigs: ProcessSignals = proc_signals(&args.pid)?;
+
+    loop {
+        tally.add(&sigs.bitmap(&args.map));
+
+        if start.elapsed() + Duration::from_millis(args.interval)
+            > Duration::from_millis(args.duration)
+        {
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(args.interval));
+        sigs = match proc_signals(&args.pid) {
+            Ok(sigs) => sigs,
+            Err(_) => {
+                eprintln!("sig-bitmap: {}: process exited", args.pid);
+                break;
+            }
+        };
+    }
+
+    println!(
+        "{}",
+        format_tally(&args.pid, &args.map, &tally, &args.interval)
+    );
+
+    Ok(())
+}
+
+/// Writes the specified type of signal bitmap of every process (see
+/// [`scan_processes`]) to a sink, one line per process, or with `dedupe` set,
+/// once per distinct bitmap along with the PIDs sharing it (see
+/// [`dedupe`]). Processes listed one per line are followed by the
+/// metadata picked by `meta` (see [`MetaCache`]), or as tab- or
+/// comma-separated values with the fields picked by `output_fields`
+/// (see [`OutputField`]), after a header naming them. With `group_by`
+/// set, the bitmaps and the findings of the checks are summarized per
+/// group instead (see [`summarize_quiet`]); processes whose group can't
+/// be told are grouped under `?`, and the findings of those matching
+/// the patterns in `ignore_file` are left out (see [`IgnoreList`]).
+///
+/// With `state` set as well, the findings are compared against those
+/// kept in the file (see [`finding_records`]), which is then updated,
+/// and only the ones that appeared or cleared are displayed (see
+/// [`FindingDiff`]); a missing file counts as no findings.
+///
+/// With `format` set to `json`, each process is written as a JSON object
+/// with all of its bitmaps (see [`format_scan_json`]), which `baseline`
+/// reads back: the processes whose bitmap changed, appeared or
+/// disappeared since that scan are displayed instead (see
+/// [`scan_diff`]).
+///
+/// With a report picked instead, the `killable` report groups processes
+/// by cgroup (see [`proc_cgroup`]), listing those a `TERM` ends, those
+/// handling it, and those needing a `KILL`; see [`killable`]. The
+/// `rt-usage` report counts the processes catching and blocking each
+/// realtime signal; see [`rt_usage`].
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the report (or the
+///   signal bitmap type), whether to dedupe or group the bitmaps, the
+///   state file, the ignore file, the baseline, and whether to include
+///   kernel threads.
+/// * `sink` - Where the output is written, at once when the scan is
+///   done.
+///
+/// # Returns
+///
+/// Whether the findings changed since the state file was written, or
+/// the processes since the baseline (`false` without either), or an
+/// error if `/proc`, the state file, the ignore file or the baseline
+/// can't be read, if the state file can't be written, if the output
+/// can't be written to the sink, or if `output_fields` are picked for a
+/// format other than `tsv` or `csv`.
+///
+/// # Example
+/// ```
+/// // List the processes that would survive a `TERM`.
+/// use sig_bitmap::{
+///     sig_scan, sink::StdoutSink, BitmapType, OutputField, ScanArgs,
+///     ScanFormat, ScanReport,
+/// };
+/// let args: ScanArgs = ScanArgs {
+///     report: Some(ScanReport::Killable),
+///     map: BitmapType::SigPnd,
+///     dedupe: false,
+///     group_by: None,
+///     state: None,
+///     ignore_file: None,
+///     kernel_threads: false,
+///     format: ScanFormat::Text,
+///     meta: vec![],
+///     output_fields: vec![],
+///     baseline: None,
+///     output: None,
+///     syslog: false,
+///     webhook: None,
+/// };
+/// sig_scan(&args, &mut StdoutSink).unwrap();
+///
+/// // Fields are only picked for tab- or comma-separated values.
+/// let args: ScanArgs = ScanArgs {
+///     report: None,
+///     format: ScanFormat::Compact,
+///     output_fields: vec![OutputField::Pid],
+///     ..args
+/// };
+/// assert!(sig_scan(&args, &mut StdoutSink).is_err());
+/// ````
+pub fn sig_scan(
+    args: &ScanArgs,
+    sink: &mut dyn OutputSink,
+) -> Result<bool, Error> {
+    if !args.output_fields.is_empty()
+        && !matches!(args.format, ScanFormat::Tsv | ScanFormat::Csv)
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "--output-fields needs --format tsv or csv",
+        ));
+    }
+
+    let procs = scan_processes(args.kernel_threads)?;
+    let mut out: String = String::new();
+
+    // Saved and compared scans leave out this process, which is a new
+    // one every run.
+    let own: u32 = std::process::id();
+
+    if let Some(path) = &args.baseline {
+        let snaps: Vec<Snapshot> = procs
+            .filter(|(pid, _)| *pid != own)
+            .map(|(pid, sigs)| snapshot(pid, sigs))
+            .collect();
+        return baseline(path, &snaps, &args.map, sink);
+    }
+
+    match (&args.report, args.dedupe, &args.group_by) {
+        (Some(ScanReport::Killable), _, _) => {
+            let groups: Vec<KillGroup> = killable(procs.map(|(pid, sigs)| {
+                let cgroup: String =
+                    proc_cgroup(&pid).unwrap_or_else(|| "?".into());
+                (pid, cgroup, sigs)
+            }));
+
+            for group in &groups {
+                out.push_str(&format!("{}\n", format_killable(group)));
+            }
+        }
+        (Some(ScanReport::RtUsage), _, _) => {
+            for rt in rt_usage(procs.map(|(_, sigs)| sigs)) {
+                out.push_str(&format!("{}\n", format_rt_usage(&rt)));
+            }
+        }
+        (None, _, Some(key)) => {
+            let users: HashMap<u32, String> = match key {
+                GroupBy::User => user_names(),
+                _ => HashMap::new(),
+            };
+            let ignore: IgnoreList = match &args.ignore_file {
+                Some(path) => IgnoreList::parse(&fs::read_to_string(path)?),
+                None => IgnoreList::default(),
+            };
+            let groups: Vec<GroupSummary> = summarize_quiet(
+                procs.map(|(pid, sigs)| {
+                    let group: Option<String> = match key {
+                        GroupBy::Cgroup => proc_cgroup(&pid),
+                        GroupBy::Unit => proc_cgroup(&pid)
+                            .and_then(|cg| unit_name(&cg).map(String::from)),
+                        GroupBy::User => proc_uid(&pid).map(|uid| {
+                            users.get(&uid).cloned().unwrap_or(uid.to_string())
+                        }),
+                        GroupBy::Comm => proc_name(&pid),
+                    };
+                    (pid, group.unwrap_or_else(|| "?".into()), sigs)
+                }),
+                &args.map,
+                |pid| {
+                    !ignore.is_empty()
+                        && ignore.matches(
+                            &proc_name(pid).unwrap_or_default(),
+                            &proc_cgroup(pid).unwrap_or_default(),
+                        )
+                },
+            );
+
+            if let Some(path) = &args.state {
+                return audit(path, &groups, sink);
+            }
+
+            for group in &groups {
+                out.push_str(&format!(
+                    "{}\n",
+                    format_group_summary(&args.map, group)
+                ));
+            }
+        }
+        (None, true, None) => {
+            let groups: Vec<MaskGroup> =
+                dedupe(procs.map(|(pid, sigs)| (pid, sigs.bitmap(&args.map))));
+
+            for group in &groups {
+                out.push_str(&format!(
+                    "{}\n",
+                    format_mask_group(&args.map, group)
+                ));
+            }
+        }
+        (None, false, None) if args.format == ScanFormat::Compact => {
+            out.push_str(&format!("{}\n", format_compact_ruler(&args.map)));
+
+            for (pid, sigs) in procs {
+                out.push_str(&format!(
+                    "{}\n",
+                    format_compact(&pid, &sigs.bitmap(&args.map).bits())
+                ));
+            }
+        }
+        (None, false, None)
+            if matches!(args.format, ScanFormat::Json | ScanFormat::JsonV1) =>
+        {
+            let json: fn(&Snapshot) -> String = match args.format {
+                ScanFormat::JsonV1 => format_scan_json_v1,
+                _ => format_scan_json,
+            };
+
+            for (pid, sigs) in procs.filter(|(pid, _)| *pid != own) {
+                out.push_str(&format!("{}\n", json(&snapshot(pid, sigs))));
+            }
+        }
+        (None, false, None)
+            if matches!(args.format, ScanFormat::Tsv | ScanFormat::Csv) =>
+        {
+            let sep: char = match args.format {
+                ScanFormat::Csv => ',',
+                _ => '\t',
+            };
+            let fields: &[OutputField] = match args.output_fields.is_empty() {
+                true => OUTPUT_FIELDS,
+                false => &args.output_fields,
+            };
+            let meta: Vec<Field> =
+                fields.iter().filter_map(meta_field).collect();
+            let header: Vec<String> = fields
+                .iter()
+                .filter_map(|fld| fld.to_possible_value())
+                .map(|val| val.get_name().to_string())
+                .collect();
+            let mut cache: MetaCache = MetaCache::default();
+
+            out.push_str(&format!("{}\n", format_row(&header, sep)));
+
+            for (pid, sigs) in procs {
+                let set: SigSet = sigs.bitmap(&args.map);
+                let mut vals = cache.lookup(&pid, &meta).into_iter();
+                let row: Vec<String> = fields
+                    .iter()
+                    .map(|fld| match fld {
+                        OutputField::Pid => pid.to_string(),
+                        OutputField::Map => {
+                            args.map.to_string().trim_end_matches(':').into()
+                        }
+                        OutputField::Count => set.len().to_string(),
+                        OutputField::Bits => format!("0x{:016x}", set.bits()),
+                        OutputField::Signals => set.to_names().join(", "),
+                        _ => {
+                            vals.next().map(|(_, val)| val).unwrap_or_default()
+                        }
+                    })
+                    .collect();
+
+                out.push_str(&format!("{}\n", format_row(&row, sep)));
+            }
+        }
+        (None, false, None) => {
+            let mut cache: MetaCache = MetaCache::default();
+
+            for (pid, sigs) in procs {
+                let bits: u64 = sigs.bitmap(&args.map).bits();
+                out.push_str(&format!(
+                    "{}\n",
+                    format_text(&pid, &args.map, &bits, &NameStyle::default())
+                ));
+
+                if !args.meta.is_empty() {
+                    out.push_str(&format!(
+                        "{}\n",
+                        format_meta(&cache.lookup(&pid, &args.meta))
+                    ));
+                }
+            }
+        }
+    }
+
+    sink.emit(&out)?;
+    Ok(false)
+}
+
+/// Writes the bitmaps that processes start with to a sink, scanning the
+/// processes every `interval` milliseconds (see [`Tracker`]) until
+/// interrupted: for each process started (forked) or running a new
+/// program (exec'd) since the previous scan, the program (the path of
+/// its executable, or its command name in brackets if that can't be
+/// read), its blocked, ignored and caught signals, and the findings of
+/// the checks that need only the bitmaps (see [`diagnose`]). This
+/// builds an audit trail of the programs that start with dangerous
+/// bitmaps, e.g. inherited from a careless parent. With
+/// `alert_webhook`, findings at least as severe as `alert_severity` are
+/// also posted to the webhook (see [`format_alert`]); an alert that
+/// can't be posted is reported on stderr, and the audit goes on.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the scan interval,
+///   whether to record processes with findings only, whether to
+///   include kernel threads, and optionally, the webhook to alert.
+/// * `sink` - Where the output is written.
+///
+/// # Returns
+///
+/// An error if `/proc` can't be read, if the webhook URL is invalid, or
+/// if the output can't be written to the sink.
+pub fn sig_audit(
+    args: &AuditArgs,
+    sink: &mut dyn OutputSink,
+) -> Result<(), Error> {
+    let mut tracker: Tracker = Tracker::default();
+    let mut hook: Option<WebhookSink> = args
+        .alert_webhook
+        .as_deref()
+        .map(WebhookSink::new)
+        .transpose()?;
+
+    loop {
+        let procs: HashMap<u32, (u64, String, ProcessSignals)> =
+            scan_processes(args.kernel_threads)?
+                .filter_map(|(pid, sigs)| {
+                    Some((pid, (proc_start_time(&pid)?, program(&pid)?, sigs)))
+                })
+                .collect();
+        let events: Vec<(u32, Event)> = tracker.update(
+            procs
+                .iter()
+                .map(|(pid, (start, prog, _))| (*pid, *start, prog.clone())),
+        );
+
+        for (pid, event) in events {
+            let (start, prog, sigs) = &procs[&pid];
+            let findings: Vec<Finding> = diagnose(&Health {
+                signals: *sigs,
+                ..Default::default()
+            });
+
+            // A webhook that can't be reached doesn't stop the audit.
+            if let Some(hook) = hook.as_mut() {
+                if let Err(err) = alert(
+                    hook,
+                    &args.alert_severity,
+                    start,
+                    prog,
+                    sigs,
+                    &findings,
+                ) {
+                    eprintln!("sig-bitmap: {}: alert: {}", pid, err);
+                }
+            }
+
+            if args.findings_only && findings.is_empty() {
+                continue;
+            }
+
+            sink.emit(&format!(
+                "{}\n",
+                format_audit(&pid, &event, prog, sigs, &findings)
+            ))?;
+        }
+
+        thread::sleep(Duration::from_millis(args.interval));
+    }
+}
+
+/// Displays the processes catching, blocking or ignoring each signal
+/// (see [`who`]), the reverse of the per-process view: as a table, or
+/// with `json` set, as a JSON object per line.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the signal numbers,
+///   the output format, and whether to include kernel threads.
+///
+/// # Returns
+///
+/// An error if `/proc` can't be read.
+///
+/// # Example
+/// ```
+/// // List the processes catching, blocking or ignoring `USR1`.
+/// use sig_bitmap::{sig_who, WhoArgs, WhoFormat};
+/// let args: WhoArgs = WhoArgs {
+///     signal: vec![10],
+///     format: WhoFormat::Table,
+///     json: false,
+///     kernel_threads: false,
+/// };
+/// sig_who(&args).unwrap();
+/// ````
+pub fn sig_who(args: &WhoArgs) -> Result<(), Error> {
+    let procs: Vec<(u32, ProcessSignals)> =
+        scan_processes(args.kernel_threads)?.collect();
+
+    for idx in &args.signal {
+        let users: SignalUsers = who(idx, procs.iter().copied());
+
+        match (args.json, args.format) {
+            (true, _) | (_, WhoFormat::Json) => {
+                println!("{}", format_who_json(&users))
+            }
+            (_, WhoFormat::JsonV1) => {
+                println!("{}", format_who_json_v1(&users))
+            }
+            (_, WhoFormat::Table) => println!("{}", format_who(&users)),
+        }
+    }
+
+    Ok(())
+}
+
+// Return the program a process runs: the path of its executable, or its
+// command name in brackets if that can't be read.
+fn program(pid: &u32) -> Option<String> {
+    match proc_exe(pid) {
+        Some(exe) => Some(exe.to_string_lossy().into_owned()),
+        None => Some(format!("[{}]", proc_name(pid)?)),
+    }
+}
+
+// Post an alert for the findings about a process that are at least as
+// severe as `least` to a webhook, if there are any.
+fn alert(
+    hook: &mut WebhookSink,
+    least: &Severity,
+    start: &u64,
+    prog: &str,
+    sigs: &ProcessSignals,
+    findings: &[Finding],
+) -> Result<(), Error> {
+    let severe: Vec<Finding> = findings
+        .iter()
+        .filter(|fnd| fnd.severity >= *least)
+        .cloned()
+        .collect();
+
+    if severe.is_empty() {
+        return Ok(());
+    }
+
+    hook.emit(&format!("{}\n", format_alert(start, prog, sigs, &severe)))
+}
+
+// Return a process in a scan, to save or to compare against a saved
+// scan.
+fn snapshot(pid: u32, signals: ProcessSignals) -> Snapshot {
+    Snapshot {
+        pid,
+        start: proc_start_time(&pid).unwrap_or_default(),
+        comm: proc_name(&pid).unwrap_or_else(|| "?".into()),
+        signals,
+    }
+}
+
+// Display the processes whose bitmap changed, appeared or disappeared
+// since the scan saved in the baseline file; returns whether any did.
+fn baseline(
+    path: &Path,
+    snaps: &[Snapshot],
+    typ: &BitmapType,
+    sink: &mut dyn OutputSink,
+) -> Result<bool, Error> {
+    let old: Vec<Snapshot> = fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            parse_snapshot(line)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+        })
+        .collect::<Result<Vec<Snapshot>, Error>>()?;
+    let diff: ScanDiff = scan_diff(&old, snaps, typ);
+
+    if diff.is_empty() {
+        return Ok(false);
+    }
+
+    sink.emit(&format!("{}\n", format_scan_diff(typ, &diff)))?;
+    Ok(true)
+}
+
+// Display the findings that appeared or cleared since those kept in the
+// state file, and keep the current ones in it; returns whether any did.
+fn audit(
+    path: &Path,
+    groups: &[GroupSummary],
+    sink: &mut dyn OutputSink,
+) -> Result<bool, Error> {
+    let old: Vec<String> = match fs::read_to_string(path) {
+        Ok(text) => text.lines().map(String::from).collect(),
+        Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err),
+    };
+    let new: Vec<String> = finding_records(groups);
+    let diff: FindingDiff = FindingDiff::new(&old, &new);
+
+    if diff.is_empty() {
+        return Ok(false);
+    }
+
+    sink.emit(&format!("{}\n", format_finding_diff(&diff)))?;
+    fs::write(
+        path,
+        new.iter()
+            .map(|rec| format!("{}\n", rec))
+            .collect::<String>(),
+    )?;
+
+    Ok(true)
+}
+
+/// Returns the report of the specified type of signal bitmap for a
+/// given process, to be rendered with [`sig_render`]. The bitmap is
+/// empty if the process doesn't exist or if there is an error
+/// interpreting the signal bitmap; the report carries a warning then.
+///
+/// With `compare_parent` set, the report holds the bitmap of the parent
+/// process as well, to show the signals the process added (`+`) or
+/// removed (`-`) relative to it, e.g. after changing its mask across
+/// fork/exec; it has no parent if the process doesn't have one. With
+/// `extended` set, it holds the state, file mode creation mask and tracer
+/// of the process as well (see [`proc_status`]), and a warning if it is
+/// traced.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the process
+///   ID (PID) and the signal bitmap type.
+///
+/// # Example
+/// ```
+/// // Print the list of signals ignored by a process with PID: 42.
+/// use sig_bitmap::{sig_bitmap, sig_render, BitmapType, Report, SigBitmapArgs};
+/// let args: SigBitmapArgs = SigBitmapArgs {
+///     pid: 42,
+///     map: BitmapType::SigIgn,
+///     ..Default::default()
+/// };
+/// let report: Report = sig_bitmap(&args);
+/// print!("{}", sig_render(&args, &report));
+/// ````
+pub fn sig_bitmap(args: &SigBitmapArgs) -> Report {
+    pid_report(args, &args.pid)
+}
+
+// Return the report for a process, as picked by `args` (but for `pid`).
+fn pid_report(args: &SigBitmapArgs, pid: &u32) -> Report {
+    let mut report: Report = proc_report(pid, &args.map, args.compare_parent);
+
+    if let Some(warn) = args
+        .target
+        .as_ref()
+        .filter(|tgt| tgt.pid == *pid)
+        .and_then(proc_verify)
+    {
+        report.warnings.push(warn);
+    }
+
+    if args.pending {
+        report.shared =
+            Some(SigSet::from_bits(proc_bitmap(pid, &BitmapType::ShdPnd)));
+    }
+
+    if args.signalfd {
+        match proc_signalfds(pid) {
+            Ok(fds) => {
+                report.signalfd = Some(
+                    fds.iter()
+                        .fold(SigSet::empty(), |all, (_, set)| all.union(set)),
+                )
+            }
+            Err(_) => report.warnings.push(Warning::FdsUnreadable(*pid)),
+        }
+    }
+
+    if args.extended {
+        report.status = proc_status(pid);
+
+        if let Some(tpid) = report.status.as_ref().and_then(|st| st.tracer) {
+            report.warnings.push(Warning::Traced(tpid));
+        }
+    }
+
+    report
+}
+
+/// Returns the arguments with the PID of the process filled in: the
+/// PID of `target`, or if a port was picked instead, the PID of the
+/// process listening on `port`; the lowest PID is taken if several
+/// processes share the port.
+///
+/// # Returns
+///
+/// An error if the sockets can't be read, or if no (visible) process
+/// listens on the port.
+pub fn sig_resolve(args: SigBitmapArgs) -> Result<SigBitmapArgs, Error> {
+    let port: u16 = match (&args.target, args.port) {
+        (Some(target), _) => {
+            let pid: u32 = target.pid;
+            return Ok(SigBitmapArgs { pid, ..args });
+        }
+        (None, Some(port)) => port,
+        (None, None) => return Ok(args),
+    };
+
+    match port_owners(&port)?.first() {
+        Some(pid) => Ok(SigBitmapArgs { pid: *pid, ..args }),
+        None => Err(Error::new(
+            ErrorKind::NotFound,
+            format!("no process listening on port {}", port),
+        )),
+    }
+}
+
+/// Returns the rendering of a report in the format picked by `args`:
+/// with `null` set, a single machine-readable record (see
+/// [`render_null`]): the PID, bitmap type, signal count, hex bitmap
+/// and comma-separated signal names, separated by tabs and terminated
+/// by a NUL byte; otherwise human-readable lines (see [`render_text`]),
+/// followed by the bitmap in binary (see [`format_binary`]) with
+/// `show_binary` set, and a grid of the bits (see [`format_bits`]) with
+/// `bits` set.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the output format.
+/// * `report` - A reference to the report to render.
+pub fn sig_render(args: &SigBitmapArgs, report: &Report) -> String {
+    if args.null {
+        return render_null(report, &name_style(args));
+    }
+
+    let mut out: String = render_text(report, &name_style(args));
+
+    if args.show_binary {
+        out.push_str(&format_binary(&report.map, &report.merged().bits()));
+        out.push('\n');
+    }
+
+    if args.bits {
+        out.push_str(&format_bits(&report.merged().bits()));
+        out.push('\n');
+    }
+
+    let pending: bool =
+        matches!(report.map, BitmapType::SigPnd | BitmapType::ShdPnd);
+
+    if args.rt_order && pending {
+        let style: NameStyle = name_style(args);
+
+        if let Some(order) =
+            format_rt_order(&report.map, &report.merged(), &style)
+        {
+            out.push_str(&order);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Writes the specified type of signal bitmap for a given process
+/// (rendered with [`sig_render`]) to a sink every time it changes,
+/// sampling it every `interval` milliseconds until the process exits.
+/// In the human-readable format, each change is followed by the signals
+/// added and removed since the previous sample (see [`SigSet::diff`]).
+/// If the PID is recycled by a different process in between samples,
+/// this is flagged and the new process is watched from then on.
+///
+/// Several processes can be watched at once: those in `pids`, until
+/// they have all exited, or those whose command name matches `name`
+/// (see [`proc_matching`]) or running the executable `exe` (see
+/// [`proc_running`]), picking up new ones as they start. They are
+/// sampled in turn on every interval, so that the changes are written
+/// as a single stream in the order they were seen, each tagged with the
+/// PID.
+///
+/// With conditions in `until` (see [`Condition`]), watching stops once
+/// all of them hold for every process watched, e.g. to wait for a
+/// process to unblock `TERM`.
+///
+/// Watching goes on until the process is interrupted: `SIGTERM` and
+/// `SIGINT` are left to their default action, ending it at once, since
+/// installing a handler takes `sigaction(2)`, which this crate can't
+/// reach without `unsafe` code or a dependency (e.g. `libc` or
+/// `signal-hook`). Nothing needs flushing then: each record is written
+/// whole to `record` as soon as the report changes, so that a session
+/// cut short replays up to its last change (see [`sig_replay`]).
+///
+/// To spare hosts with many processes, at most `budget` of them are read
+/// on each interval, the others taking their turn on the following
+/// intervals, and each interval is lengthened by a random delay of up to
+/// `jitter` milliseconds.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the process
+///   ID (PID) or the processes to watch, the signal bitmap type, the
+///   sampling interval, jitter and budget, and the conditions to stop
+///   at.
+/// * `sink` - Where the output is written.
+///
+/// # Returns
+///
+/// An error if the output can't be written to the sink, if `/proc`
+/// can't be read to match process names or executables, or with
+/// conditions, if the processes exit before they hold.
+pub fn sig_watch(
+    args: &SigBitmapArgs,
+    sink: &mut dyn OutputSink,
+) -> Result<(), Error> {
+    // Samples are tagged with the start time of the process, so that
+    // a recycled PID isn't reported as a change of the bitmap.
+    let mut last: BTreeMap<u32, (u64, Report)> = BTreeMap::new();
+    let mut first: bool = true;
+    let began: Instant = Instant::now();
+    let mut record: Option<FileSink> =
+        args.record.as_deref().map(FileSink::append).transpose()?;
+    let mut cache: MetaCache = MetaCache::default();
+    // Processes whose conditions held when they were last read.
+    let mut held: BTreeSet<u32> = BTreeSet::new();
+    // Index of the process whose turn it is to be read next.
+    let mut cursor: usize = 0;
+
+    loop {
+        let pids: Vec<u32> = match (&args.name, &args.exe) {
+            (Some(name), _) => proc_matching(name)?,
+            (None, Some(exe)) => proc_running(exe)?,
+            (None, None) if !args.pids.is_empty() => args.pids.clone(),
+            (None, None) => vec![args.pid],
+        };
+        let gone: Vec<u32> = last
+            .keys()
+            .filter(|pid| !pids.contains(pid))
+            .copied()
+            .collect();
+
+        for pid in gone {
+            last.remove(&pid);
+            held.remove(&pid);
+            eprintln!("sig-bitmap: {}: process exited", pid);
+        }
+
+        let from: usize = cursor % pids.len().max(1);
+        let count: usize = args
+            .budget
+            .map_or(pids.len(), |budget| budget.get().min(pids.len()));
+        let turn: Vec<u32> = pids
+            .iter()
+            .cycle()
+            .skip(from)
+            .take(count)
+            .copied()
+            .collect();
+        cursor = from + count;
+
+        for pid in turn {
+            let lst: Option<(u64, Report)> = last.remove(&pid);
+            let start: u64 = match (proc_start_time(&pid), &lst) {
+                (Some(start), _) => start,
+                (None, None) => {
+                    if first {
+                        eprintln!("sig-bitmap: {}: no such process", pid);
+                    }
+                    continue;
+                }
+                (None, Some(_)) => {
+                    eprintln!("sig-bitmap: {}: process exited", pid);
+                    continue;
+                }
+            };
+            let report: Report = pid_report(args, &pid);
+
+            if matches!(&lst, Some((lst_start, _)) if *lst_start != start) {
+                eprintln!(
+                    "sig-bitmap: {}: PID reused by a different process",
+                    pid
+                );
+            }
+
+            if lst.as_ref() != Some(&(start, report.clone())) {
+                let mut out: String = sig_render(args, &report);
+
+                if !args.meta.is_empty() {
+                    out.push_str(&format_meta(&cache.lookup(&pid, &args.meta)));
+                    out.push('\n');
+                }
+
+                if let Some((lst_start, lst)) = &lst {
+                    let diff: SigSetDiff = report.merged().diff(&lst.merged());
+
+                    if *lst_start == start && !args.null && !diff.is_empty() {
+                        out.push_str(&format_delta(
+                            &args.map,
+                            &diff,
+                            &name_style(args),
+                        ));
+                        out.push('\n');
+                    }
+                }
+
+                sink.emit(&out)?;
+
+                if let Some(record) = &mut record {
+                    let frame: Frame = Frame {
+                        elapsed: began.elapsed().as_millis() as u64,
+                        start,
+                        report: report.clone(),
+                    };
+                    record.emit(&format!("{}\n", frame.to_json()))?;
+                }
+            }
+
+            last.insert(pid, (start, report));
+
+            match proc_signals(&pid)
+                .is_ok_and(|sigs| args.until.iter().all(|cnd| cnd.holds(&sigs)))
+            {
+                true => held.insert(pid),
+                false => held.remove(&pid),
+            };
+        }
+
+        // Once every process has had a turn, all of them have been read.
+        if cursor >= pids.len() {
+            // Whether the conditions hold for every process.
+            let hold: bool = !args.until.is_empty()
+                && last.keys().all(|pid| held.contains(pid));
+
+            if hold && !last.is_empty() {
+                return Ok(());
+            }
+
+            if last.is_empty() && args.name.is_none() && args.exe.is_none() {
+                return match args.until.is_empty() {
+                    true => Ok(()),
+                    false => Err(Error::new(
+                        ErrorKind::NotFound,
+                        "exited before the condition held",
+                    )),
+                };
+            }
+
+            first = false;
+        }
+
+        thread::sleep(Duration::from_millis(
+            args.interval.saturating_add(jitter(args.jitter)),
+        ));
+    }
+}
+
+// Return a random delay of up to `max` milliseconds; the keys of each
+// `RandomState` are random, so that hashing nothing draws a number.
+fn jitter(max: u64) -> u64 {
+    match max {
+        0 => 0,
+        _ => RandomState::new().build_hasher().finish() % max.saturating_add(1),
+    }
+}
+
+/// Replays a watch session recorded with `record` (see [`sig_watch`]):
+/// the reports are rendered as they were when watching, along with the
+/// signals added and removed, waiting between them as long as when they
+/// were recorded (divided by `speed`, unless it is `0`). The output of
+/// the session (e.g. NUL-delimited records) isn't recorded, so reports
+/// are always rendered as human-readable lines.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the path to the
+///   recorded session and the speed of the replay.
+///
+/// A session whose watch was killed while writing a record ends with
+/// that record cut short; it is skipped, with a warning.
+///
+/// # Returns
+///
+/// An error if the session can't be read, or has a line that isn't a
+/// record (see [`Frame::parse`]), other than a last one cut short.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{session::Frame, sig_replay, ReplayArgs, Report};
+/// use std::path::PathBuf;
+/// let frame: Frame = Frame {
+///     elapsed: 1500,
+///     start: 9000,
+///     report: Report {
+///         pid: 42,
+///         ..Default::default()
+///     },
+/// };
+/// let record: String = frame.to_json();
+/// let session: PathBuf = std::env::temp_dir().join("sig-bitmap.jsonl");
+/// std::fs::write(&session, format!("{}\n{}", record, &record[..20]))
+///     .unwrap();
+/// assert!(sig_replay(&ReplayArgs { session, speed: 0.0 }).is_ok());
+/// ````
+pub fn sig_replay(args: &ReplayArgs) -> Result<(), Error> {
+    let text: String = fs::read_to_string(&args.session)?;
+    let style: NameStyle = NameStyle::default();
+    let mut last: BTreeMap<u32, (u64, Report)> = BTreeMap::new();
+    let mut elapsed: u64 = 0;
+
+    let mut lines = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .peekable();
+
+    while let Some(line) = lines.next() {
+        let frame: Frame = match Frame::parse(line) {
+            Ok(frame) => frame,
+            // A watch killed while writing a record leaves it cut short,
+            // as the last line, without a newline.
+            Err(_) if lines.peek().is_none() && !text.ends_with('\n') => {
+                eprintln!(
+                    "sig-bitmap: {}: last record cut short, skipped",
+                    args.session.display()
+                );
+                break;
+            }
+            Err(err) => return Err(Error::new(ErrorKind::InvalidData, err)),
+        };
+        let (pid, start): (u32, u64) = (frame.report.pid, frame.start);
+
+        if args.speed > 0.0 {
+            let wait: u64 = frame.elapsed.saturating_sub(elapsed);
+            thread::sleep(Duration::from_secs_f64(
+                wait as f64 / 1000.0 / args.speed,
+            ));
+        }
+        elapsed = frame.elapsed;
+
+        let mut out: String = render_text(&frame.report, &style);
+
+        if let Some(warn) = frame.report.impossible() {
+            eprintln!("sig-bitmap: {}: {}", pid, warn);
+        }
+
+        match last.get(&pid) {
+            Some((lst_start, _)) if *lst_start != start => eprintln!(
+                "sig-bitmap: {}: PID reused by a different process",
+                pid
+            ),
+            Some((_, lst)) => {
+                let diff: SigSetDiff =
+                    frame.report.merged().diff(&lst.merged());
+
+                if !diff.is_empty() {
+                    out.push_str(&format_delta(
+                        &frame.report.map,
+                        &diff,
+                        &style,
+                    ));
+                    out.push('\n');
+                }
+            }
+            None => {}
+        }
+
+        print!("{}", out);
+        last.insert(pid, (start, frame.report));
+    }
+
+    Ok(())
+}
+
+/// Displays the processes catching, blocking or ignoring each signal on
+/// several hosts (see [`merge_users`]), given the outputs of `who` with
+/// `json` set (see [`sig_who`]) collected from each: a file per host,
+/// named after it (the file name without its extension), so that scans
+/// can be run per host and read as a single fleet report.
+///
+/// # Arguments
+///
+/// * `args` - A reference to a `struct` containing the paths to the
+///   outputs.
+///
+/// # Returns
+///
+/// An error if an output can't be read, or has a line that isn't a JSON
+/// object written by `who` (see [`parse_users`]).
+pub fn sig_merge(args: &MergeArgs) -> Result<(), Error> {
+    let mut reports: Vec<(String, SignalUsers)> = Vec::new();
+
+    for path in &args.reports {
+        let host: String = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+
+        for line in fs::read_to_string(path)?.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let users: SignalUsers = parse_users(line)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+            reports.push((host.clone(), users));
+        }
+    }
+
+    let fleet: Vec<FleetUsers> = merge_users(reports);
+
+    for users in &fleet {
+        println!("{}", format_fleet(users));
+    }
+
+    Ok(())
+}
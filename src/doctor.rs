@@ -0,0 +1,584 @@
+//! Signal health checks: an opinionated summary of the signal bitmaps,
+//! threads, signal queue and state of a process, as findings with
+//! severity levels.
+use crate::signals::{ProcessSignals, SigSet};
+use std::fmt;
+
+// Synchronous fault signals (ILL, TRAP, BUS, FPE, SEGV); the kernel
+// kills the process on a fault even if these are blocked or ignored.
+const FAULT_SIGS: u64 = 0x4d8;
+
+// Signals asking a process to terminate (HUP, INT, QUIT, TERM).
+const TERM_SIGS: u64 = 0x4007;
+
+// Signal number of `CHLD`.
+const SIGCHLD_IDX: u8 = 0x11;
+
+// Lint rules, in the order they are checked.
+static RULES: &[Rule] = &[
+    Rule::FaultBlocked,
+    Rule::FaultIgnored,
+    Rule::TermIgnored,
+    Rule::ChldIgnored,
+];
+
+// Fill level of the signal queue (percent) for a warning, or worse.
+const QUEUE_WARN: u64 = 50;
+const QUEUE_CRIT: u64 = 90;
+
+/// How serious a finding is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Severity {
+    /// Worth knowing, usually intended.
+    Info,
+
+    /// Likely to cause signals to be lost, delayed or ineffective.
+    Warning,
+
+    /// Signals are not being delivered (or can't be).
+    Critical,
+}
+
+/// The check that produced a finding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Check {
+    /// Questionable dispositions or masks.
+    Lint,
+
+    /// Signals pending but blocked.
+    Stuck,
+
+    /// Threads disagreeing on their masks.
+    Threads,
+
+    /// Pressure on the queue of pending signals (`SigQ`).
+    Queue,
+
+    /// A process state in which signals aren't acted upon.
+    State,
+
+    /// Signal settings of a systemd unit at odds with the process.
+    Unit,
+
+    /// Signals at odds with a policy (see [`crate::policy`]).
+    Policy,
+}
+
+/// A single finding about the signal health of a process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    /// How serious the finding is.
+    pub severity: Severity,
+
+    /// The check that produced the finding.
+    pub check: Check,
+
+    /// What was found.
+    pub message: String,
+
+    /// The lint rule that produced the finding, for findings of the
+    /// `lint` check (see [`lint`]).
+    pub rule: Option<Rule>,
+}
+
+/// A rule of the `lint` check (see [`lint`]). Its ID (as displayed,
+/// e.g. `term-ignored`) and severity are stable, so that findings can
+/// be suppressed or gated on by rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Rule {
+    /// Fault signals blocked (`fault-blocked`, warning).
+    FaultBlocked,
+
+    /// Fault signals ignored (`fault-ignored`, warning).
+    FaultIgnored,
+
+    /// Termination signals ignored (`term-ignored`, warning).
+    TermIgnored,
+
+    /// `CHLD` ignored (`chld-ignored`, info).
+    ChldIgnored,
+}
+
+/// What the checks look at, read from `/proc/<pid>/status` of a
+/// process and its threads.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Health {
+    /// State of the process (`R`, `S`, `D`, `T`, `t`, `Z`, ...).
+    pub state: char,
+
+    /// Number of signals queued for the real user ID of the process.
+    pub queued: u64,
+
+    /// Limit on the number of queued signals (`RLIMIT_SIGPENDING`).
+    pub queue_limit: u64,
+
+    /// Signal bitmaps of the process (its main thread).
+    pub signals: ProcessSignals,
+
+    /// Thread IDs and signal bitmaps of every thread of the process,
+    /// including the main thread.
+    pub threads: Vec<(u32, ProcessSignals)>,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "INFO"),
+            Severity::Warning => write!(f, "WARNING"),
+            Severity::Critical => write!(f, "CRITICAL"),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Check::Lint => write!(f, "lint"),
+            Check::Stuck => write!(f, "stuck"),
+            Check::Threads => write!(f, "threads"),
+            Check::Queue => write!(f, "queue"),
+            Check::State => write!(f, "state"),
+            Check::Unit => write!(f, "unit"),
+            Check::Policy => write!(f, "policy"),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rule::FaultBlocked => write!(f, "fault-blocked"),
+            Rule::FaultIgnored => write!(f, "fault-ignored"),
+            Rule::TermIgnored => write!(f, "term-ignored"),
+            Rule::ChldIgnored => write!(f, "chld-ignored"),
+        }
+    }
+}
+
+impl Rule {
+    /// Returns the severity of the findings of the rule.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Rule::FaultBlocked | Rule::FaultIgnored | Rule::TermIgnored => {
+                Severity::Warning
+            }
+            Rule::ChldIgnored => Severity::Info,
+        }
+    }
+
+    /// Returns the rule with an ID (e.g. `term-ignored`), or `None` if
+    /// there is none.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::doctor::Rule;
+    /// assert_eq!(Rule::from_id("chld-ignored"), Some(Rule::ChldIgnored));
+    /// assert_eq!(Rule::from_id("chld"), None);
+    /// ````
+    pub fn from_id(id: &str) -> Option<Rule> {
+        RULES.iter().find(|rule| rule.to_string() == id).copied()
+    }
+}
+
+impl Finding {
+    // Return a finding.
+    pub(crate) fn new(
+        severity: Severity,
+        check: Check,
+        message: String,
+    ) -> Finding {
+        Finding {
+            severity,
+            check,
+            message,
+            rule: None,
+        }
+    }
+
+    // Return a finding of a lint rule.
+    fn from_rule(rule: Rule, message: String) -> Finding {
+        Finding {
+            severity: rule.severity(),
+            check: Check::Lint,
+            message,
+            rule: Some(rule),
+        }
+    }
+}
+
+// Return the comma-separated names of the signals in a set.
+fn names(set: &SigSet) -> String {
+    set.to_names().join(", ")
+}
+
+/// Returns the findings of the `lint` check on the signal bitmaps of a
+/// process: questionable dispositions and masks, by rule (see
+/// [`Rule`]), in this order:
+///
+/// * `fault-blocked` - Fault signals (`ILL`, `TRAP`, `BUS`, `FPE`,
+///   `SEGV`) that are blocked; a fault still kills the process.
+/// * `fault-ignored` - Fault signals that are ignored.
+/// * `term-ignored` - Termination signals (`HUP`, `INT`, `QUIT`,
+///   `TERM`) that are ignored.
+/// * `chld-ignored` - `CHLD` being ignored, so children are reaped
+///   automatically.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{doctor::*, ProcessSignals, SigSet};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     ignored: SigSet::from_bits(0x14000),
+///     ..Default::default()
+/// };
+/// let mut findings: Vec<Finding> = lint(&sigs);
+/// findings.retain(|fnd| fnd.rule != Some(Rule::ChldIgnored));
+/// assert_eq!(findings.len(), 1);
+/// assert_eq!(findings[0].rule, Some(Rule::TermIgnored));
+/// assert_eq!(findings[0].severity, Severity::Warning);
+/// ````
+pub fn lint(sigs: &ProcessSignals) -> Vec<Finding> {
+    let mut out: Vec<Finding> = Vec::new();
+    let faults: SigSet = SigSet::from_bits(FAULT_SIGS);
+    let terms: SigSet = SigSet::from_bits(TERM_SIGS);
+
+    let blk: SigSet = sigs.blocked.intersection(&faults);
+    if !blk.is_empty() {
+        out.push(Finding::from_rule(
+            Rule::FaultBlocked,
+            format!(
+                "fault signals blocked (a fault still kills): {}",
+                names(&blk)
+            ),
+        ));
+    }
+
+    let ign: SigSet = sigs.ignored.intersection(&faults);
+    if !ign.is_empty() {
+        out.push(Finding::from_rule(
+            Rule::FaultIgnored,
+            format!(
+                "fault signals ignored (a fault still kills): {}",
+                names(&ign)
+            ),
+        ));
+    }
+
+    let ign: SigSet = sigs.ignored.intersection(&terms);
+    if !ign.is_empty() {
+        out.push(Finding::from_rule(
+            Rule::TermIgnored,
+            format!("termination signals ignored: {}", names(&ign)),
+        ));
+    }
+
+    if sigs.ignored.contains(&SIGCHLD_IDX) {
+        out.push(Finding::from_rule(
+            Rule::ChldIgnored,
+            String::from("CHLD ignored: children are reaped automatically"),
+        ));
+    }
+
+    out
+}
+
+// Signals pending but blocked, per thread and for the whole process.
+fn stuck(health: &Health, out: &mut Vec<Finding>) {
+    let mut blk_all: SigSet = SigSet::from_bits(u64::MAX);
+
+    for (tid, sigs) in &health.threads {
+        let stk: SigSet = sigs.pending.intersection(&sigs.blocked);
+        if !stk.is_empty() {
+            out.push(Finding::new(
+                Severity::Warning,
+                Check::Stuck,
+                format!(
+                    "pending but blocked in thread {}: {}",
+                    tid,
+                    names(&stk)
+                ),
+            ));
+        }
+        blk_all = blk_all.intersection(&sigs.blocked);
+    }
+
+    if health.threads.is_empty() {
+        blk_all = health.signals.blocked;
+    }
+
+    let stk: SigSet = health.signals.shared_pending.intersection(&blk_all);
+    if !stk.is_empty() {
+        out.push(Finding::new(
+            Severity::Critical,
+            Check::Stuck,
+            format!("pending but blocked in every thread: {}", names(&stk)),
+        ));
+    }
+}
+
+// Signals blocked by some threads of the process, but not all.
+fn threads(health: &Health, out: &mut Vec<Finding>) {
+    let (any, all): (SigSet, SigSet) = health.threads.iter().fold(
+        (SigSet::empty(), SigSet::from_bits(u64::MAX)),
+        |(any, all), (_, sigs)| {
+            (any.union(&sigs.blocked), all.intersection(&sigs.blocked))
+        },
+    );
+    let diff: SigSet = any.difference(&all);
+
+    if !diff.is_empty() {
+        out.push(Finding::new(
+            Severity::Info,
+            Check::Threads,
+            format!(
+                "{} threads, blocked in some only: {}",
+                health.threads.len(),
+                names(&diff),
+            ),
+        ));
+    }
+}
+
+// Pressure on the queue of pending signals.
+fn queue(health: &Health, out: &mut Vec<Finding>) {
+    if health.queue_limit == 0 {
+        return;
+    }
+
+    let pct: u64 = health.queued.saturating_mul(100) / health.queue_limit;
+    let severity: Severity = match pct {
+        _ if pct >= QUEUE_CRIT => Severity::Critical,
+        _ if pct >= QUEUE_WARN => Severity::Warning,
+        _ => return,
+    };
+
+    out.push(Finding::new(
+        severity,
+        Check::Queue,
+        format!(
+            "signal queue {}% full: {}/{}",
+            pct, health.queued, health.queue_limit
+        ),
+    ));
+}
+
+// A process state in which signals aren't acted upon.
+fn state(health: &Health, out: &mut Vec<Finding>) {
+    let (severity, message): (Severity, &str) = match health.state {
+        'D' => (
+            Severity::Warning,
+            "in uninterruptible sleep: signals wait until it wakes",
+        ),
+        'T' => (
+            Severity::Warning,
+            "stopped: signals other than KILL and CONT stay pending",
+        ),
+        't' => (
+            Severity::Info,
+            "stopped by a tracer: signals are reported to the tracer",
+        ),
+        'Z' => (
+            Severity::Critical,
+            "zombie: exited and not reaped by its parent",
+        ),
+        _ => return,
+    };
+
+    out.push(Finding::new(severity, Check::State, message.to_string()));
+}
+
+/// Returns the findings of every check on a process, most severe
+/// first:
+///
+/// * `lint` - Fault signals (`ILL`, `TRAP`, `BUS`, `FPE`, `SEGV`) that
+///   are blocked or ignored, termination signals that are ignored, and
+///   `CHLD` being ignored (see [`lint`]).
+/// * `stuck` - Signals pending in a thread that blocks them, and shared
+///   signals pending while every thread blocks them (critical).
+/// * `threads` - Signals blocked by some threads, but not all.
+/// * `queue` - The signal queue filling up (warning from 50%, critical
+///   from 90%).
+/// * `state` - Uninterruptible sleep, stopped, traced or zombie.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{doctor::*, ProcessSignals, SigSet};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     shared_pending: SigSet::from_bits(0x4000),
+///     blocked: SigSet::from_bits(0x4000),
+///     ..Default::default()
+/// };
+/// let health: Health = Health {
+///     state: 'S',
+///     signals: sigs,
+///     threads: vec![(42, sigs)],
+///     ..Default::default()
+/// };
+/// let findings: Vec<Finding> = diagnose(&health);
+/// assert_eq!(findings[0].severity, Severity::Critical);
+/// assert_eq!(findings[0].check, Check::Stuck);
+/// ```
+pub fn diagnose(health: &Health) -> Vec<Finding> {
+    let mut out: Vec<Finding> = lint(&health.signals);
+
+    stuck(health, &mut out);
+    threads(health, &mut out);
+    queue(health, &mut out);
+    state(health, &mut out);
+
+    out.sort_by_key(|fnd| std::cmp::Reverse(fnd.severity));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks and severities of a list of findings.
+    type Checks = Vec<(Check, Severity)>;
+
+    // Return the checks and severities of the findings for `health`.
+    fn checks(health: &Health) -> Checks {
+        diagnose(health)
+            .iter()
+            .map(|fnd| (fnd.check, fnd.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_diagnose_healthy() {
+        let sigs: ProcessSignals = ProcessSignals {
+            blocked: SigSet::from_bits(0x4000),
+            caught: SigSet::from_bits(0x4001),
+            ..Default::default()
+        };
+        let health: Health = Health {
+            state: 'S',
+            queued: 3,
+            queue_limit: 63432,
+            signals: sigs,
+            threads: vec![(42, sigs), (43, sigs)],
+        };
+
+        assert_eq!(diagnose(&health), vec![]);
+    }
+
+    #[test]
+    fn test_diagnose_lint() {
+        let health: Health = Health {
+            signals: ProcessSignals {
+                blocked: SigSet::from_bits(0x400),
+                ignored: SigSet::from_bits(0x4000 | 0x10000),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            diagnose(&health),
+            vec![
+                Finding::from_rule(
+                    Rule::FaultBlocked,
+                    String::from(
+                        "fault signals blocked (a fault still kills): SEGV"
+                    ),
+                ),
+                Finding::from_rule(
+                    Rule::TermIgnored,
+                    String::from("termination signals ignored: TERM"),
+                ),
+                Finding::from_rule(
+                    Rule::ChldIgnored,
+                    String::from(
+                        "CHLD ignored: children are reaped automatically"
+                    ),
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_rule_from_id() {
+        let tests: Vec<(&str, Option<Rule>)> = vec![
+            ("fault-blocked", Some(Rule::FaultBlocked)),
+            ("fault-ignored", Some(Rule::FaultIgnored)),
+            ("term-ignored", Some(Rule::TermIgnored)),
+            ("chld-ignored", Some(Rule::ChldIgnored)),
+            ("TERM-IGNORED", None),
+            ("lint", None),
+            ("", None),
+        ];
+
+        for test in tests {
+            assert_eq!(Rule::from_id(test.0), test.1);
+        }
+
+        for rule in RULES {
+            assert_eq!(Rule::from_id(&rule.to_string()), Some(*rule));
+        }
+    }
+
+    #[test]
+    fn test_diagnose_threads() {
+        let main: ProcessSignals = ProcessSignals {
+            pending: SigSet::from_bits(0x2),
+            shared_pending: SigSet::from_bits(0x4001),
+            blocked: SigSet::from_bits(0x4003),
+            ..Default::default()
+        };
+        let work: ProcessSignals = ProcessSignals {
+            shared_pending: SigSet::from_bits(0x4001),
+            blocked: SigSet::from_bits(0x4000),
+            ..Default::default()
+        };
+        let health: Health = Health {
+            state: 'S',
+            signals: main,
+            threads: vec![(42, main), (43, work)],
+            ..Default::default()
+        };
+        let findings: Vec<Finding> = diagnose(&health);
+
+        assert_eq!(
+            checks(&health),
+            vec![
+                (Check::Stuck, Severity::Critical),
+                (Check::Stuck, Severity::Warning),
+                (Check::Threads, Severity::Info),
+            ],
+        );
+        assert!(findings[0].message.ends_with(": TERM"));
+        assert!(findings[1].message.ends_with("thread 42: INT"));
+        assert!(findings[2].message.ends_with("some only: HUP, INT"));
+    }
+
+    #[test]
+    fn test_diagnose_queue_state() {
+        let tests: Vec<(char, u64, Checks)> = vec![
+            ('R', 10, vec![]),
+            ('S', 50, vec![(Check::Queue, Severity::Warning)]),
+            (
+                'D',
+                95,
+                vec![
+                    (Check::Queue, Severity::Critical),
+                    (Check::State, Severity::Warning),
+                ],
+            ),
+            ('t', 0, vec![(Check::State, Severity::Info)]),
+            ('Z', 0, vec![(Check::State, Severity::Critical)]),
+        ];
+
+        for test in tests {
+            let health: Health = Health {
+                state: test.0,
+                queued: test.1,
+                queue_limit: 100,
+                ..Default::default()
+            };
+            assert_eq!(checks(&health), test.2);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Errors returned by the library.
+use std::fmt;
+
+/// An error interpreting signals or signal bitmaps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// A signal name (or number) that doesn't match any signal.
+    UnknownSignal(String),
+
+    /// A preset name that doesn't match any preset.
+    UnknownPreset(String),
+
+    /// A token in a byte dump that isn't a hex byte.
+    InvalidByte(String),
+
+    /// A byte dump that isn't the size of a `sigset_t` (8 bytes).
+    InvalidLength(usize),
+
+    /// An architecture name that doesn't match a registered table.
+    UnknownArch(String),
+
+    /// A line of a signal table that isn't a signal number and a name.
+    InvalidEntry(String),
+
+    /// A condition that isn't a bitmap type and a signal (`blk:TERM`).
+    InvalidCondition(String),
+
+    /// A target that isn't a PID, with or without a command name (e.g.
+    /// `nginx(1234)`).
+    InvalidTarget(String),
+
+    /// A line of a policy file that isn't a section or a rule in one.
+    InvalidPolicy(String),
+
+    /// A line of a recorded session that isn't a record (of this
+    /// version).
+    InvalidRecord(String),
+
+    /// A path template without the `{pid}` placeholder.
+    InvalidTemplate(String),
+
+    /// A line of a report to merge that isn't a JSON object written by
+    /// `who --json` (of this version).
+    InvalidReport(String),
+
+    /// A line of a saved scan that isn't a JSON object written by
+    /// `scan --format json` (of this version).
+    InvalidSnapshot(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnknownSignal(name) => write!(f, "unknown signal: {}", name),
+            Error::UnknownPreset(name) => {
+                write!(f, "unknown preset: @{}", name)
+            }
+            Error::InvalidByte(text) => write!(f, "invalid byte: {}", text),
+            Error::InvalidLength(len) => {
+                write!(f, "invalid sigset_t size: {} bytes (expected 8)", len)
+            }
+            Error::UnknownArch(name) => {
+                write!(f, "unknown architecture: {}", name)
+            }
+            Error::InvalidEntry(line) => {
+                write!(f, "invalid signal table entry: {}", line)
+            }
+            Error::InvalidCondition(text) => {
+                write!(f, "invalid condition: {}", text)
+            }
+            Error::InvalidTarget(text) => write!(f, "invalid target: {}", text),
+            Error::InvalidPolicy(line) => {
+                write!(f, "invalid policy line: {}", line)
+            }
+            Error::InvalidRecord(line) => {
+                write!(f, "invalid session record: {}", line)
+            }
+            Error::InvalidTemplate(text) => {
+                write!(f, "invalid path template (no {{pid}}): {}", text)
+            }
+            Error::InvalidReport(line) => {
+                write!(f, "invalid report line: {}", line)
+            }
+            Error::InvalidSnapshot(line) => {
+                write!(f, "invalid scan line: {}", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,70 @@
+//! The optional features of the crate (see `Cargo.toml`), and whether
+//! they were compiled in, to tell what a build can do.
+
+/// An optional feature of the crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Feature {
+    /// Name of the feature, as in `Cargo.toml`.
+    pub name: &'static str,
+
+    /// What the feature provides.
+    pub about: &'static str,
+
+    /// Whether the feature was compiled in.
+    pub enabled: bool,
+}
+
+/// The optional features of the crate, in the order of `Cargo.toml`.
+pub static FEATURES: &[Feature] = &[
+    Feature {
+        name: "cli",
+        about: "Command line interface",
+        enabled: cfg!(feature = "cli"),
+    },
+    Feature {
+        name: "proc",
+        about: "Readers for /proc/<pid>/status",
+        enabled: cfg!(feature = "proc"),
+    },
+    Feature {
+        name: "format",
+        about: "Formatting for display",
+        enabled: cfg!(feature = "format"),
+    },
+];
+
+/// Returns `true` if a feature was compiled in, and `false` if not or if
+/// there is no such feature.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::features::enabled;
+/// assert_eq!(enabled("proc"), cfg!(feature = "proc"));
+/// assert!(!enabled("bogus"));
+/// ````
+pub fn enabled(name: &str) -> bool {
+    FEATURES.iter().any(|ftr| ftr.name == name && ftr.enabled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_features() {
+        let manifest: &str = include_str!("../Cargo.toml");
+        let names: Vec<&str> = manifest
+            .split("[features]")
+            .nth(1)
+            .unwrap_or_default()
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once('=').map(|(key, _)| key.trim()))
+            .filter(|name| *name != "default")
+            .collect();
+        let ftrs: Vec<&str> = FEATURES.iter().map(|ftr| ftr.name).collect();
+
+        assert_eq!(ftrs, names);
+    }
+}
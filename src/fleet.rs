@@ -0,0 +1,249 @@
+//! Fleet reports: the processes using signals on several hosts (as
+//! written by `who --json` on each), merged into a single report that
+//! attributes each process to its host, so that scans can be collected
+//! per host and read in one place.
+use crate::{error::Error, scan::SignalUsers, session::json_fields};
+use std::collections::{BTreeMap, HashMap};
+
+// Versions of the structure of the objects read (as written by
+// `format_who_json`); objects of the first version have none.
+const SCHEMA_VERSIONS: [Option<u64>; 3] = [None, Some(1), Some(2)];
+
+// Signal numbers.
+static SIG_RANGE: std::ops::RangeInclusive<u8> = 0x01..=0x40;
+
+/// The processes catching, blocking or ignoring a signal on several
+/// hosts, as host names and PIDs, in the order the hosts were merged
+/// (and, for a host, of the PIDs).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FleetUsers {
+    /// Signal number.
+    pub signal: u8,
+
+    /// Processes that catch the signal.
+    pub caught: Vec<(String, u32)>,
+
+    /// Processes that block the signal.
+    pub blocked: Vec<(String, u32)>,
+
+    /// Processes that ignore the signal.
+    pub ignored: Vec<(String, u32)>,
+}
+
+/// Returns the processes using a signal in a JSON object written by
+/// `who --json` or `--format json-v1`; fields it doesn't know of are
+/// skipped.
+///
+/// # Errors
+///
+/// [`Error::InvalidReport`] for a line that isn't such an object, or is
+/// of another version.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{fleet::parse_users, scan::SignalUsers};
+/// let users: SignalUsers = parse_users(
+///     "{\"schema_version\":2,\"signal\":\"USR1\",\"number\":10,\"caught\":[42,43],\
+///      \"blocked\":[],\"ignored\":[7]}",
+/// )
+/// .unwrap();
+/// assert_eq!(users.caught, vec![42, 43]);
+/// assert_eq!(users.ignored, vec![7]);
+/// ````
+pub fn parse_users(line: &str) -> Result<SignalUsers, Error> {
+    let invalid = || Error::InvalidReport(line.to_string());
+    let fields: HashMap<&str, &str> = json_fields(line).ok_or_else(invalid)?;
+    let num = |key: &str| -> Option<u64> {
+        fields.get(key).and_then(|val| val.parse::<u64>().ok())
+    };
+    let pids = |key: &str| -> Result<Vec<u32>, Error> {
+        fields
+            .get(key)
+            .and_then(|val| val.strip_prefix('[')?.strip_suffix(']'))
+            .ok_or_else(invalid)?
+            .split(',')
+            .map(str::trim)
+            .filter(|pid| !pid.is_empty())
+            .map(|pid| pid.parse::<u32>().map_err(|_| invalid()))
+            .collect()
+    };
+
+    let version: Option<u64> = match fields.contains_key("schema_version") {
+        true => Some(num("schema_version").ok_or_else(invalid)?),
+        false => None,
+    };
+
+    if !SCHEMA_VERSIONS.contains(&version) {
+        return Err(invalid());
+    }
+
+    Ok(SignalUsers {
+        signal: num("number")
+            .and_then(|idx| u8::try_from(idx).ok())
+            .filter(|idx| SIG_RANGE.contains(idx))
+            .ok_or_else(invalid)?,
+        caught: pids("caught")?,
+        blocked: pids("blocked")?,
+        ignored: pids("ignored")?,
+    })
+}
+
+/// Returns the processes using each signal across hosts, given those
+/// using signals on each host (see [`parse_users`]) along with the name
+/// of the host, in order of the signal numbers.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{fleet::*, scan::SignalUsers};
+/// let web1: SignalUsers = SignalUsers {
+///     signal: 10,
+///     caught: vec![42],
+///     ..Default::default()
+/// };
+/// let web2: SignalUsers = SignalUsers {
+///     signal: 10,
+///     caught: vec![7],
+///     ..Default::default()
+/// };
+/// let fleet: Vec<FleetUsers> = merge_users(vec![
+///     (String::from("web1"), web1),
+///     (String::from("web2"), web2),
+/// ]);
+/// assert_eq!(
+///     fleet[0].caught,
+///     vec![(String::from("web1"), 42), (String::from("web2"), 7)],
+/// );
+/// ````
+pub fn merge_users<I>(reports: I) -> Vec<FleetUsers>
+where
+    I: IntoIterator<Item = (String, SignalUsers)>,
+{
+    let mut out: BTreeMap<u8, FleetUsers> = BTreeMap::new();
+
+    for (host, users) in reports {
+        let fleet: &mut FleetUsers =
+            out.entry(users.signal).or_insert_with(|| FleetUsers {
+                signal: users.signal,
+                ..Default::default()
+            });
+
+        for (pids, procs) in [
+            (users.caught, &mut fleet.caught),
+            (users.blocked, &mut fleet.blocked),
+            (users.ignored, &mut fleet.ignored),
+        ] {
+            procs.extend(pids.into_iter().map(|pid| (host.clone(), pid)));
+        }
+    }
+
+    out.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signal number, and PIDs catching, blocking and ignoring it.
+    type Parsed = Option<(u8, Vec<u32>, Vec<u32>, Vec<u32>)>;
+
+    #[test]
+    fn test_parse_users() {
+        let tests: Vec<(&str, Parsed)> = vec![
+            (
+                "{\"schema_version\":1,\"signal\":\"USR1\",\"number\":10,\
+                 \"caught\":[42,43],\"blocked\":[],\"ignored\":[7]}",
+                Some((10, vec![42, 43], vec![], vec![7])),
+            ),
+            (
+                " { \"ignored\": [ 1 , 2 ], \"host\": \"a,[b]\", \
+                 \"blocked\": [3], \"caught\": [], \"number\": 64, \
+                 \"schema_version\": 1 } ",
+                Some((64, vec![], vec![3], vec![1, 2])),
+            ),
+            (
+                "{\"schema_version\":2,\"signal\":\"USR1\",\"number\":10,\
+                 \"caught\":[],\"blocked\":[42],\"ignored\":[]}",
+                Some((10, vec![], vec![42], vec![])),
+            ),
+            (
+                "{\"signal\":\"USR1\",\"number\":10,\"caught\":[42],\
+                 \"blocked\":[],\"ignored\":[]}",
+                Some((10, vec![42], vec![], vec![])),
+            ),
+            (
+                "{\"schema_version\":3,\"number\":10,\"caught\":[],\
+                 \"blocked\":[],\"ignored\":[]}",
+                None,
+            ),
+            (
+                "{\"schema_version\":\"2\",\"number\":10,\"caught\":[],\
+                 \"blocked\":[],\"ignored\":[]}",
+                None,
+            ),
+            (
+                "{\"schema_version\":1,\"number\":65,\"caught\":[],\
+                 \"blocked\":[],\"ignored\":[]}",
+                None,
+            ),
+            (
+                "{\"schema_version\":1,\"number\":10,\"caught\":[-1],\
+                 \"blocked\":[],\"ignored\":[]}",
+                None,
+            ),
+            (
+                "{\"schema_version\":1,\"number\":10,\"caught\":[],\
+                 \"blocked\":[]}",
+                None,
+            ),
+            ("SIGNAL: USR1 (10)", None),
+            ("", None),
+        ];
+
+        for test in tests {
+            let users: Parsed = parse_users(test.0).ok().map(|users| {
+                (users.signal, users.caught, users.blocked, users.ignored)
+            });
+
+            assert_eq!(users, test.1);
+        }
+    }
+
+    #[test]
+    fn test_merge_users() {
+        let users =
+            |signal: u8, caught: Vec<u32>, ignored: Vec<u32>| SignalUsers {
+                signal,
+                caught,
+                ignored,
+                ..Default::default()
+            };
+        let host = |name: &str, pid: u32| (name.to_string(), pid);
+        let fleet: Vec<FleetUsers> = merge_users(vec![
+            (String::from("web1"), users(15, vec![42], vec![])),
+            (String::from("web1"), users(10, vec![42, 43], vec![])),
+            (String::from("web2"), users(10, vec![7], vec![9])),
+        ]);
+
+        assert_eq!(
+            fleet,
+            vec![
+                FleetUsers {
+                    signal: 10,
+                    caught: vec![
+                        host("web1", 42),
+                        host("web1", 43),
+                        host("web2", 7),
+                    ],
+                    blocked: vec![],
+                    ignored: vec![host("web2", 9)],
+                },
+                FleetUsers {
+                    signal: 15,
+                    caught: vec![host("web1", 42)],
+                    ..Default::default()
+                },
+            ],
+        );
+        assert!(merge_users(vec![]).is_empty());
+    }
+}
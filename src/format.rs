@@ -0,0 +1,1613 @@
+//! Formatting of interpreted signal bitmaps for display.
+use crate::{
+    arch::{ArchTable, Mismatch},
+    audit::Event,
+    doctor::Finding,
+    fleet::FleetUsers,
+    report::{Report, Status},
+    sample::Tally,
+    scan::{
+        FindingDiff, GroupSummary, KillGroup, MaskGroup, RtUsage, ScanDiff,
+        SignalUsers, Snapshot,
+    },
+    session::json_escape,
+    signals::{
+        category, interpret, sigabbrev_np, BitmapType, NameStyle, Outcome,
+        ProcessSignals, SigSet, SigSetDiff, SignalCategory, NR_SIGS,
+    },
+};
+use std::fmt::Display;
+use textwrap::{fill, Options};
+
+// Maximum display column width.
+const MAX_WIDTH: usize = 80;
+
+// Subsequent column width (after header).
+const SUB_WIDTH: usize = 45;
+
+// Subsequent column width (after header) of a delta line.
+const DIFF_WIDTH: usize = 20;
+
+// Subsequent column width (after header) of a decoded line.
+const DECODE_WIDTH: usize = 33;
+
+// Subsequent column width (after header) of a finding.
+const FIND_WIDTH: usize = 30;
+
+// Subsequent column width (after header) of a line of PIDs.
+const PIDS_WIDTH: usize = 16;
+
+// Column width of the header of a line (e.g. `PID: 42`).
+const HDR_WIDTH: usize = 12;
+
+// Version of the structure of the JSON objects, bumped when fields are
+// changed or removed (not when added); objects in the previous version
+// are written by the `_v1` functions.
+const SCHEMA_VERSION: u32 = 2;
+
+// Column width of a bit in the bit grid (fits `RTMIN+15`).
+const BIT_WIDTH: usize = 9;
+
+// Glyphs of a signal in the compact view, if it is set or not.
+const GLYPH_SET: char = '#';
+const GLYPH_UNSET: char = '.';
+
+// Number of signals in a group of glyphs in the compact view.
+const GLYPH_GROUP: u8 = 8;
+
+// Column width of the PID in the compact view (fits `pid_max`).
+const GLYPH_PID_WIDTH: usize = 7;
+
+/// Returns the human-readable line for a signal bitmap, listing the
+/// signals it holds (named in `style`) and wrapped to fit the terminal.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_text, BitmapType, NameStyle};
+/// assert_eq!(
+///     format_text(&42, &BitmapType::SigBlk, &0x4001, &NameStyle::default()),
+///     "PID: 42     SigBlk: 2  [0x0000000000004001]: HUP, TERM",
+/// );
+/// ````
+pub fn format_text(
+    pid: &u32,
+    typ: &BitmapType,
+    map: &u64,
+    style: &NameStyle,
+) -> String {
+    format_line(&format!("PID: {:<6}", pid), &typ.to_string(), map, style)
+}
+
+/// Returns the human-readable line for the signals pending for a thread
+/// (`SigPnd`) and for the whole process (`ShdPnd`) together, aligned
+/// with [`format_text`]: each signal is marked with where it is pending
+/// (`thread`, `shared` or `both`), e.g. `TERM(shared)`.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_pending, NameStyle, SigSet};
+/// assert_eq!(
+///     format_pending(
+///         &42,
+///         &SigSet::from_bits(0x201),
+///         &SigSet::from_bits(0x4200),
+///         &NameStyle::default(),
+///     ),
+///     "PID: 42     Pending: 3 [0x0000000000004201]: HUP(thread), USR1(both),\n\
+///      \x20                                            TERM(shared)",
+/// );
+/// ````
+pub fn format_pending(
+    pid: &u32,
+    thread: &SigSet,
+    shared: &SigSet,
+    style: &NameStyle,
+) -> String {
+    let all: SigSet = thread.union(shared);
+    let sig_lst: Vec<String> = all
+        .iter()
+        .map(|idx| {
+            let origin: &str =
+                match (thread.contains(&idx), shared.contains(&idx)) {
+                    (true, true) => "both",
+                    (true, false) => "thread",
+                    _ => "shared",
+                };
+            format!("{}({})", style.name(&idx), origin)
+        })
+        .collect();
+
+    wrap(
+        &format!(
+            "PID: {:<6} Pending: {:<2}[0x{:016x}]: {}",
+            pid,
+            sig_lst.len(),
+            all.bits(),
+            fmt_list(&sig_lst),
+        ),
+        SUB_WIDTH,
+    )
+}
+
+/// Returns the human-readable line for the signals of a process left
+/// to their default action (see
+/// [`crate::ProcessSignals::default_disposition`]), aligned with
+/// [`format_text`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::format::format_default;
+/// assert_eq!(
+///     format_default(&42, &0x4001),
+///     "PID: 42     SigDfl: 2  [0x0000000000004001]: HUP, TERM",
+/// );
+/// ````
+pub fn format_default(pid: &u32, map: &u64) -> String {
+    format_line(
+        &format!("PID: {:<6}", pid),
+        "SigDfl:",
+        map,
+        &NameStyle::default(),
+    )
+}
+
+/// Returns the human-readable lines for a signal bitmap shared by
+/// several processes (see [`crate::scan::dedupe`]): the bitmap with the
+/// number of processes, aligned with [`format_text`], followed by
+/// their PIDs.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_mask_group, scan::MaskGroup, *};
+/// let group: MaskGroup = MaskGroup {
+///     bits: SigSet::from_bits(0x4000),
+///     pids: vec![42, 43, 44],
+/// };
+/// assert_eq!(
+///     format_mask_group(&BitmapType::SigBlk, &group),
+///     "PIDS: 3     SigBlk: 1  [0x0000000000004000]: TERM\n\
+///      \x20           42, 43, 44",
+/// );
+/// ````
+pub fn format_mask_group(typ: &BitmapType, group: &MaskGroup) -> String {
+    let pid_lst: Vec<String> = group.pids.iter().map(u32::to_string).collect();
+
+    format!(
+        "{}\n{}",
+        format_line(
+            &format!("PIDS: {:<5}", group.pids.len()),
+            &typ.to_string(),
+            &group.bits.bits(),
+            &NameStyle::default(),
+        ),
+        wrap(
+            &format!("{}{}", " ".repeat(HDR_WIDTH), pid_lst.join(", ")),
+            HDR_WIDTH,
+        ),
+    )
+}
+
+/// Returns the human-readable line for the signal bitmap of the parent
+/// of a process, aligned with [`format_text`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_parent, BitmapType, NameStyle};
+/// assert_eq!(
+///     format_parent(&1, &BitmapType::SigBlk, &0x1, &NameStyle::default()),
+///     "PPID: 1     SigBlk: 1  [0x0000000000000001]: HUP",
+/// );
+/// ````
+pub fn format_parent(
+    ppid: &u32,
+    typ: &BitmapType,
+    map: &u64,
+    style: &NameStyle,
+) -> String {
+    format_line(&format!("PPID: {:<5}", ppid), &typ.to_string(), map, style)
+}
+
+/// Returns the human-readable line for the signals added to (`+`) and
+/// removed from (`-`) a signal bitmap, aligned with [`format_text`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_delta, BitmapType, NameStyle, SigSet};
+/// let diff = SigSet::from_bits(0x4001).diff(&SigSet::from_bits(0x3));
+/// assert_eq!(
+///     format_delta(&BitmapType::SigBlk, &diff, &NameStyle::default()),
+///     "DIFF:       SigBlk: +TERM, -INT",
+/// );
+/// ````
+pub fn format_delta(
+    typ: &BitmapType,
+    diff: &SigSetDiff,
+    style: &NameStyle,
+) -> String {
+    let mut sig_lst: Vec<String> = Vec::new();
+
+    sig_lst.extend(
+        style
+            .names(&diff.added)
+            .iter()
+            .map(|name| format!("+{}", name)),
+    );
+    sig_lst.extend(
+        style
+            .names(&diff.removed)
+            .iter()
+            .map(|name| format!("-{}", name)),
+    );
+
+    wrap(
+        &format!("{:<11} {} {}", "DIFF:", typ, fmt_list(&sig_lst)),
+        DIFF_WIDTH,
+    )
+}
+
+/// Returns the human-readable line for the signals in a signal bitmap
+/// likely inherited from the parent (see [`Report::inherited`]),
+/// aligned with [`format_text`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_inherited, BitmapType, NameStyle, SigSet};
+/// assert_eq!(
+///     format_inherited(
+///         &BitmapType::SigIgn,
+///         &SigSet::from_bits(0x1000),
+///         &NameStyle::default(),
+///     ),
+///     "INHR:       SigIgn: PIPE",
+/// );
+/// ````
+pub fn format_inherited(
+    typ: &BitmapType,
+    set: &SigSet,
+    style: &NameStyle,
+) -> String {
+    wrap(
+        &format!("{:<11} {} {}", "INHR:", typ, fmt_list(&style.names(set))),
+        DIFF_WIDTH,
+    )
+}
+
+/// Returns the human-readable line for the signals a process reads
+/// through signalfd descriptors (see [`Report::signalfd`]), aligned
+/// with [`format_text`]; blocked signals among them are read that way
+/// rather than left pending.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_signalfd, NameStyle, SigSet};
+/// assert_eq!(
+///     format_signalfd(&SigSet::from_bits(0x4003), &NameStyle::default()),
+///     "SIGFD:      HUP, INT, TERM",
+/// );
+/// ````
+pub fn format_signalfd(set: &SigSet, style: &NameStyle) -> String {
+    wrap(
+        &format!("{:<11} {}", "SIGFD:", fmt_list(&style.names(set))),
+        HDR_WIDTH,
+    )
+}
+
+/// Returns the human-readable line for the metadata of a process (e.g.
+/// its command name and user), as `key=value` pairs aligned with
+/// [`format_text`]; values with whitespace are quoted.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::format::format_meta;
+/// assert_eq!(
+///     format_meta(&[("comm", "nginx".into()), ("cmdline", "nginx: worker".into())]),
+///     "META:       comm=nginx cmdline=\"nginx: worker\"",
+/// );
+/// ````
+pub fn format_meta<K: Display>(meta: &[(K, String)]) -> String {
+    let pairs: Vec<String> = meta
+        .iter()
+        .map(|(key, val)| match val.contains(char::is_whitespace) {
+            true => format!("{}={:?}", key, val),
+            false => format!("{}={}", key, val),
+        })
+        .collect();
+
+    wrap(&format!("{:<11} {}", "META:", pairs.join(" ")), HDR_WIDTH)
+}
+
+/// Returns the human-readable lines for the realtime signals in a
+/// bitmap of pending signals, numbered in the order they are delivered
+/// (lowest number first, after any standard signal), and a note that
+/// they queue; `None` if there are none.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_rt_order, BitmapType, NameStyle, SigSet};
+/// assert_eq!(
+///     format_rt_order(
+///         &BitmapType::SigPnd,
+///         &SigSet::from_bits(0xa_0000_4000),
+///         &NameStyle::default(),
+///     ),
+///     Some(String::from(
+///         "ORDR:       SigPnd: 1. RTMIN, 2. RTMIN+2\n\
+///          NOTE:       Realtime signals queue; the lowest is delivered first.",
+///     )),
+/// );
+/// ````
+pub fn format_rt_order(
+    typ: &BitmapType,
+    set: &SigSet,
+    style: &NameStyle,
+) -> Option<String> {
+    let sig_lst: Vec<String> = set
+        .iter()
+        .filter(|idx| category(idx) == SignalCategory::Realtime)
+        .enumerate()
+        .map(|(pos, idx)| format!("{}. {}", pos + 1, style.name(&idx)))
+        .collect();
+
+    if sig_lst.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "{}\n{}",
+        wrap(
+            &format!("{:<11} {} {}", "ORDR:", typ, sig_lst.join(", ")),
+            DIFF_WIDTH,
+        ),
+        wrap(
+            &format!(
+                "{:<11} Realtime signals queue; the lowest is delivered first.",
+                "NOTE:",
+            ),
+            HDR_WIDTH,
+        ),
+    ))
+}
+
+/// Returns the human-readable lines for the details of a process in
+/// extended reports (see [`Status`]), aligned with [`format_delta`]; the
+/// mask is left out if the kernel doesn't report it, and the tracer if
+/// the process isn't traced.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_status, report::Status};
+/// let status: Status = Status {
+///     state: String::from("t (tracing stop)"),
+///     umask: Some(0o022),
+///     tracer: Some(1234),
+/// };
+/// assert_eq!(
+///     format_status(&status),
+///     "STATE:      t (tracing stop)\n\
+///      UMASK:      0022\n\
+///      TRACER:     1234",
+/// );
+/// ````
+pub fn format_status(status: &Status) -> String {
+    let mut out: String = format!("{:<11} {}", "STATE:", status.state);
+
+    if let Some(umask) = status.umask {
+        out.push_str(&format!("\n{:<11} {:04o}", "UMASK:", umask));
+    }
+
+    if let Some(tpid) = status.tracer {
+        out.push_str(&format!("\n{:<11} {}", "TRACER:", tpid));
+    }
+
+    out
+}
+
+/// Returns the human-readable rendering of a report: the line for the
+/// bitmap of the process (see [`format_text`]) and its details in an
+/// extended report (see [`format_status`]), followed by the lines
+/// for the bitmap of its parent and the delta if it has one, for
+/// ignored signals, the ones likely inherited from the parent, and the
+/// signals read through signalfd if asked for; signals are named in
+/// `style`.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::render_text, BitmapType, NameStyle, Report, SigSet};
+/// let report: Report = Report {
+///     pid: 42,
+///     map: BitmapType::SigBlk,
+///     bits: SigSet::from_bits(0x4000),
+///     parent: Some((1, SigSet::from_bits(0x1))),
+///     warnings: vec![],
+///     status: None,
+///     shared: None,
+///     signalfd: None,
+/// };
+/// assert_eq!(
+///     render_text(&report, &NameStyle::default()),
+///     "PID: 42     SigBlk: 1  [0x0000000000004000]: TERM\n\
+///      PPID: 1     SigBlk: 1  [0x0000000000000001]: HUP\n\
+///      DIFF:       SigBlk: +TERM, -HUP\n",
+/// );
+/// ````
+pub fn render_text(report: &Report, style: &NameStyle) -> String {
+    let mut out: String = String::new();
+
+    match report.shared {
+        Some(shd) => out.push_str(&format_pending(
+            &report.pid,
+            &report.bits,
+            &shd,
+            style,
+        )),
+        None => out.push_str(&format_text(
+            &report.pid,
+            &report.map,
+            &report.bits.bits(),
+            style,
+        )),
+    }
+    out.push('\n');
+
+    if let Some(status) = &report.status {
+        out.push_str(&format_status(status));
+        out.push('\n');
+    }
+
+    if let (Some((ppid, par)), Some(diff)) = (report.parent, report.delta()) {
+        out.push_str(&format_parent(&ppid, &report.map, &par.bits(), style));
+        out.push('\n');
+        out.push_str(&format_delta(&report.map, &diff, style));
+        out.push('\n');
+    }
+
+    if let Some(inh) = report.inherited() {
+        out.push_str(&format_inherited(&report.map, &inh, style));
+        out.push('\n');
+    }
+
+    if let Some(sfd) = &report.signalfd {
+        out.push_str(&format_signalfd(sfd, style));
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Returns the machine-readable rendering of a report: the record for
+/// the bitmap of the process (see [`format_null`]); the parent isn't
+/// part of the record.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::render_null, BitmapType, NameStyle, Report, SigSet};
+/// let report: Report = Report {
+///     pid: 42,
+///     map: BitmapType::SigBlk,
+///     bits: SigSet::from_bits(0x4000),
+///     parent: None,
+///     warnings: vec![],
+///     status: None,
+///     shared: None,
+///     signalfd: None,
+/// };
+/// assert_eq!(
+///     render_null(&report, &NameStyle::default()),
+///     "42\tSigBlk\t1\t0x0000000000004000\tTERM\0",
+/// );
+/// ````
+pub fn render_null(report: &Report, style: &NameStyle) -> String {
+    format_null(&report.pid, &report.map, &report.bits.bits(), style)
+}
+
+/// Returns the human-readable lines for a signal bitmap in binary, in
+/// groups of 8 bits from the most significant bit, with the upper and
+/// lower 32 bits on separate lines aligned with [`format_delta`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_binary, BitmapType};
+/// assert_eq!(
+///     format_binary(&BitmapType::SigBlk, &0x4001),
+///     "BIN:        SigBlk: 00000000 00000000 00000000 00000000\n\
+///      \x20                   00000000 00000000 01000000 00000001",
+/// );
+/// ````
+pub fn format_binary(typ: &BitmapType, map: &u64) -> String {
+    let groups: Vec<String> = map
+        .to_be_bytes()
+        .iter()
+        .map(|byte| format!("{:08b}", byte))
+        .collect();
+
+    format!(
+        "{:<11} {} {}\n{}{}",
+        "BIN:",
+        typ,
+        groups[..4].join(" "),
+        " ".repeat(DIFF_WIDTH),
+        groups[4..].join(" "),
+    )
+}
+
+/// Returns a grid of the 64 bits of a signal bitmap, eight to a row from
+/// the most significant bit, with the bit indices above the bits and the
+/// name of the signal under each set bit (bit `n` is signal `n + 1`).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::format::format_bits;
+/// let grid: String = format_bits(&0x4001);
+/// let rows: Vec<&str> = grid.lines().rev().take(3).collect();
+/// assert_eq!(rows[0].split_whitespace().collect::<Vec<_>>(), ["HUP"]);
+/// assert!(rows[1].ends_with("0        1"));
+/// assert!(rows[2].starts_with("bit: 7        6"));
+/// ````
+pub fn format_bits(map: &u64) -> String {
+    let mut rows: Vec<String> = Vec::new();
+
+    for byte in (0..8).rev() {
+        let bits: Vec<u8> = (0..8).rev().map(|bit| byte * 8 + bit).collect();
+        let mut idx_ln: String = String::from("bit: ");
+        let mut bit_ln: String = String::from("     ");
+        let mut sig_ln: String = String::from("     ");
+
+        for bit in &bits {
+            let set: bool = map & (0x1_u64 << bit) != 0;
+            let name: String = match set {
+                true => sigabbrev_np(&(bit + 1)),
+                false => String::new(),
+            };
+
+            idx_ln.push_str(&format!("{:<w$}", bit, w = BIT_WIDTH));
+            bit_ln.push_str(&format!("{:<w$}", set as u8, w = BIT_WIDTH));
+            sig_ln.push_str(&format!("{:<w$}", name, w = BIT_WIDTH));
+        }
+
+        let mut row: String =
+            format!("{}\n{}", idx_ln.trim_end(), bit_ln.trim_end());
+        if !sig_ln.trim().is_empty() {
+            row.push('\n');
+            row.push_str(sig_ln.trim_end());
+        }
+        rows.push(row);
+    }
+
+    rows.join("\n\n")
+}
+
+/// Returns the compact line for a signal bitmap: the PID, then a glyph
+/// per signal at a fixed position (`#` if it is set, `.` if not), from
+/// signal 1 on the left to 64 on the right in groups of 8, so that the
+/// bitmaps of many processes can be compared at a glance; see
+/// [`format_compact_ruler`] and [`format_legend`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::format::format_compact;
+/// assert_eq!(
+///     format_compact(&42, &0x4001),
+///     "42      #....... ......#. ........ ........ ........ ........ \
+///      ........ ........",
+/// );
+/// ````
+pub fn format_compact(pid: &u32, map: &u64) -> String {
+    let set: SigSet = SigSet::from_bits(*map);
+    let groups: Vec<String> = (0..NR_SIGS / GLYPH_GROUP)
+        .map(|grp| {
+            (0x1..=GLYPH_GROUP)
+                .map(|off| match set.contains(&(grp * GLYPH_GROUP + off)) {
+                    true => GLYPH_SET,
+                    false => GLYPH_UNSET,
+                })
+                .collect()
+        })
+        .collect();
+
+    format!("{:<w$} {}", pid, groups.join(" "), w = GLYPH_PID_WIDTH)
+}
+
+/// Returns the header line for compact lines (see [`format_compact`]):
+/// the type of the signal bitmap, then the number of the first signal
+/// of each group of glyphs, above it.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_compact_ruler, BitmapType};
+/// assert_eq!(
+///     format_compact_ruler(&BitmapType::SigBlk),
+///     "SigBlk: 1        9        17       25       33       41       \
+///      49       57",
+/// );
+/// ````
+pub fn format_compact_ruler(typ: &BitmapType) -> String {
+    let marks: String = (0..NR_SIGS / GLYPH_GROUP)
+        .map(|grp| {
+            format!(
+                "{:<w$}",
+                grp * GLYPH_GROUP + 1,
+                w = GLYPH_GROUP as usize + 1
+            )
+        })
+        .collect();
+
+    format!("{:<w$} {}", typ, marks.trim_end(), w = GLYPH_PID_WIDTH)
+}
+
+/// Returns the legend of the compact view (see [`format_compact`]): the
+/// glyphs, then the signals of each group of glyphs, in order.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::format::format_legend;
+/// let legend: String = format_legend();
+/// let lines: Vec<&str> = legend.lines().take(3).collect();
+/// assert_eq!(
+///     lines,
+///     [
+///         "GLYPHS:     # set, . unset",
+///         "1-8:        HUP, INT, QUIT, ILL, TRAP, ABRT, BUS, FPE",
+///         "9-16:       KILL, USR1, SEGV, USR2, PIPE, ALRM, TERM, STKFLT",
+///     ],
+/// );
+/// ````
+pub fn format_legend() -> String {
+    let mut lines: Vec<String> = vec![format!(
+        "{:<11} {} set, {} unset",
+        "GLYPHS:", GLYPH_SET, GLYPH_UNSET
+    )];
+
+    for grp in 0..NR_SIGS / GLYPH_GROUP {
+        let first: u8 = grp * GLYPH_GROUP + 1;
+        let last: u8 = first + GLYPH_GROUP - 1;
+        let sig_lst: Vec<String> =
+            (first..=last).map(|idx| sigabbrev_np(&idx)).collect();
+
+        lines.push(wrap(
+            &format!(
+                "{:<11} {}",
+                format!("{}-{}:", first, last),
+                sig_lst.join(", ")
+            ),
+            HDR_WIDTH,
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Returns the line the command line prints for a process by default
+/// (`sig-bitmap --pid <pid>`): its pending signals, named in `style`,
+/// as [`format_text`] lays them out, so that tools embedding the crate
+/// show the same line. (The summary of a group of processes is
+/// [`format_group_summary`].)
+///
+/// # Stability
+///
+/// The line is not changed other than in a release that Cargo's semver
+/// rules treat as breaking, so it can be compared across versions.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_summary, NameStyle, ProcessSignals, SigSet};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     pid: 42,
+///     pending: SigSet::from_bits(0x4001),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     format_summary(&sigs, &NameStyle::default()),
+///     "PID: 42     SigPnd: 2  [0x0000000000004001]: HUP, TERM",
+/// );
+/// ````
+pub fn format_summary(sigs: &ProcessSignals, style: &NameStyle) -> String {
+    format_text(&sigs.pid, &BitmapType::SigPnd, &sigs.pending.bits(), style)
+}
+
+/// Returns the human-readable line for a signal bitmap that wasn't read
+/// from a process, e.g. one decoded from a byte dump.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::format::format_decoded;
+/// assert_eq!(
+///     format_decoded(&0x4001),
+///     "SigSet: 2  [0x0000000000004001]: HUP, TERM",
+/// );
+/// ````
+pub fn format_decoded(map: &u64) -> String {
+    decoded_line(map, &interpret(map))
+}
+
+/// Returns the human-readable line for a signal bitmap that wasn't read
+/// from a process, naming the signals after the table of another
+/// architecture (see [`ArchTable`]), aligned with [`format_decoded`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{arch::ArchTable, format::format_decoded_arch};
+/// let table: ArchTable = ArchTable::new([(1, "HUP"), (16, "USR1")]);
+/// assert_eq!(
+///     format_decoded_arch(&0x8001, &table),
+///     "SigSet: 2  [0x0000000000008001]: HUP, USR1",
+/// );
+/// ````
+pub fn format_decoded_arch(map: &u64, table: &ArchTable) -> String {
+    decoded_line(map, &table.interpret(map))
+}
+
+// Return the (wrapped) line for a decoded signal bitmap and the names
+// of its signals.
+fn decoded_line(map: &u64, sig_lst: &[String]) -> String {
+    wrap(
+        &format!(
+            "SigSet: {:<2} [0x{:016x}]: {}",
+            sig_lst.len(),
+            map,
+            fmt_list(sig_lst),
+        ),
+        DECODE_WIDTH,
+    )
+}
+
+// Return the (wrapped) line for a signal bitmap, prefixed with `hdr`
+// and the label `lbl` (e.g. the bitmap type), naming the signals in
+// `style`.
+fn format_line(hdr: &str, lbl: &str, map: &u64, style: &NameStyle) -> String {
+    let sig_lst: Vec<String> = style.names(&SigSet::from_bits(*map));
+
+    wrap(
+        &format!(
+            "{} {} {:<2} [0x{:016x}]: {}",
+            hdr,
+            lbl,
+            sig_lst.len(),
+            map,
+            fmt_list(&sig_lst),
+        ),
+        SUB_WIDTH,
+    )
+}
+
+// Return the comma-separated list of signals, or `NONE`.
+fn fmt_list(sig_lst: &[String]) -> String {
+    match sig_lst.is_empty() {
+        true => String::from("NONE"),
+        false => sig_lst.join(", "),
+    }
+}
+
+// Return the JSON object of `format_who_json`, in the specified
+// version of its structure (`None` for the first, unversioned one).
+fn who_json(users: &SignalUsers, version: Option<u32>) -> String {
+    let list = |pids: &[u32]| -> String {
+        let pid_lst: Vec<String> = pids.iter().map(u32::to_string).collect();
+        format!("[{}]", pid_lst.join(","))
+    };
+
+    format!(
+        "{{{}\"signal\":\"{}\",\"number\":{},\"caught\":{},\"blocked\":{},\
+         \"ignored\":{}}}",
+        version
+            .map(|ver| format!("\"schema_version\":{},", ver))
+            .unwrap_or_default(),
+        sigabbrev_np(&users.signal),
+        users.signal,
+        list(&users.caught),
+        list(&users.blocked),
+        list(&users.ignored),
+    )
+}
+
+// Return the JSON object of `format_scan_json`, in the specified version
+// of its structure.
+fn scan_json(snap: &Snapshot, version: u32) -> String {
+    let bits = |set: &SigSet| format!("\"0x{:016x}\"", set.bits());
+    let sigs: &ProcessSignals = &snap.signals;
+
+    format!(
+        "{{\"schema_version\":{},\"pid\":{},\"start\":{},\"comm\":\"{}\",\
+         \"pending\":{},\"shared_pending\":{},\"blocked\":{},\
+         \"ignored\":{},\"caught\":{}}}",
+        version,
+        snap.pid,
+        snap.start,
+        json_escape(&snap.comm),
+        bits(&sigs.pending),
+        bits(&sigs.shared_pending),
+        bits(&sigs.blocked),
+        bits(&sigs.ignored),
+        bits(&sigs.caught),
+    )
+}
+
+// Wrap a line to the display width, indenting subsequent lines.
+fn wrap(line: &str, indent: usize) -> String {
+    let sub_fmt: &str = &" ".repeat(indent);
+
+    fill(
+        line,
+        Options::new(MAX_WIDTH)
+            .subsequent_indent(sub_fmt)
+            .word_splitter(textwrap::WordSplitter::NoHyphenation)
+            .break_words(false),
+    )
+}
+
+/// Returns a machine-readable record for a signal bitmap: the PID,
+/// bitmap type, signal count, hex bitmap and comma-separated signal
+/// names, separated by tabs and terminated by a NUL byte.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_null, BitmapType, NameStyle};
+/// assert_eq!(
+///     format_null(&42, &BitmapType::SigBlk, &0x4001, &NameStyle::default()),
+///     "42\tSigBlk\t2\t0x0000000000004001\tHUP,TERM\0",
+/// );
+/// ````
+pub fn format_null(
+    pid: &u32,
+    typ: &BitmapType,
+    map: &u64,
+    style: &NameStyle,
+) -> String {
+    let sig_lst: Vec<String> = style.names(&SigSet::from_bits(*map));
+
+    format!(
+        "{}\t{}\t{}\t0x{:016x}\t{}\0",
+        pid,
+        typ.to_string().trim_end_matches(':'),
+        sig_lst.len(),
+        map,
+        sig_lst.join(","),
+    )
+}
+
+/// Returns the line describing what happens to a signal sent to a
+/// process.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_outcome, Outcome};
+/// assert_eq!(
+///     format_outcome(&42, &15, &Outcome::Handled),
+///     "PID: 42     TERM:    handled (caught)",
+/// );
+/// ````
+pub fn format_outcome(pid: &u32, idx: &u8, out: &Outcome) -> String {
+    format!(
+        "PID: {:<6} {:<8} {}",
+        pid,
+        format!("{}:", sigabbrev_np(idx)),
+        out,
+    )
+}
+
+/// Returns the line for a finding of the signal health checks; see
+/// [`crate::doctor::diagnose`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{doctor::*, format::format_finding};
+/// let finding: Finding = Finding {
+///     severity: Severity::Warning,
+///     check: Check::Lint,
+///     message: String::from("termination signals ignored: TERM"),
+///     rule: Some(Rule::TermIgnored),
+/// };
+/// assert_eq!(
+///     format_finding(&42, &finding),
+///     "PID: 42     WARNING  lint:    termination signals ignored: TERM",
+/// );
+/// ````
+pub fn format_finding(pid: &u32, fnd: &Finding) -> String {
+    finding_line(&format!("PID: {:<6}", pid), fnd)
+}
+
+// Return the (wrapped) line for a finding, prefixed with `hdr`.
+fn finding_line(hdr: &str, fnd: &Finding) -> String {
+    wrap(
+        &format!(
+            "{} {:<8} {:<8} {}",
+            hdr,
+            fnd.severity.to_string(),
+            format!("{}:", fnd.check),
+            fnd.message,
+        ),
+        FIND_WIDTH,
+    )
+}
+
+/// Returns the lines for a group of processes in a scan summary (see
+/// [`crate::scan::summarize`]): the name of the group and its number
+/// of processes, followed by its distinct bitmaps (see
+/// [`format_mask_group`]) and the findings, each with the number of
+/// processes it applies to.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{doctor::*, format::format_group_summary, scan::*, *};
+/// let summary: GroupSummary = GroupSummary {
+///     group: String::from("app.service"),
+///     count: 2,
+///     masks: vec![MaskGroup {
+///         bits: SigSet::from_bits(0x4000),
+///         pids: vec![42, 43],
+///     }],
+///     findings: vec![(
+///         Finding {
+///             severity: Severity::Warning,
+///             check: Check::Lint,
+///             message: String::from("termination signals ignored: TERM"),
+///             rule: Some(Rule::TermIgnored),
+///         },
+///         2,
+///     )],
+/// };
+/// assert_eq!(
+///     format_group_summary(&BitmapType::SigIgn, &summary),
+///     "GROUP: app.service (2 processes)\n\
+///      PIDS: 2     SigIgn: 1  [0x0000000000004000]: TERM\n\
+///      \x20           42, 43\n\
+///      PIDS: 2     WARNING  lint:    termination signals ignored: TERM",
+/// );
+/// ````
+pub fn format_group_summary(
+    typ: &BitmapType,
+    summary: &GroupSummary,
+) -> String {
+    let mut lines: Vec<String> = vec![format!(
+        "GROUP: {} ({} {})",
+        summary.group,
+        summary.count,
+        match summary.count {
+            1 => "process",
+            _ => "processes",
+        },
+    )];
+
+    for group in &summary.masks {
+        lines.push(format_mask_group(typ, group));
+    }
+
+    for (fnd, count) in &summary.findings {
+        lines.push(finding_line(&format!("PIDS: {:<5}", count), fnd));
+    }
+
+    lines.join("\n")
+}
+
+/// Returns the lines for a group of processes of the `killable` report
+/// (see [`crate::scan::killable`]): the name of the group, followed by
+/// the processes a `TERM` ends (`TERM`), those handling it (`HNDL`), and
+/// those needing a `KILL` (`KILL`), leaving out the empty ones.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_killable, scan::KillGroup};
+/// let group: KillGroup = KillGroup {
+///     group: String::from("/system.slice/app.service"),
+///     ends: vec![42, 43],
+///     needs_kill: vec![44],
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     format_killable(&group),
+///     "CGROUP: /system.slice/app.service\n\
+///      \x20   TERM:   2   42, 43\n\
+///      \x20   KILL:   1   44",
+/// );
+/// ````
+pub fn format_killable(group: &KillGroup) -> String {
+    let mut lines: Vec<String> = vec![format!("CGROUP: {}", group.group)];
+
+    for (lbl, pids) in [
+        ("TERM:", &group.ends),
+        ("HNDL:", &group.handled),
+        ("KILL:", &group.needs_kill),
+    ] {
+        if !pids.is_empty() {
+            lines.push(format_pids(lbl, pids));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Returns the lines for a process that started (`SPAWN`) or runs a
+/// new program (`EXEC`) (see [`Event`]): the program, the bitmaps it
+/// starts with (blocked, ignored and caught signals), and the findings
+/// of the checks on them.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{audit::Event, format::format_audit, *};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     ignored: SigSet::from_bits(0x1000),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     format_audit(&42, &Event::Exec, "/usr/bin/app", &sigs, &[]),
+///     "EXEC: 42    /usr/bin/app\n\
+///      PID: 42     SigBlk: 0  [0x0000000000000000]: NONE\n\
+///      PID: 42     SigIgn: 1  [0x0000000000001000]: PIPE\n\
+///      PID: 42     SigCgt: 0  [0x0000000000000000]: NONE",
+/// );
+/// ````
+pub fn format_audit(
+    pid: &u32,
+    event: &Event,
+    prog: &str,
+    sigs: &ProcessSignals,
+    findings: &[Finding],
+) -> String {
+    let lbl: &str = match event {
+        Event::Spawn => "SPAWN:",
+        Event::Exec => "EXEC:",
+    };
+    let mut lines: Vec<String> = vec![format!(
+        "{:<HDR_WIDTH$}{}",
+        format!("{} {}", lbl, pid),
+        prog
+    )];
+
+    for typ in [BitmapType::SigBlk, BitmapType::SigIgn, BitmapType::SigCgt] {
+        lines.push(format_text(
+            pid,
+            &typ,
+            &sigs.bitmap(&typ).bits(),
+            &NameStyle::default(),
+        ));
+    }
+
+    for fnd in findings {
+        lines.push(format_finding(pid, fnd));
+    }
+
+    lines.join("\n")
+}
+
+/// Returns the lines for the processes catching (`CGT`), blocking
+/// (`BLK`) or ignoring (`IGN`) a signal (see [`SignalUsers`]), with the
+/// number of each.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_who, scan::SignalUsers};
+/// let users: SignalUsers = SignalUsers {
+///     signal: 10,
+///     caught: vec![42, 43],
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     format_who(&users),
+///     "SIGNAL: USR1 (10)\n    \
+///      CGT:    2   42, 43\n    \
+///      BLK:    0\n    \
+///      IGN:    0",
+/// );
+/// ````
+pub fn format_who(users: &SignalUsers) -> String {
+    let mut lines: Vec<String> = vec![format!(
+        "SIGNAL: {} ({})",
+        sigabbrev_np(&users.signal),
+        users.signal
+    )];
+
+    for (lbl, pids) in [
+        ("CGT:", &users.caught),
+        ("BLK:", &users.blocked),
+        ("IGN:", &users.ignored),
+    ] {
+        lines.push(format_pids(lbl, pids));
+    }
+
+    lines.join("\n")
+}
+
+/// Returns the lines for the differences between the built-in table and
+/// that of the host (see [`crate::arch::self_test`]), or a line saying
+/// they match if there are none.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{arch::Mismatch, format::format_self_test};
+/// assert_eq!(
+///     format_self_test(&[Mismatch::RtMin(32, 33)]),
+///     "DIFF:       realtime signals start at 32 here, but at 33 on the host",
+/// );
+/// assert_eq!(
+///     format_self_test(&[]),
+///     "OK:         the built-in table matches the host",
+/// );
+/// ````
+pub fn format_self_test(diffs: &[Mismatch]) -> String {
+    if diffs.is_empty() {
+        return format!("{:<11} the built-in table matches the host", "OK:");
+    }
+
+    diffs
+        .iter()
+        .map(|diff| wrap(&format!("{:<11} {}", "DIFF:", diff), HDR_WIDTH))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Returns the lines for the processes catching (`CGT`), blocking
+/// (`BLK`) or ignoring (`IGN`) a signal on several hosts (see
+/// [`FleetUsers`]), with the number of each; processes are listed as
+/// `HOST:PID`.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{fleet::FleetUsers, format::format_fleet};
+/// let users: FleetUsers = FleetUsers {
+///     signal: 10,
+///     caught: vec![(String::from("web1"), 42), (String::from("web2"), 7)],
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     format_fleet(&users),
+///     "SIGNAL: USR1 (10)\n    \
+///      CGT:    2   web1:42, web2:7\n    \
+///      BLK:    0\n    \
+///      IGN:    0",
+/// );
+/// ````
+pub fn format_fleet(users: &FleetUsers) -> String {
+    let mut lines: Vec<String> = vec![format!(
+        "SIGNAL: {} ({})",
+        sigabbrev_np(&users.signal),
+        users.signal
+    )];
+
+    for (lbl, procs) in [
+        ("CGT:", &users.caught),
+        ("BLK:", &users.blocked),
+        ("IGN:", &users.ignored),
+    ] {
+        let ids: Vec<String> = procs
+            .iter()
+            .map(|(host, pid)| format!("{}:{}", host, pid))
+            .collect();
+        lines.push(format_pids(lbl, &ids));
+    }
+
+    lines.join("\n")
+}
+
+/// Returns a row of values separated by `sep` (e.g. a tab or a comma),
+/// for tab- or comma-separated output. Values with the separator, a
+/// double quote or a line break in them are double-quoted, with double
+/// quotes doubled (as in RFC 4180).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::format::format_row;
+/// assert_eq!(format_row(&["42", "app", "HUP, INT"], '\t'), "42\tapp\tHUP, INT");
+/// assert_eq!(format_row(&["42", "app", "HUP, INT"], ','), "42,app,\"HUP, INT\"");
+/// ````
+pub fn format_row<S: AsRef<str>>(vals: &[S], sep: char) -> String {
+    let cells: Vec<String> = vals
+        .iter()
+        .map(|val| {
+            let val: &str = val.as_ref();
+            match val.contains([sep, '"', '\n', '\r']) {
+                true => format!("\"{}\"", val.replace('"', "\"\"")),
+                false => val.to_string(),
+            }
+        })
+        .collect();
+
+    cells.join(&sep.to_string())
+}
+
+/// Returns a JSON object for the processes catching, blocking or
+/// ignoring a signal (see [`SignalUsers`]), on a single line. The object
+/// carries the version of its structure (`schema_version`, 2), which is
+/// only bumped when fields are changed or removed, so that scripts can
+/// tell; fields may be added without it. Version 1 is written by
+/// [`format_who_json_v1`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_who_json, scan::SignalUsers};
+/// let users: SignalUsers = SignalUsers {
+///     signal: 10,
+///     caught: vec![42, 43],
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     format_who_json(&users),
+///     "{\"schema_version\":2,\"signal\":\"USR1\",\"number\":10,\"caught\":[42,43],\
+///      \"blocked\":[],\"ignored\":[]}",
+/// );
+/// ````
+pub fn format_who_json(users: &SignalUsers) -> String {
+    who_json(users, Some(SCHEMA_VERSION))
+}
+
+/// Returns a JSON object for the processes using a signal, as
+/// [`format_who_json`] does, in version 1 of its structure (`--format
+/// json-v1`): without `schema_version`, for scripts written against it.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_who_json_v1, scan::SignalUsers};
+/// let users: SignalUsers = SignalUsers {
+///     signal: 10,
+///     ignored: vec![7],
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     format_who_json_v1(&users),
+///     "{\"signal\":\"USR1\",\"number\":10,\"caught\":[],\"blocked\":[],\
+///      \"ignored\":[7]}",
+/// );
+/// ````
+pub fn format_who_json_v1(users: &SignalUsers) -> String {
+    who_json(users, None)
+}
+
+/// Returns a JSON object for a process in a scan (see [`Snapshot`]), on
+/// a single line, with all of its signal bitmaps; read back with
+/// [`crate::scan::parse_snapshot`]. Like [`format_who_json`], the object
+/// carries the version of its structure (`schema_version`, 2). The
+/// command name is escaped as a JSON string, so any name is read back
+/// as is.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_scan_json, scan::*, ProcessSignals};
+/// let snap: Snapshot = Snapshot {
+///     pid: 42,
+///     start: 9000,
+///     comm: String::from("app"),
+///     signals: ProcessSignals {
+///         pid: 42,
+///         ..Default::default()
+///     },
+/// };
+/// assert_eq!(
+///     format_scan_json(&snap),
+///     "{\"schema_version\":2,\"pid\":42,\"start\":9000,\"comm\":\"app\",\
+///      \"pending\":\"0x0000000000000000\",\"shared_pending\":\"0x0000000000000000\",\
+///      \"blocked\":\"0x0000000000000000\",\"ignored\":\"0x0000000000000000\",\
+///      \"caught\":\"0x0000000000000000\"}",
+/// );
+///
+/// let odd: Snapshot = Snapshot {
+///     comm: String::from("a\"b\\c\nd\te\u{1}"),
+///     ..snap
+/// };
+/// assert!(format_scan_json(&odd)
+///     .contains(r#""comm":"a\"b\\c\nd\te\u0001""#));
+/// assert_eq!(parse_snapshot(&format_scan_json(&odd)).unwrap(), odd);
+/// ````
+pub fn format_scan_json(snap: &Snapshot) -> String {
+    scan_json(snap, SCHEMA_VERSION)
+}
+
+/// Returns a JSON object for a process in a scan, as
+/// [`format_scan_json`] does, in version 1 of its structure (`--format
+/// json-v1`), which has the same fields.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_scan_json_v1, scan::Snapshot};
+/// let snap: Snapshot = Snapshot {
+///     pid: 42,
+///     start: 9000,
+///     comm: String::from("app"),
+///     ..Default::default()
+/// };
+/// assert!(format_scan_json_v1(&snap).starts_with("{\"schema_version\":1,"));
+/// ````
+pub fn format_scan_json_v1(snap: &Snapshot) -> String {
+    scan_json(snap, 1)
+}
+
+/// Returns a JSON object alerting of findings about a process (e.g. to
+/// post to a webhook), on a single line: the process (PID, start time
+/// and program), the most severe of the findings, the findings, and
+/// each signal bitmap as `bits` and decoded `signals`. A `text` field
+/// sums up the findings for receivers showing only that (e.g. Slack).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{doctor::*, format::format_alert, *};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     pid: 42,
+///     ignored: SigSet::from_bits(0x4000),
+///     ..Default::default()
+/// };
+/// let finding: Finding = Finding {
+///     severity: Severity::Warning,
+///     check: Check::Lint,
+///     message: String::from("termination signals ignored: TERM"),
+///     rule: Some(Rule::TermIgnored),
+/// };
+/// assert_eq!(
+///     format_alert(&9000, "/usr/bin/app", &sigs, &[finding]),
+///     "{\"schema_version\":2,\
+///      \"text\":\"sig-bitmap: 42 (/usr/bin/app): WARNING lint: termination signals ignored: TERM\",\
+///      \"pid\":42,\"start\":9000,\"program\":\"/usr/bin/app\",\"severity\":\"WARNING\",\
+///      \"findings\":[{\"severity\":\"WARNING\",\"check\":\"lint\",\"rule\":\"term-ignored\",\
+///      \"message\":\"termination signals ignored: TERM\"}],\
+///      \"pending\":{\"bits\":\"0x0000000000000000\",\"signals\":[]},\
+///      \"shared_pending\":{\"bits\":\"0x0000000000000000\",\"signals\":[]},\
+///      \"blocked\":{\"bits\":\"0x0000000000000000\",\"signals\":[]},\
+///      \"ignored\":{\"bits\":\"0x0000000000004000\",\"signals\":[\"TERM\"]},\
+///      \"caught\":{\"bits\":\"0x0000000000000000\",\"signals\":[]}}",
+/// );
+/// ````
+pub fn format_alert(
+    start: &u64,
+    prog: &str,
+    sigs: &ProcessSignals,
+    findings: &[Finding],
+) -> String {
+    let quote = |text: &str| format!("\"{}\"", json_escape(text));
+    let mask = |set: &SigSet| {
+        let names: Vec<String> =
+            set.to_names().iter().map(|name| quote(name)).collect();
+        format!(
+            "{{\"bits\":\"0x{:016x}\",\"signals\":[{}]}}",
+            set.bits(),
+            names.join(",")
+        )
+    };
+    let text: Vec<String> = findings
+        .iter()
+        .map(|fnd| {
+            format!(
+                "sig-bitmap: {} ({}): {} {}: {}",
+                sigs.pid, prog, fnd.severity, fnd.check, fnd.message
+            )
+        })
+        .collect();
+    let items: Vec<String> = findings
+        .iter()
+        .map(|fnd| {
+            format!(
+                "{{\"severity\":\"{}\",\"check\":\"{}\",\"rule\":{},\
+                 \"message\":{}}}",
+                fnd.severity,
+                fnd.check,
+                fnd.rule
+                    .map(|rule| quote(&rule.to_string()))
+                    .unwrap_or_else(|| String::from("null")),
+                quote(&fnd.message),
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\"schema_version\":{},\"text\":{},\"pid\":{},\"start\":{},\
+         \"program\":{},\"severity\":{},\"findings\":[{}],\"pending\":{},\
+         \"shared_pending\":{},\"blocked\":{},\"ignored\":{},\"caught\":{}}}",
+        SCHEMA_VERSION,
+        quote(&text.join("\n")),
+        sigs.pid,
+        start,
+        quote(prog),
+        findings
+            .iter()
+            .map(|fnd| fnd.severity)
+            .max()
+            .map(|sev| quote(&sev.to_string()))
+            .unwrap_or_else(|| String::from("null")),
+        items.join(","),
+        mask(&sigs.pending),
+        mask(&sigs.shared_pending),
+        mask(&sigs.blocked),
+        mask(&sigs.ignored),
+        mask(&sigs.caught),
+    )
+}
+
+/// Returns the lines for the processes that appeared (`NEW`) and
+/// disappeared (`GONE`) between two scans, and those whose specified
+/// type of signal bitmap changed (`CHNG`), see [`ScanDiff`]: the PID and
+/// command name of each, followed by its bitmap (as in the new scan,
+/// but for the ones gone), aligned with [`format_text`], and for the
+/// ones changed, the signals added and removed (see [`format_delta`]).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_scan_diff, scan::*, *};
+/// let old: Snapshot = Snapshot {
+///     pid: 42,
+///     start: 9000,
+///     comm: String::from("app"),
+///     ..Default::default()
+/// };
+/// let new: Snapshot = Snapshot {
+///     signals: ProcessSignals {
+///         blocked: SigSet::from_bits(0x4000),
+///         ..Default::default()
+///     },
+///     ..old.clone()
+/// };
+/// let diff: ScanDiff = ScanDiff {
+///     changed: vec![(old, new)],
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     format_scan_diff(&BitmapType::SigBlk, &diff),
+///     "CHNG:       42 (app)\n\
+///      PID: 42     SigBlk: 1  [0x0000000000004000]: TERM\n\
+///      DIFF:       SigBlk: +TERM",
+/// );
+/// ````
+pub fn format_scan_diff(typ: &BitmapType, diff: &ScanDiff) -> String {
+    let style: NameStyle = NameStyle::default();
+    let entry = |lbl: &str, snap: &Snapshot| -> String {
+        format!(
+            "{:<11} {} ({})\n{}",
+            lbl,
+            snap.pid,
+            snap.comm,
+            format_text(
+                &snap.pid,
+                typ,
+                &snap.signals.bitmap(typ).bits(),
+                &style,
+            ),
+        )
+    };
+    let mut lines: Vec<String> = Vec::new();
+
+    for snap in &diff.appeared {
+        lines.push(entry("NEW:", snap));
+    }
+
+    for snap in &diff.disappeared {
+        lines.push(entry("GONE:", snap));
+    }
+
+    for (old, new) in &diff.changed {
+        lines.push(entry("CHNG:", new));
+        lines.push(format_delta(
+            typ,
+            &new.signals.bitmap(typ).diff(&old.signals.bitmap(typ)),
+            &style,
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// Returns the lines for the findings that appeared (`NEW`) and cleared
+/// (`GONE`) between two scans (see [`FindingDiff`]), each with its
+/// group.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_finding_diff, scan::FindingDiff};
+/// let diff: FindingDiff = FindingDiff::new(
+///     &[],
+///     &[String::from("app\tWARNING\tlint\ttermination signals ignored: QUIT")],
+/// );
+/// assert_eq!(
+///     format_finding_diff(&diff),
+///     "NEW:        app: WARNING  lint:    termination signals ignored: QUIT",
+/// );
+/// ````
+pub fn format_finding_diff(diff: &FindingDiff) -> String {
+    let mut lines: Vec<String> = Vec::new();
+
+    for (lbl, recs) in [("NEW:", &diff.added), ("GONE:", &diff.removed)] {
+        for rec in recs {
+            let fields: Vec<&str> = rec.splitn(4, '\t').collect();
+            let line: String = match fields[..] {
+                [group, sev, chk, msg] => format!(
+                    "{:<11} {}: {:<8} {:<8} {}",
+                    lbl,
+                    group,
+                    sev,
+                    format!("{}:", chk),
+                    msg,
+                ),
+                _ => format!("{:<11} {}", lbl, rec),
+            };
+            lines.push(wrap(&line, HDR_WIDTH));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Returns the line for the number of processes catching and blocking a
+/// realtime signal (see [`RtUsage`]).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_rt_usage, scan::RtUsage};
+/// let rt: RtUsage = RtUsage { signal: 34, caught: 3, blocked: 1 };
+/// assert_eq!(
+///     format_rt_usage(&rt),
+///     "RT: 34      RTMIN     SigCgt: 3     SigBlk: 1",
+/// );
+/// ````
+pub fn format_rt_usage(rt: &RtUsage) -> String {
+    format!(
+        "{:<HDR_WIDTH$}{:<BIT_WIDTH$} {} {:<5} {} {}",
+        format!("RT: {}", rt.signal),
+        sigabbrev_np(&rt.signal),
+        BitmapType::SigCgt,
+        rt.caught,
+        BitmapType::SigBlk,
+        rt.blocked,
+    )
+}
+
+// Return the (wrapped) line for a list of PIDs (or other IDs of
+// processes), with the label `lbl`.
+fn format_pids<T: Display>(lbl: &str, pids: &[T]) -> String {
+    let pid_lst: Vec<String> = pids.iter().map(T::to_string).collect();
+
+    wrap(
+        &format!("    {:<7} {:<3} {}", lbl, pid_lst.len(), pid_lst.join(", ")),
+        PIDS_WIDTH,
+    )
+}
+
+/// Returns the lines for a tally of the signals seen in a bitmap of a
+/// process sampled every `interval` milliseconds (see [`Tally`]): the
+/// number of samples, followed by each signal seen, the number of
+/// samples it was seen in, and roughly for how long.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{format::format_tally, sample::Tally, *};
+/// let mut tally: Tally = Tally::default();
+/// for bits in [0x4000, 0x4000, 0x0, 0x0] {
+///     tally.add(&SigSet::from_bits(bits));
+/// }
+/// assert_eq!(
+///     format_tally(&42, &BitmapType::SigPnd, &tally, &100),
+///     "PID: 42     SigPnd: 4 samples, every 100ms\n\
+///      \x20           TERM     2    ~200ms",
+/// );
+/// ````
+pub fn format_tally(
+    pid: &u32,
+    typ: &BitmapType,
+    tally: &Tally,
+    interval: &u64,
+) -> String {
+    let mut lines: Vec<String> = vec![format!(
+        "PID: {:<6} {} {} samples, every {}",
+        pid,
+        typ,
+        tally.samples(),
+        fmt_millis(interval),
+    )];
+
+    for (idx, hits) in tally.seen() {
+        lines.push(format!(
+            "{}{:<8} {:<4} ~{}",
+            " ".repeat(HDR_WIDTH),
+            sigabbrev_np(&idx),
+            hits,
+            fmt_millis(&(hits as u64 * interval)),
+        ));
+    }
+
+    if lines.len() == 1 {
+        lines.push(format!("{}NONE", " ".repeat(HDR_WIDTH)));
+    }
+
+    lines.join("\n")
+}
+
+// Return a duration in milliseconds, in seconds from a second up.
+fn fmt_millis(ms: &u64) -> String {
+    match ms {
+        _ if *ms < 1000 => format!("{}ms", ms),
+        _ if ms.is_multiple_of(1000) => format!("{}s", ms / 1000),
+        _ => format!("{:.1}s", *ms as f64 / 1000.0),
+    }
+}
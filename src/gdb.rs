@@ -0,0 +1,150 @@
+//! Annotation of gdb output: signal sets (`sigset_t`) and signal numbers
+//! (`siginfo_t`) printed by gdb are followed by the names of the signals,
+//! so that e.g. `print $_siginfo` or `print *sa` can be piped through.
+use crate::signals::{sigabbrev_np, SigSet, NR_SIGS};
+
+// Fields holding a signal set: glibc's `sigset_t` and the kernel's.
+static SET_FIELDS: &[&str] = &["__val = {", "sig = {"];
+
+// Fields holding a signal number: `siginfo_t`.
+static NUM_FIELDS: &[&str] = &["si_signo = "];
+
+// Parse a number as printed by gdb, in hex (`/x`) or decimal.
+fn parse_number(text: &str) -> Option<u64> {
+    match text.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse::<u64>().ok(),
+    }
+}
+
+// Return the offset of the first field in the text (at the start of a
+// word), and the field.
+fn next_field(text: &str) -> Option<(usize, &'static str)> {
+    SET_FIELDS
+        .iter()
+        .chain(NUM_FIELDS)
+        .filter_map(|field| {
+            text.match_indices(field)
+                .find(|(pos, _)| {
+                    !text[..*pos].ends_with(|c: char| {
+                        c.is_ascii_alphanumeric() || c == '_'
+                    })
+                })
+                .map(|(pos, _)| (pos, *field))
+        })
+        .min_by_key(|(pos, _)| *pos)
+}
+
+/// Returns a line of gdb output with the signals of the signal sets and
+/// numbers in it appended in angle brackets, as gdb does for its own
+/// annotations (e.g. `<repeats 15 times>`); other text is left as is.
+///
+/// Sets are read from the first word of glibc's `sigset_t` (`__val`) or
+/// the kernel's (`sig`), i.e. signals 1 to 64 on 64-bit targets, in hex
+/// or decimal; signal numbers from `si_signo` of `siginfo_t`.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::gdb::annotate;
+/// assert_eq!(
+///     annotate("$1 = {__val = {0x4001, 0x0 <repeats 15 times>}}"),
+///     "$1 = {__val = {0x4001, 0x0 <repeats 15 times>} <HUP, TERM>}",
+/// );
+/// assert_eq!(
+///     annotate("$2 = {si_signo = 17, si_errno = 0, si_code = 1,"),
+///     "$2 = {si_signo = 17 <CHLD>, si_errno = 0, si_code = 1,",
+/// );
+/// ````
+pub fn annotate(line: &str) -> String {
+    let mut out: String = String::with_capacity(line.len());
+    let mut rest: &str = line;
+
+    while let Some((pos, field)) = next_field(rest) {
+        out.push_str(&rest[..pos + field.len()]);
+        rest = &rest[pos + field.len()..];
+
+        let (end, note): (usize, Option<String>) = match SET_FIELDS
+            .contains(&field)
+        {
+            true => match rest.find('}') {
+                Some(end) => (
+                    end + 1,
+                    rest[..end]
+                        .split(',')
+                        .next()
+                        .and_then(|word| word.split_whitespace().next())
+                        .and_then(parse_number)
+                        .map(|map| match SigSet::from_bits(map).to_names() {
+                            names if names.is_empty() => String::from("NONE"),
+                            names => names.join(", "),
+                        }),
+                ),
+                None => (0, None),
+            },
+            false => {
+                let end: usize = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                (
+                    end,
+                    rest[..end]
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|idx| (0x1..=NR_SIGS).contains(idx))
+                        .map(|idx| sigabbrev_np(&idx)),
+                )
+            }
+        };
+
+        out.push_str(&rest[..end]);
+        rest = &rest[end..];
+
+        if let Some(note) = note {
+            out.push_str(&format!(" <{}>", note));
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate() {
+        let tests: Vec<(&str, &str)> = vec![
+            ("", ""),
+            (
+                "SIGHUP        Yes\tYes\tYes\t\tHangup",
+                "SIGHUP        Yes\tYes\tYes\t\tHangup",
+            ),
+            (
+                "$1 = {__val = {16385, 0 <repeats 15 times>}}",
+                "$1 = {__val = {16385, 0 <repeats 15 times>} <HUP, TERM>}",
+            ),
+            (
+                "$2 = {__val = {0x0 <repeats 16 times>}}",
+                "$2 = {__val = {0x0 <repeats 16 times>} <NONE>}",
+            ),
+            (
+                "$3 = {sa_mask = {sig = {0x10000}}, blocked = {sig = {0x4000}}}",
+                "$3 = {sa_mask = {sig = {0x10000} <CHLD>}, \
+                 blocked = {sig = {0x4000} <TERM>}}",
+            ),
+            (
+                "$4 = {si_signo = 11, si_errno = 0, ssi_signo = 11,",
+                "$4 = {si_signo = 11 <SEGV>, si_errno = 0, ssi_signo = 11,",
+            ),
+            ("$5 = {si_signo = 0,", "$5 = {si_signo = 0,"),
+            ("$6 = {xsig = {0x1}}", "$6 = {xsig = {0x1}}"),
+            ("$7 = {__val = {0x1,", "$7 = {__val = {0x1,"),
+            ("$8 = {__val = {<optimized out>}}", "$8 = {__val = {<optimized out>}}"),
+        ];
+
+        for test in tests {
+            assert_eq!(annotate(test.0), test.1);
+        }
+    }
+}
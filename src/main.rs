@@ -1,8 +1,257 @@
 use clap::Parser;
-use sig_bitmap::{sig_bitmap, SigBitmapArgs};
+#[cfg(unix)]
+use sig_bitmap::sink::SyslogSink;
+use sig_bitmap::{
+    procfs::{proc_supported, set_status_path},
+    report::Warning,
+    sig_annotate, sig_audit, sig_bitmap, sig_compose, sig_decode, sig_doctor,
+    sig_gdb_filter, sig_legend, sig_merge, sig_render, sig_replay, sig_resolve,
+    sig_sample, sig_scan, sig_self_test, sig_simulate, sig_verify, sig_version,
+    sig_watch, sig_who,
+    sink::{FileSink, OutputSink, StdoutSink, WebhookSink},
+    AuditArgs, Cli, Command, Report, SigBitmapArgs,
+};
+use std::{
+    io::{Error, ErrorKind},
+    path::PathBuf,
+};
 
 /// Parse command line arguments, display the bitmap.
 fn main() {
-    let args: SigBitmapArgs = SigBitmapArgs::parse();
-    sig_bitmap(&args);
+    let cli: Cli = Cli::parse();
+
+    if let Some(template) = &cli.status_path {
+        if let Err(err) = set_status_path(template) {
+            eprintln!("sig-bitmap: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    if cli.version {
+        sig_version(cli.verbose);
+        return;
+    }
+
+    // Only commands decoding bitmaps given to them work without /proc.
+    let offline: bool = matches!(
+        cli.command,
+        Some(
+            Command::Compose(_)
+                | Command::Decode(_)
+                | Command::Replay(_)
+                | Command::Merge(_)
+                | Command::Legend
+                | Command::SelfTest
+                | Command::Annotate
+                | Command::GdbFilter
+        )
+    );
+
+    if !offline {
+        if let Err(err) = proc_supported() {
+            eprintln!("sig-bitmap: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    match cli.command {
+        Some(Command::Simulate(args)) => {
+            if let Err(err) = sig_simulate(&args) {
+                eprintln!("sig-bitmap: {}: {}", args.pid, err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Compose(args)) => {
+            if let Err(err) = sig_compose(&args) {
+                eprintln!("sig-bitmap: {}", err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Decode(args)) => {
+            if let Err(err) = sig_decode(&args) {
+                eprintln!("sig-bitmap: {}", err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Doctor(args)) => {
+            if let Err(err) = sig_doctor(&args) {
+                eprintln!("sig-bitmap: {}: {}", args.pid, err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Verify(args)) => match sig_verify(&args) {
+            Ok(false) => {}
+            Ok(true) => std::process::exit(2),
+            Err(err) => {
+                eprintln!("sig-bitmap: {}: {}", args.pid, err);
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Scan(args)) => {
+            match sink(&args.output, args.syslog, &args.webhook)
+                .and_then(|mut sink| sig_scan(&args, sink.as_mut()))
+            {
+                Ok(false) => {}
+                Ok(true) => std::process::exit(2),
+                Err(err) => {
+                    eprintln!("sig-bitmap: {}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Command::Sample(args)) => {
+            if let Err(err) = sig_sample(&args) {
+                eprintln!("sig-bitmap: {}: {}", args.pid, err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Who(args)) => {
+            if let Err(err) = sig_who(&args) {
+                eprintln!("sig-bitmap: {}", err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Audit(args)) => {
+            if let Err(err) = audit(&args) {
+                eprintln!("sig-bitmap: {}", err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Replay(args)) => {
+            if let Err(err) = sig_replay(&args) {
+                eprintln!("sig-bitmap: {}", err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Merge(args)) => {
+            if let Err(err) = sig_merge(&args) {
+                eprintln!("sig-bitmap: {}", err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Legend) => sig_legend(),
+        Some(Command::SelfTest) => match sig_self_test() {
+            Ok(false) => {}
+            Ok(true) => std::process::exit(2),
+            Err(err) => {
+                eprintln!("sig-bitmap: {}", err);
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Annotate) => {
+            if let Err(err) = sig_annotate() {
+                eprintln!("sig-bitmap: {}", err);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::GdbFilter) => {
+            if let Err(err) = sig_gdb_filter() {
+                eprintln!("sig-bitmap: {}", err);
+                std::process::exit(1);
+            }
+        }
+        None => {
+            if let Some(args) = cli.args {
+                let args: SigBitmapArgs = match sig_resolve(args) {
+                    Ok(args) => args,
+                    Err(err) => {
+                        eprintln!("sig-bitmap: {}", err);
+                        std::process::exit(1);
+                    }
+                };
+
+                match run(&args) {
+                    Ok(false) => {}
+                    Ok(true) => std::process::exit(2),
+                    Err(err) => {
+                        match args.pids.is_empty()
+                            && args.name.is_none()
+                            && args.exe.is_none()
+                        {
+                            true => {
+                                eprintln!("sig-bitmap: {}: {}", args.pid, err)
+                            }
+                            false => eprintln!("sig-bitmap: {}", err),
+                        }
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Open the output sink picked on the command line: a file, syslog or
+/// a webhook, or else the standard output.
+fn sink(
+    output: &Option<PathBuf>,
+    syslog: bool,
+    webhook: &Option<String>,
+) -> Result<Box<dyn OutputSink>, Error> {
+    Ok(match (output, syslog, webhook) {
+        (Some(path), _, _) => Box::new(FileSink::append(path)?),
+        #[cfg(unix)]
+        (_, true, _) => Box::new(SyslogSink::open()?),
+        #[cfg(not(unix))]
+        (_, true, _) => {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "syslog is only supported on Unix",
+            ))
+        }
+        (_, _, Some(url)) => Box::new(WebhookSink::new(url)?),
+        _ => Box::new(StdoutSink),
+    })
+}
+
+/// Record the bitmaps processes start with, writing to the chosen sink.
+fn audit(args: &AuditArgs) -> Result<(), Error> {
+    let mut sink: Box<dyn OutputSink> =
+        sink(&args.output, args.syslog, &args.webhook)?;
+
+    sig_audit(args, sink.as_mut())
+}
+
+/// Display (or watch) the bitmap, writing to the chosen output sink;
+/// returns `true` if the bitmap was asserted to be empty, but isn't.
+fn run(args: &SigBitmapArgs) -> Result<bool, Error> {
+    let mut sink: Box<dyn OutputSink> =
+        sink(&args.output, args.syslog, &args.webhook)?;
+
+    if args.watch {
+        return sig_watch(args, sink.as_mut()).map(|_| false);
+    }
+
+    let report: Report = sig_bitmap(args);
+    sink.emit(&sig_render(args, &report))?;
+
+    for warn in &report.warnings {
+        eprintln!("sig-bitmap: {}: {}", args.pid, warn);
+    }
+
+    if !args.assert_empty {
+        return Ok(false);
+    }
+
+    if report.warnings.iter().any(|warn| {
+        matches!(warn, Warning::Unreadable(_) | Warning::Malformed(..))
+    }) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "can't assert that the bitmap is empty",
+        ));
+    }
+
+    let names: Vec<String> = report.merged().to_names();
+
+    if !names.is_empty() {
+        eprintln!(
+            "sig-bitmap: {}: {} not empty: {}",
+            args.pid,
+            report.map.to_string().trim_end_matches(':'),
+            names.join(", ")
+        );
+    }
+
+    Ok(!names.is_empty())
 }
@@ -0,0 +1,164 @@
+//! Metadata of processes shown next to their bitmaps in outputs listing
+//! several processes (scans and watches), so that they can be told apart
+//! without cross-referencing `ps`; it is read once per process and kept
+//! until the PID is reused.
+use crate::procfs::{
+    proc_cgroup, proc_cmdline, proc_name, proc_start_time, proc_uid,
+};
+use std::{collections::HashMap, fmt, fs};
+
+// Clock ticks per second of the start times in `/proc/<pid>/stat`
+// (`USER_HZ`, fixed at 100 on the architectures Linux runs on).
+const USER_HZ: u64 = 100;
+
+// Maximum number of characters of a command line shown.
+const CMDLINE_MAX: usize = 40;
+
+/// A piece of metadata of a process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[non_exhaustive]
+pub enum Field {
+    /// The command name of the process.
+    Comm,
+
+    /// The command line of the process (truncated).
+    Cmdline,
+
+    /// The (real) user running the process, by name if known.
+    User,
+
+    /// The cgroup of the process.
+    Cgroup,
+
+    /// When the process started, in seconds after boot.
+    Start,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Field::Comm => write!(f, "comm"),
+            Field::Cmdline => write!(f, "cmdline"),
+            Field::User => write!(f, "user"),
+            Field::Cgroup => write!(f, "cgroup"),
+            Field::Start => write!(f, "start"),
+        }
+    }
+}
+
+// Metadata of a process, by field.
+type Meta = Vec<(Field, String)>;
+
+/// Metadata of processes read so far, by PID, along with their start
+/// times to tell a reused PID.
+#[derive(Clone, Debug, Default)]
+pub struct MetaCache {
+    users: Option<HashMap<u32, String>>,
+    seen: HashMap<u32, (Option<u64>, Meta)>,
+}
+
+impl MetaCache {
+    /// Returns the metadata of a process picked by `fields`, in that
+    /// order; a field that can't be read is `?`. Metadata is read from
+    /// `/proc` the first time a process is looked up, and after its PID
+    /// is reused.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::meta::{Field, MetaCache};
+    /// let mut cache: MetaCache = MetaCache::default();
+    /// let meta = cache.lookup(&u32::MAX, &[Field::Comm, Field::Start]);
+    /// assert_eq!(
+    ///     meta,
+    ///     vec![(Field::Comm, String::from("?")), (Field::Start, String::from("?"))],
+    /// );
+    /// ````
+    pub fn lookup(
+        &mut self,
+        pid: &u32,
+        fields: &[Field],
+    ) -> Vec<(Field, String)> {
+        let start: Option<u64> = proc_start_time(pid);
+
+        if let Some((old, meta)) = self.seen.get(pid) {
+            if *old == start && meta.iter().map(|(fld, _)| fld).eq(fields) {
+                return meta.clone();
+            }
+        }
+
+        let meta: Meta = fields
+            .iter()
+            .map(|fld| {
+                let val: Option<String> = match fld {
+                    Field::Comm => proc_name(pid),
+                    Field::Cmdline => {
+                        proc_cmdline(pid).map(|cmd| truncate(&cmd))
+                    }
+                    Field::User => proc_uid(pid).map(|uid| {
+                        self.users
+                            .get_or_insert_with(user_names)
+                            .get(&uid)
+                            .cloned()
+                            .unwrap_or(uid.to_string())
+                    }),
+                    Field::Cgroup => proc_cgroup(pid),
+                    Field::Start => start.map(|tck| {
+                        format!("{}.{:02}s", tck / USER_HZ, tck % USER_HZ)
+                    }),
+                };
+                (*fld, val.unwrap_or_else(|| String::from("?")))
+            })
+            .collect();
+
+        self.seen.insert(*pid, (start, meta.clone()));
+        meta
+    }
+}
+
+// Return a command line cut to `CMDLINE_MAX` characters, ending with
+// `...` if it was cut.
+fn truncate(cmd: &str) -> String {
+    match cmd.char_indices().nth(CMDLINE_MAX) {
+        Some((end, _)) => format!("{}...", &cmd[..end]),
+        None => cmd.to_string(),
+    }
+}
+
+/// Returns the names of the users in `/etc/passwd` by user ID, or none
+/// if it can't be read.
+pub fn user_names() -> HashMap<u32, String> {
+    let text: String = fs::read_to_string("/etc/passwd").unwrap_or_default();
+
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name: &str = fields.next()?;
+            let uid: u32 = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        let long: String = "x".repeat(CMDLINE_MAX + 1);
+        let tests: Vec<(&str, String)> = vec![
+            ("", String::new()),
+            (
+                "nginx: worker process",
+                String::from("nginx: worker process"),
+            ),
+            (&long[..CMDLINE_MAX], "x".repeat(CMDLINE_MAX)),
+            (&long, format!("{}...", "x".repeat(CMDLINE_MAX))),
+        ];
+
+        for test in tests {
+            assert_eq!(truncate(test.0), test.1);
+        }
+    }
+}
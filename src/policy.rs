@@ -0,0 +1,256 @@
+//! Signal policies: the signals that must (or must not) be in each signal
+//! bitmap of a process, declared in a file (e.g. `TERM` must not be
+//! blocked, `PIPE` must be ignored), so that deployments can be gated on
+//! the signal configuration of what they start.
+use crate::{
+    doctor::{Check, Finding, Severity},
+    error::Error,
+    signals::{
+        sigabbrev_np, signal_number, BitmapType, Condition, ProcessSignals,
+    },
+};
+
+// Sections of a policy (named after the fields of `ProcessSignals`), and
+// the bitmaps they declare the signals of.
+static SECTIONS: &[(&str, BitmapType)] = &[
+    ("pending", BitmapType::SigPnd),
+    ("shared_pending", BitmapType::ShdPnd),
+    ("blocked", BitmapType::SigBlk),
+    ("ignored", BitmapType::SigIgn),
+    ("caught", BitmapType::SigCgt),
+];
+
+// Return the strings in a value: a quoted string, or an array of them.
+fn parse_strings(val: &str) -> Option<Vec<String>> {
+    let unquote = |text: &str| -> Option<String> {
+        let text: &str = text.trim();
+        ['"', '\'']
+            .iter()
+            .find_map(|q| text.strip_prefix(*q)?.strip_suffix(*q))
+            .map(str::to_string)
+    };
+
+    match val.strip_prefix('[').and_then(|val| val.strip_suffix(']')) {
+        Some(items) => items
+            .split(',')
+            .filter(|item| !item.trim().is_empty())
+            .map(unquote)
+            .collect(),
+        None => unquote(val).map(|name| vec![name]),
+    }
+}
+
+/// Returns the conditions declared in the contents of a policy file, a
+/// subset of TOML: a section per signal bitmap (`[pending]`,
+/// `[shared_pending]`, `[blocked]`, `[ignored]` or `[caught]`) with the
+/// signals that must be in it (`require`) and those that must not
+/// (`forbid`), as a string or an array of strings (which may span
+/// lines); comments (`#`) and blank lines are skipped.
+///
+/// # Errors
+///
+/// [`Error::InvalidPolicy`] for a line that isn't a known section or a
+/// `require` or `forbid` key in one, and [`Error::UnknownSignal`] for a
+/// name that doesn't match a signal.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{policy::parse_policy, BitmapType, Condition};
+/// let policy: Vec<Condition> = parse_policy(
+///     "[blocked]\nforbid = [\"TERM\", \"INT\"]\n\n[ignored]\nrequire = \"PIPE\"\n",
+/// )
+/// .unwrap();
+/// assert_eq!(policy.len(), 3);
+/// assert_eq!(policy[2].map, BitmapType::SigIgn);
+/// ````
+pub fn parse_policy(text: &str) -> Result<Vec<Condition>, Error> {
+    let mut out: Vec<Condition> = Vec::new();
+    let mut map: Option<BitmapType> = None;
+    let mut lines = text
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim());
+
+    while let Some(line) = lines.next() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let invalid = || Error::InvalidPolicy(line.to_string());
+
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|name| name.strip_suffix(']'))
+        {
+            map = Some(
+                SECTIONS
+                    .iter()
+                    .find(|(sec, _)| *sec == name.trim())
+                    .map(|(_, typ)| typ.clone())
+                    .ok_or_else(invalid)?,
+            );
+            continue;
+        }
+
+        let (key, val): (&str, &str) =
+            line.split_once('=').ok_or_else(invalid)?;
+        let set: bool = match key.trim() {
+            "require" => true,
+            "forbid" => false,
+            _ => return Err(invalid()),
+        };
+        let typ: BitmapType = map.clone().ok_or_else(invalid)?;
+        let mut val: String = val.trim().to_string();
+
+        while val.starts_with('[') && !val.ends_with(']') {
+            val.push(' ');
+            val.push_str(lines.next().ok_or_else(invalid)?);
+        }
+
+        for name in parse_strings(&val).ok_or_else(invalid)? {
+            let signal: u8 = signal_number(&name)
+                .ok_or_else(|| Error::UnknownSignal(name.clone()))?;
+
+            out.push(Condition {
+                map: typ.clone(),
+                signal,
+                set,
+            });
+        }
+    }
+
+    Ok(out)
+}
+
+/// Returns the violations of a policy by the signal bitmaps of a
+/// process, as critical findings, in the order of the policy.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{policy::*, ProcessSignals, SigSet};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     blocked: SigSet::from_bits(0x4000),
+///     ..Default::default()
+/// };
+/// let policy = parse_policy("[blocked]\nforbid = [\"TERM\"]\n").unwrap();
+/// assert_eq!(
+///     check_policy(&policy, &sigs)[0].message,
+///     "TERM must not be blocked",
+/// );
+/// ````
+pub fn check_policy(
+    policy: &[Condition],
+    sigs: &ProcessSignals,
+) -> Vec<Finding> {
+    policy
+        .iter()
+        .filter(|cnd| !cnd.holds(sigs))
+        .map(|cnd| {
+            let state: &str = match cnd.map {
+                BitmapType::SigPnd => "pending",
+                BitmapType::ShdPnd => "pending (shared)",
+                BitmapType::SigBlk => "blocked",
+                BitmapType::SigIgn => "ignored",
+                BitmapType::SigCgt => "caught",
+            };
+            let must: &str = match cnd.set {
+                true => "must",
+                false => "must not",
+            };
+
+            Finding::new(
+                Severity::Critical,
+                Check::Policy,
+                format!("{} {} be {}", sigabbrev_np(&cnd.signal), must, state),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::SigSet;
+
+    // Bitmap types, signal numbers and whether they must be set.
+    type Rules = Vec<(BitmapType, u8, bool)>;
+
+    #[test]
+    fn test_parse_policy() {
+        let tests: Vec<(&str, Result<Rules, Error>)> = vec![
+            ("", Ok(vec![])),
+            (
+                "# Web tier.\n[blocked]\nforbid = [\"TERM\", 'SIGINT',] # stop\n\n\
+                 [ignored]\nrequire = \"PIPE\"\n[caught]\nrequire = [\n  \"HUP\",\n  \
+                 \"USR1\",\n]\n",
+                Ok(vec![
+                    (BitmapType::SigBlk, 15, false),
+                    (BitmapType::SigBlk, 2, false),
+                    (BitmapType::SigIgn, 13, true),
+                    (BitmapType::SigCgt, 1, true),
+                    (BitmapType::SigCgt, 10, true),
+                ]),
+            ),
+            (
+                "[shared_pending]\nforbid = []\n",
+                Ok(vec![]),
+            ),
+            (
+                "require = [\"TERM\"]\n",
+                Err(Error::InvalidPolicy(String::from("require = [\"TERM\"]"))),
+            ),
+            (
+                "[masked]\nforbid = [\"TERM\"]\n",
+                Err(Error::InvalidPolicy(String::from("[masked]"))),
+            ),
+            (
+                "[blocked]\nallow = [\"TERM\"]\n",
+                Err(Error::InvalidPolicy(String::from("allow = [\"TERM\"]"))),
+            ),
+            (
+                "[blocked]\nforbid = [TERM]\n",
+                Err(Error::InvalidPolicy(String::from("forbid = [TERM]"))),
+            ),
+            (
+                "[blocked]\nforbid = [\"TERM\",\n",
+                Err(Error::InvalidPolicy(String::from("forbid = [\"TERM\","))),
+            ),
+            (
+                "[blocked]\nforbid = \"SIGBOGUS\"\n",
+                Err(Error::UnknownSignal(String::from("SIGBOGUS"))),
+            ),
+        ];
+
+        for test in tests {
+            assert_eq!(
+                parse_policy(test.0),
+                test.1.map(|rules| rules
+                    .into_iter()
+                    .map(|(map, signal, set)| Condition { map, signal, set })
+                    .collect())
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_policy() {
+        let policy: Vec<Condition> = parse_policy(
+            "[blocked]\nforbid = [\"TERM\", \"INT\"]\n[ignored]\n\
+             require = [\"PIPE\"]\n[shared_pending]\nrequire = \"USR1\"\n",
+        )
+        .unwrap();
+        let sigs: ProcessSignals = ProcessSignals {
+            blocked: SigSet::from_bits(0x4000),
+            ignored: SigSet::from_bits(0x1000),
+            ..Default::default()
+        };
+        let messages: Vec<String> = check_policy(&policy, &sigs)
+            .into_iter()
+            .map(|fnd| fnd.message)
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec!["TERM must not be blocked", "USR1 must be pending (shared)"],
+        );
+    }
+}
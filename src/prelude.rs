@@ -0,0 +1,20 @@
+//! The types most uses of the crate need, for a single glob import:
+//!
+//! ```
+//! use sig_bitmap::prelude::*;
+//! let set: SigSet = SigSet::from_names(["HUP", "TERM"]).unwrap();
+//! assert_eq!(set.bits(), 0x4001);
+//! let sig: Signal = "SIGTERM".parse().unwrap();
+//! assert!(set.contains(&sig.number()));
+//! ````
+//!
+//! # Stability
+//!
+//! Items are not removed from the prelude, or changed incompatibly,
+//! other than in a release that Cargo's semver rules treat as breaking.
+//! Additions are kept few, since the prelude is glob-imported and new
+//! names may clash with those of the importing crate.
+pub use crate::{
+    error::Error,
+    signals::{BitmapType, ProcessSignals, SigSet, Signal},
+};
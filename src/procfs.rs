@@ -0,0 +1,1013 @@
+//! Readers for the signal bitmaps in `/proc/<pid>/status`.
+use crate::{
+    doctor::Health,
+    error::Error as SigError,
+    report::{Report, Status, Warning},
+    scan::glob,
+    signals::{BitmapType, ProcessSignals, SigSet},
+};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufRead, BufReader, Error, ErrorKind},
+    path::{Path, PathBuf},
+    sync::{PoisonError, RwLock},
+};
+
+// Template of the path of the status file of a process, and the
+// placeholder for the PID in it.
+const STATUS_PATH: &str = "/proc/{pid}/status";
+const PID_HOLDER: &str = "{pid}";
+
+// Template set with `set_status_path`, if any.
+static STATUS_TEMPLATE: RwLock<String> = RwLock::new(String::new());
+
+// Fields of `/proc/<pid>/stat` (numbered from 1, see `proc(5)`).
+const STAT_FLAGS: usize = 9;
+const STAT_START_TIME: usize = 22;
+
+// Process flag of kernel threads (`include/linux/sched.h`).
+const PF_KTHREAD: u64 = 0x00200000;
+
+// Socket tables in `/proc/net`, and the state of their listening (TCP)
+// or bound, unconnected (UDP) sockets.
+static NET_TAB: &[(&str, &str)] =
+    &[("tcp", "0A"), ("tcp6", "0A"), ("udp", "07"), ("udp6", "07")];
+
+/// A process picked by PID, and optionally by the command name it is
+/// expected to run (as copied from kernel logs or the journal).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Target {
+    /// PID of the process.
+    pub pid: u32,
+
+    /// Command name the process is expected to run.
+    pub comm: Option<String>,
+}
+
+impl Target {
+    /// Returns the target in a PID (`1234`), or in a PID with a command
+    /// name as printed by the kernel and the journal: `nginx(1234)`,
+    /// `nginx[1234]` (a trailing `:` is dropped) or `1234/nginx`.
+    ///
+    /// # Errors
+    ///
+    /// [`SigError::InvalidTarget`] if there is no PID, or no command
+    /// name along with it.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::procfs::Target;
+    /// let target: Target = Target::parse("nginx(1234)").unwrap();
+    /// assert_eq!((target.pid, target.comm.as_deref()), (1234, Some("nginx")));
+    /// ````
+    pub fn parse(text: &str) -> Result<Target, SigError> {
+        let err = || SigError::InvalidTarget(text.to_string());
+        let spec: &str = text.trim().trim_end_matches(':');
+
+        if let Ok(pid) = spec.parse::<u32>() {
+            return Ok(Target { pid, comm: None });
+        }
+
+        let (comm, pid): (&str, &str) = match spec.chars().last() {
+            Some(')') => {
+                spec[..spec.len() - 1].rsplit_once('(').ok_or_else(err)?
+            }
+            Some(']') => {
+                spec[..spec.len() - 1].rsplit_once('[').ok_or_else(err)?
+            }
+            _ => spec
+                .split_once('/')
+                .map(|(pid, comm)| (comm, pid))
+                .ok_or_else(err)?,
+        };
+
+        match (pid.parse::<u32>(), comm.is_empty()) {
+            (Ok(pid), false) => Ok(Target {
+                pid,
+                comm: Some(comm.to_string()),
+            }),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// Returns a warning if a process runs a command other than the one it
+/// was picked by (see [`Target`]), e.g. since the PID was reused;
+/// `None` if it runs that command, if none was given, or if the name of
+/// the process can't be read.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::{proc_verify, Target};
+/// let target: Target = Target { pid: std::process::id(), comm: None };
+/// assert_eq!(proc_verify(&target), None);
+/// ````
+pub fn proc_verify(target: &Target) -> Option<Warning> {
+    let want: &String = target.comm.as_ref()?;
+
+    match proc_name(&target.pid) {
+        Some(name) if name != *want => {
+            Some(Warning::CommMismatch(target.pid, name, want.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the parsed value of the string representation of the
+/// specified type of signal bitmap for a process, or an empty
+/// bitmap if it can't be read.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{procfs::proc_bitmap, BitmapType};
+/// let bit_map: u64 = proc_bitmap(&1, &BitmapType::SigIgn);
+/// ````
+pub fn proc_bitmap(pid: &u32, typ: &BitmapType) -> u64 {
+    proc_mask(pid, typ).unwrap_or_default()
+}
+
+// Return the specified type of signal bitmap for a process, or why
+// it can't be read.
+fn proc_mask(pid: &u32, typ: &BitmapType) -> Result<u64, Warning> {
+    let text: String =
+        proc_field(pid, &typ.to_string()).ok_or(Warning::Unreadable(*pid))?;
+
+    parse_mask(&text).ok_or(Warning::Malformed(*pid, text))
+}
+
+// Parse the value of a signal bitmap field: the hex bitmap, followed by
+// anything a vendor kernel appends to it.
+fn parse_mask(text: &str) -> Option<u64> {
+    u64::from_str_radix(text.split_whitespace().next()?, 16).ok()
+}
+
+/// Returns an error of kind [`ErrorKind::Unsupported`] unless processes
+/// can be read on this platform: the readers in this module need
+/// Linux's `/proc`, and [`proc_signals`], [`read_maps`],
+/// [`scan_processes`], [`proc_signalfds`] and [`proc_health`] return
+/// this error elsewhere. Decoding bitmaps (e.g. from captures) works
+/// everywhere.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_supported;
+/// assert_eq!(
+///     proc_supported().is_ok(),
+///     cfg!(any(target_os = "linux", target_os = "android")),
+/// );
+/// ````
+pub fn proc_supported() -> Result<(), Error> {
+    match cfg!(any(target_os = "linux", target_os = "android")) {
+        true => Ok(()),
+        false => Err(Error::new(
+            ErrorKind::Unsupported,
+            "reading processes needs Linux's /proc",
+        )),
+    }
+}
+
+/// Sets the template of the path of the status files read for processes
+/// (`/proc/{pid}/status` by default), for systems exposing them
+/// elsewhere, e.g. a restricted container mounting the host's `/proc`
+/// at another path; `{pid}` is replaced with the PID. Other files of
+/// processes are still read from `/proc`.
+///
+/// # Errors
+///
+/// [`SigError::InvalidTemplate`] if the template has no `{pid}`.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::set_status_path;
+/// set_status_path("/proc/{pid}/status").unwrap();
+/// assert!(set_status_path("/proc/self/status").is_err());
+/// ````
+pub fn set_status_path(template: &str) -> Result<(), SigError> {
+    if !template.contains(PID_HOLDER) {
+        return Err(SigError::InvalidTemplate(template.to_string()));
+    }
+
+    *STATUS_TEMPLATE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = template.to_string();
+    Ok(())
+}
+
+// Return the path of the status file of a process.
+fn status_path(pid: &u32) -> String {
+    let template = STATUS_TEMPLATE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    match template.is_empty() {
+        true => STATUS_PATH.replace(PID_HOLDER, &pid.to_string()),
+        false => template.replace(PID_HOLDER, &pid.to_string()),
+    }
+}
+
+/// Returns the report of the specified type of signal bitmap for a
+/// process, along with that of its parent if `parent` is set (and the
+/// process has one). Bitmaps that can't be read are empty, and the
+/// problems (as well as signals that can't be in the bitmap, see
+/// [`Report::impossible`]) are attached to the report as warnings.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{procfs::proc_report, report::Warning, BitmapType, Report};
+/// let report: Report = proc_report(&1, &BitmapType::SigIgn, true);
+/// assert_eq!(report.parent, None);
+/// assert!(report.warnings.contains(&Warning::NoParent));
+/// ````
+pub fn proc_report(pid: &u32, typ: &BitmapType, parent: bool) -> Report {
+    let mut warnings: Vec<Warning> = Vec::new();
+    let bits: SigSet = report_mask(pid, typ, &mut warnings);
+    let parent: Option<(u32, SigSet)> = match parent {
+        true => match proc_parent(pid) {
+            Some(ppid) => Some((ppid, report_mask(&ppid, typ, &mut warnings))),
+            None => {
+                warnings.push(Warning::NoParent);
+                None
+            }
+        },
+        false => None,
+    };
+    let mut report: Report = Report {
+        pid: *pid,
+        map: typ.clone(),
+        bits,
+        parent,
+        warnings,
+        status: None,
+        shared: None,
+        signalfd: None,
+    };
+
+    if let Some(warn) = report.impossible() {
+        report.warnings.push(warn);
+    }
+
+    report
+}
+
+// Return the specified type of signal bitmap for a process, or an
+// empty bitmap (adding a warning) if it can't be read.
+fn report_mask(
+    pid: &u32,
+    typ: &BitmapType,
+    warns: &mut Vec<Warning>,
+) -> SigSet {
+    match proc_mask(pid, typ) {
+        Ok(bits) => SigSet::from_bits(bits),
+        Err(warn) => {
+            warns.push(warn);
+            SigSet::empty()
+        }
+    }
+}
+
+/// Returns the parent PID (`PPid`) of a process, or `None` if it can't
+/// be read or the process has no parent (e.g. `init`).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_parent;
+/// let ppid: Option<u32> = proc_parent(&1);
+/// ````
+pub fn proc_parent(pid: &u32) -> Option<u32> {
+    proc_field(pid, "PPid:")?
+        .parse::<u32>()
+        .ok()
+        .filter(|ppid| *ppid != 0)
+}
+
+/// Returns the start time of a process (in clock ticks after boot) from
+/// `/proc/<pid>/stat`, or `None` if the process doesn't exist. Since a
+/// recycled PID belongs to a process with a different start time, this
+/// tells samples of the same PID from different processes apart.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_start_time;
+/// let start: Option<u64> = proc_start_time(&1);
+/// ````
+pub fn proc_start_time(pid: &u32) -> Option<u64> {
+    proc_stat_field(pid, STAT_START_TIME)
+}
+
+/// Returns the details of a process shown in extended reports: its
+/// state, file mode creation mask and tracer, or `None` if the state
+/// can't be read.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_status;
+/// let status = proc_status(&std::process::id()).unwrap();
+/// assert!(status.state.starts_with('R'));
+/// assert_eq!(status.tracer, None);
+/// ````
+pub fn proc_status(pid: &u32) -> Option<Status> {
+    Some(Status {
+        state: proc_field(pid, "State:")?,
+        umask: proc_field(pid, "Umask:")
+            .and_then(|text| u32::from_str_radix(&text, 8).ok()),
+        tracer: proc_field(pid, "TracerPid:")
+            .and_then(|text| text.parse::<u32>().ok())
+            .filter(|tpid| *tpid != 0),
+    })
+}
+
+/// Returns the command name of a process (`Name`, the first 15 bytes
+/// of the executable name), or `None` if it can't be read.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_name;
+/// let name: Option<String> = proc_name(&1);
+/// ````
+pub fn proc_name(pid: &u32) -> Option<String> {
+    proc_field(pid, "Name:")
+}
+
+/// Returns the command line of a process (`/proc/<pid>/cmdline`), its
+/// arguments separated by spaces, or `None` if it can't be read or is
+/// empty (e.g. for kernel threads).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_cmdline;
+/// assert!(proc_cmdline(&std::process::id()).is_some());
+/// ````
+pub fn proc_cmdline(pid: &u32) -> Option<String> {
+    let cmd: Vec<u8> = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = cmd
+        .split(|c| *c == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+
+    match args.is_empty() {
+        true => None,
+        false => Some(args.join(" ")),
+    }
+}
+
+/// Returns the path of the executable of a process (the target of
+/// `/proc/<pid>/exe`), or `None` if it can't be read (e.g. for kernel
+/// threads, or processes of other users without privileges). The
+/// ` (deleted)` suffix of replaced executables is dropped.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_exe;
+/// use std::path::PathBuf;
+/// let exe: Option<PathBuf> = proc_exe(&std::process::id());
+/// assert!(exe.is_some());
+/// ````
+pub fn proc_exe(pid: &u32) -> Option<PathBuf> {
+    let link: PathBuf = fs::read_link(format!("/proc/{}/exe", pid)).ok()?;
+
+    match link
+        .to_str()
+        .and_then(|path| path.strip_suffix(" (deleted)"))
+    {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(link),
+    }
+}
+
+/// Returns the real user ID of a process (the first of `Uid`), or
+/// `None` if it can't be read.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_uid;
+/// let uid: Option<u32> = proc_uid(&1);
+/// ````
+pub fn proc_uid(pid: &u32) -> Option<u32> {
+    proc_field(pid, "Uid:")?
+        .split_whitespace()
+        .next()?
+        .parse::<u32>()
+        .ok()
+}
+
+/// Returns `true` if a process is a kernel thread: the `PF_KTHREAD`
+/// flag is set in `/proc/<pid>/stat`, or if the flags can't be read,
+/// its command line (`/proc/<pid>/cmdline`) is empty.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_kernel_thread;
+/// assert!(!proc_kernel_thread(&std::process::id()));
+/// ````
+pub fn proc_kernel_thread(pid: &u32) -> bool {
+    match proc_stat_field(pid, STAT_FLAGS) {
+        Some(flags) => flags & PF_KTHREAD != 0,
+        None => fs::read(format!("/proc/{}/cmdline", pid))
+            .is_ok_and(|cmd| cmd.is_empty()),
+    }
+}
+
+// Return a numeric field of the stat file of a process.
+fn proc_stat_field(pid: &u32, idx: usize) -> Option<u64> {
+    let stat: String =
+        fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_stat_field(&stat, idx)
+}
+
+// Return a numeric field (numbered from 1, as in `proc(5)`) from the
+// contents of a stat file; fields are counted after the command name
+// (field 2), which is parenthesized and may have spaces and
+// parentheses in it.
+fn parse_stat_field(stat: &str, idx: usize) -> Option<u64> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.split_whitespace()
+        .nth(idx.checked_sub(3)?)?
+        .parse::<u64>()
+        .ok()
+}
+
+// Return the (trimmed) value of the line with the prefix `lpfx`
+// in the status file of a process.
+fn proc_field(pid: &u32, lpfx: &str) -> Option<String> {
+    let fread: File = File::open(status_path(pid)).ok()?;
+
+    BufReader::new(fread)
+        .lines()
+        .map_while(Result::ok)
+        .find(|line| line.starts_with(lpfx))
+        .map(|line| line.trim_start_matches(lpfx).trim().to_string())
+}
+
+/// Returns all the signal bitmaps of a process.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_signals;
+/// let sigs = proc_signals(&1);
+/// ````
+pub fn proc_signals(pid: &u32) -> Result<ProcessSignals, Error> {
+    proc_supported()?;
+
+    let fread: File = File::open(status_path(pid))?;
+    let mut sigs: ProcessSignals = ProcessSignals {
+        pid: *pid,
+        ..Default::default()
+    };
+
+    for line in BufReader::new(fread).lines() {
+        let line: String = line?;
+        let (typ, text): (&str, &str) = match line.split_once(':') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let field: &mut SigSet = match typ.trim() {
+            "SigPnd" => &mut sigs.pending,
+            "ShdPnd" => &mut sigs.shared_pending,
+            "SigBlk" => &mut sigs.blocked,
+            "SigIgn" => &mut sigs.ignored,
+            "SigCgt" => &mut sigs.caught,
+            _ => continue,
+        };
+        *field = parse_mask(text).map(SigSet::from_bits).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid {} bitmap: {}", typ.trim(), text.trim()),
+            )
+        })?;
+    }
+
+    Ok(sigs)
+}
+
+/// Returns the requested types of signal bitmap of a process, reading
+/// its status file once and stopping at the last bitmap requested;
+/// cheaper than [`proc_signals`] when only a few bitmaps are needed.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{procfs::read_maps, BitmapType, SigSet};
+/// use std::collections::HashMap;
+/// let maps: HashMap<BitmapType, SigSet> =
+///     read_maps(&1, &[BitmapType::SigIgn, BitmapType::SigCgt]).unwrap();
+/// assert_eq!(maps.len(), 2);
+/// ````
+pub fn read_maps(
+    pid: &u32,
+    types: &[BitmapType],
+) -> Result<HashMap<BitmapType, SigSet>, Error> {
+    proc_supported()?;
+
+    let fread: File = File::open(status_path(pid))?;
+    let mut maps: HashMap<BitmapType, SigSet> = HashMap::new();
+
+    for line in BufReader::new(fread).lines() {
+        let line: String = line?;
+        let typ: &BitmapType =
+            match types.iter().find(|typ| line.starts_with(&typ.to_string())) {
+                Some(typ) => typ,
+                None => continue,
+            };
+        let text: &str = line[typ.to_string().len()..].trim();
+        let bits: SigSet =
+            parse_mask(text).map(SigSet::from_bits).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid {} bitmap: {}", typ, text),
+                )
+            })?;
+
+        maps.insert(typ.clone(), bits);
+        if types.iter().all(|typ| maps.contains_key(typ)) {
+            break;
+        }
+    }
+
+    match types.iter().find(|typ| !maps.contains_key(typ)) {
+        Some(typ) => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("no {} in the status of PID {}", typ, pid),
+        )),
+        None => Ok(maps),
+    }
+}
+
+/// Returns an iterator over the signal bitmaps of every process in
+/// `/proc`, as `(pid, signals)` pairs in the order of the directory.
+/// Processes are read one at a time as the iterator advances, so memory
+/// stays flat however many there are; processes that exit (or can't be
+/// read) in between are skipped.
+///
+/// Kernel threads (see [`proc_kernel_thread`]) are skipped unless
+/// `kernel_threads` is set; their masks are rarely of interest outside
+/// of kernel work.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::scan_processes;
+/// let blocking: usize = scan_processes(false)
+///     .unwrap()
+///     .filter(|(_, sigs)| !sigs.blocked.is_empty())
+///     .count();
+/// ````
+pub fn scan_processes(
+    kernel_threads: bool,
+) -> Result<impl Iterator<Item = (u32, ProcessSignals)>, Error> {
+    proc_supported()?;
+
+    Ok(fs::read_dir("/proc")?
+        .map_while(Result::ok)
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(move |pid| kernel_threads || !proc_kernel_thread(pid))
+        .filter_map(|pid| Some((pid, proc_signals(&pid).ok()?))))
+}
+
+/// Returns the cgroup of a process from `/proc/<pid>/cgroup`: the path
+/// in the unified (v2) hierarchy, or on hosts without one, in the
+/// hierarchy systemd tracks services with (`name=systemd`). Returns
+/// `None` if it can't be read.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_cgroup;
+/// let cgroup: Option<String> = proc_cgroup(&1);
+/// ````
+pub fn proc_cgroup(pid: &u32) -> Option<String> {
+    parse_cgroup(&fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?)
+}
+
+// Return the cgroup path from the contents of a cgroup file, whose
+// lines are `hierarchy-ID:controllers:path`.
+fn parse_cgroup(text: &str) -> Option<String> {
+    let paths: Vec<(&str, &str)> = text
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(':')?;
+            rest.split_once(':')
+        })
+        .collect();
+
+    ["", "name=systemd"].iter().find_map(|want| {
+        paths
+            .iter()
+            .find(|(ctl, _)| ctl == want)
+            .map(|(_, path)| path.to_string())
+    })
+}
+
+/// Returns the PIDs of the processes whose command name (see
+/// [`proc_name`]) matches a pattern, in ascending order, leaving out
+/// the calling process. The pattern is matched against the whole name,
+/// with `*` matching any run of characters and `?` any one character.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_matching;
+/// let workers: Vec<u32> = proc_matching("nginx*").unwrap();
+/// ````
+pub fn proc_matching(pattern: &str) -> Result<Vec<u32>, Error> {
+    proc_pids(|pid| proc_name(pid).is_some_and(|name| glob(pattern, &name)))
+}
+
+/// Returns the PIDs of the processes running an executable (see
+/// [`proc_exe`]), in ascending order, leaving out the calling process.
+/// Symbolic links in the path are resolved first, so that e.g.
+/// `/usr/sbin/nginx` matches when `/usr/sbin` links to `/usr/bin`;
+/// this tells apart binaries sharing a command name.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_running;
+/// use std::path::Path;
+/// let workers: Vec<u32> = proc_running(Path::new("/usr/sbin/nginx")).unwrap();
+/// ````
+pub fn proc_running(exe: &Path) -> Result<Vec<u32>, Error> {
+    let exe: PathBuf = fs::canonicalize(exe).unwrap_or(exe.to_path_buf());
+    proc_pids(|pid| proc_exe(pid).is_some_and(|path| path == exe))
+}
+
+// Return the PIDs of the processes (other than the calling one) to keep,
+// in ascending order.
+fn proc_pids<F>(keep: F) -> Result<Vec<u32>, Error>
+where
+    F: Fn(&u32) -> bool,
+{
+    let own: u32 = std::process::id();
+    let mut pids: Vec<u32> = fs::read_dir("/proc")?
+        .map_while(Result::ok)
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| *pid != own && keep(pid))
+        .collect();
+
+    pids.sort_unstable();
+    Ok(pids)
+}
+
+/// Returns the PIDs of the processes with a TCP socket listening on, or
+/// a UDP socket bound to, a local port, in ascending order; there may
+/// be several, e.g. workers sharing a socket. Sockets are matched to
+/// processes through the socket inodes in `/proc/net/{tcp,udp}{,6}`
+/// and the file descriptors in `/proc/<pid>/fd`, so processes whose
+/// descriptors can't be read (those of other users, without
+/// privileges) aren't found.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::port_owners;
+/// let pids: Vec<u32> = port_owners(&8080).unwrap();
+/// ````
+pub fn port_owners(port: &u16) -> Result<Vec<u32>, Error> {
+    let mut inodes: Vec<u64> = Vec::new();
+
+    for (tab, state) in NET_TAB {
+        // Tables of disabled protocols (e.g. IPv6) may be missing.
+        if let Ok(text) = fs::read_to_string(format!("/proc/net/{}", tab)) {
+            inodes.extend(parse_net_inodes(&text, port, state));
+        }
+    }
+
+    let mut pids: Vec<u32> = Vec::new();
+
+    if inodes.is_empty() {
+        return Ok(pids);
+    }
+
+    for entry in fs::read_dir("/proc")?.map_while(Result::ok) {
+        let pid: u32 = match entry.file_name().to_string_lossy().parse() {
+            Ok(pid) => pid,
+            Err(_) => continue,
+        };
+        let fds: fs::ReadDir = match fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+        let owns: bool = fds.map_while(Result::ok).any(|fd| {
+            fs::read_link(fd.path()).is_ok_and(|link| {
+                link.to_str()
+                    .and_then(|link| link.strip_prefix("socket:["))
+                    .and_then(|link| link.strip_suffix(']'))
+                    .and_then(|ino| ino.parse::<u64>().ok())
+                    .is_some_and(|ino| inodes.contains(&ino))
+            })
+        });
+
+        if owns {
+            pids.push(pid);
+        }
+    }
+
+    pids.sort_unstable();
+    Ok(pids)
+}
+
+// Return the inodes of the sockets on the local port `port` in the
+// state `state`, from the contents of a socket table in `/proc/net`.
+fn parse_net_inodes(text: &str, port: &u16, state: &str) -> Vec<u64> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (_, lport) = fields.get(1)?.rsplit_once(':')?;
+
+            (u16::from_str_radix(lport, 16).ok()? == *port
+                && *fields.get(3)? == state)
+                .then(|| fields.get(9)?.parse::<u64>().ok())?
+        })
+        .collect()
+}
+
+/// Returns the signalfd descriptors of a process and the signals each
+/// one reads, in ascending order of descriptor, from the `sigmask:`
+/// lines in `/proc/<pid>/fdinfo` (only signalfd descriptors have one).
+/// Descriptors closed while they are read are skipped.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{procfs::proc_signalfds, SigSet};
+/// let fds: Vec<(u32, SigSet)> = proc_signalfds(&std::process::id()).unwrap();
+/// assert!(fds.is_empty());
+/// ````
+pub fn proc_signalfds(pid: &u32) -> Result<Vec<(u32, SigSet)>, Error> {
+    proc_supported()?;
+
+    let mut fds: Vec<(u32, SigSet)> = Vec::new();
+
+    for entry in fs::read_dir(format!("/proc/{}/fdinfo", pid))? {
+        let entry: fs::DirEntry = entry?;
+        let fd: u32 = match entry.file_name().to_string_lossy().parse() {
+            Ok(fd) => fd,
+            Err(_) => continue,
+        };
+
+        if let Some(set) = fs::read_to_string(entry.path())
+            .ok()
+            .as_deref()
+            .and_then(parse_sigmask)
+        {
+            fds.push((fd, set));
+        }
+    }
+
+    fds.sort_unstable_by_key(|(fd, _)| *fd);
+    Ok(fds)
+}
+
+// Return the signals read through a signalfd descriptor, from the
+// contents of its entry in `/proc/<pid>/fdinfo`, or `None` if it isn't
+// a signalfd descriptor.
+fn parse_sigmask(text: &str) -> Option<SigSet> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("sigmask:"))
+        .and_then(parse_mask)
+        .map(SigSet::from_bits)
+}
+
+/// Returns the thread IDs of a process, in ascending order, from
+/// `/proc/<pid>/task`.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_threads;
+/// let tids = proc_threads(&1);
+/// ````
+pub fn proc_threads(pid: &u32) -> Result<Vec<u32>, Error> {
+    let mut tids: Vec<u32> = Vec::new();
+
+    for entry in fs::read_dir(format!("/proc/{}/task", pid))? {
+        if let Ok(tid) = entry?.file_name().to_string_lossy().parse::<u32>() {
+            tids.push(tid);
+        }
+    }
+
+    tids.sort_unstable();
+    Ok(tids)
+}
+
+/// Returns what the signal health checks look at for a process: its
+/// state, signal queue (`SigQ`), and the signal bitmaps of the process
+/// and each of its threads; see [`crate::doctor::diagnose`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::procfs::proc_health;
+/// let health = proc_health(&1);
+/// ````
+pub fn proc_health(pid: &u32) -> Result<Health, Error> {
+    proc_supported()?;
+
+    let signals: ProcessSignals = proc_signals(pid)?;
+    let mut health: Health = Health {
+        signals,
+        ..Default::default()
+    };
+
+    if let Some(state) = proc_field(pid, "State:") {
+        health.state = state.chars().next().unwrap_or_default();
+    }
+
+    if let Some((queued, limit)) = proc_field(pid, "SigQ:")
+        .as_deref()
+        .and_then(|text| text.split_once('/'))
+    {
+        health.queued = queued.parse::<u64>().unwrap_or_default();
+        health.queue_limit = limit.parse::<u64>().unwrap_or_default();
+    }
+
+    // Threads may exit in between; skip those that are gone.
+    for tid in proc_threads(pid).unwrap_or_default() {
+        if let Ok(sigs) = proc_signals(&tid) {
+            health.threads.push((tid, sigs));
+        }
+    }
+
+    if health.threads.is_empty() {
+        health.threads.push((*pid, signals));
+    }
+
+    Ok(health)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A PID, and the command name given with it.
+    type Parsed = Option<(u32, Option<&'static str>)>;
+
+    #[test]
+    fn test_scan_processes() {
+        let pid: u32 = std::process::id();
+
+        assert!(scan_processes(false)
+            .unwrap()
+            .any(|(scn_pid, _)| scn_pid == pid));
+    }
+
+    #[test]
+    fn test_read_maps() {
+        let pid: u32 = std::process::id();
+        let sigs: ProcessSignals = proc_signals(&pid).unwrap();
+        let maps: HashMap<BitmapType, SigSet> =
+            read_maps(&pid, &[BitmapType::SigBlk, BitmapType::SigCgt]).unwrap();
+
+        assert_eq!(maps.len(), 2);
+        assert_eq!(maps[&BitmapType::SigBlk], sigs.blocked);
+        assert_eq!(maps[&BitmapType::SigCgt], sigs.caught);
+        assert!(read_maps(&pid, &[]).unwrap().is_empty());
+        assert!(read_maps(&u32::MAX, &[BitmapType::SigPnd]).is_err());
+    }
+
+    #[test]
+    fn test_proc_report_warnings() {
+        let report: Report = proc_report(&u32::MAX, &BitmapType::SigBlk, true);
+
+        assert_eq!(report.bits, SigSet::empty());
+        assert_eq!(
+            report.warnings,
+            vec![Warning::Unreadable(u32::MAX), Warning::NoParent],
+        );
+    }
+
+    #[test]
+    fn test_target_parse() {
+        let tests: Vec<(&str, Parsed)> = vec![
+            ("1234", Some((1234, None))),
+            (" 1234 ", Some((1234, None))),
+            ("nginx(1234)", Some((1234, Some("nginx")))),
+            ("kworker/0:1(42)", Some((42, Some("kworker/0:1")))),
+            ("(sd-pam)(900)", Some((900, Some("(sd-pam)")))),
+            ("sshd[812]:", Some((812, Some("sshd")))),
+            ("1234/nginx", Some((1234, Some("nginx")))),
+            ("nginx", None),
+            ("(1234)", None),
+            ("nginx(12a4)", None),
+            ("nginx/1234", None),
+            ("1234/", None),
+            ("", None),
+        ];
+
+        for test in tests {
+            assert_eq!(
+                Target::parse(test.0).ok(),
+                test.1.map(|(pid, comm)| Target {
+                    pid,
+                    comm: comm.map(String::from),
+                }),
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_net_inodes() {
+        let text: &str = "  sl  local_address rem_address   st tx_queue \
+             rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 \
+             00000000     0        0 662 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 0100007F:A2C4 01 00000000:00000000 00:00000000 \
+             00000000  1000        0 924 1 0000000000000000 20 4 30 10 -1
+   2: 0100007F:0035 00000000:0000 0A 00000000:00000000 00:00000000 \
+             00000000   101        0 925 1 0000000000000000 100 0 0 10 0";
+
+        assert_eq!(parse_net_inodes(text, &8080, "0A"), vec![662]);
+        assert_eq!(parse_net_inodes(text, &8080, "01"), vec![924]);
+        assert_eq!(parse_net_inodes(text, &53, "0A"), vec![925]);
+        assert_eq!(parse_net_inodes(text, &22, "0A"), vec![]);
+    }
+
+    #[test]
+    fn test_parse_cgroup() {
+        let tests: Vec<(&str, Option<String>)> = vec![
+            ("0::/system.slice/nginx.service\n", Some("/system.slice/nginx.service".into())),
+            (
+                "12:pids:/user.slice\n1:name=systemd:/system.slice/cron.service\n\
+                 0::/\n",
+                Some("/".into()),
+            ),
+            (
+                "12:pids:/user.slice\n1:name=systemd:/system.slice/cron.service\n",
+                Some("/system.slice/cron.service".into()),
+            ),
+            ("12:pids:/user.slice\n", None),
+            ("", None),
+        ];
+
+        for test in tests {
+            assert_eq!(parse_cgroup(test.0), test.1);
+        }
+    }
+
+    #[test]
+    fn test_parse_stat_field() {
+        let tests: Vec<(&str, usize, Option<u64>)> = vec![
+            (
+                "9101 (cat) R 8991 8991 8991 0 -1 4194304 83 0 0 0 0 0 0 0 \
+                 20 0 1 0 57880 2703360 321 18446744073709551615",
+                STAT_START_TIME,
+                Some(57880),
+            ),
+            (
+                "42 (a) b (c) S 1 42 42 0 -1 4194560 1 0 0 0 0 0 0 0 \
+                 20 0 1 0 12345 0 0",
+                STAT_START_TIME,
+                Some(12345),
+            ),
+            (
+                "2 (kthreadd) S 0 0 0 0 -1 2129984 0 0 0 0 0 0 0 0 \
+                 20 0 1 0 10 0 0",
+                STAT_FLAGS,
+                Some(2129984),
+            ),
+            ("42 (short) S 1 42", STAT_START_TIME, None),
+            ("42 (short) S 1 42", 2, None),
+            ("", STAT_FLAGS, None),
+        ];
+
+        for test in tests {
+            assert_eq!(parse_stat_field(test.0, test.1), test.2);
+        }
+        assert_ne!(2129984 & PF_KTHREAD, 0);
+    }
+
+    #[test]
+    fn test_parse_sigmask() {
+        let tests: Vec<(&str, Option<u64>)> = vec![
+            (
+                "pos:\t0\nflags:\t02004002\nmnt_id:\t15\nino:\t1057\n\
+                 sigmask:\t0000000000004001\n",
+                Some(0x4001),
+            ),
+            ("pos:\t0\nflags:\t02\nmnt_id:\t15\nino:\t1057\n", None),
+            (
+                "pos:\t0\nflags:\t02\nmnt_id:\t15\nino:\t1\n\
+                 tfd:        5 events:       19 data: 5  pos:0 ino:1 sdev:f\n",
+                None,
+            ),
+            ("sigmask:\tbogus\n", None),
+            ("", None),
+        ];
+
+        for test in tests {
+            assert_eq!(parse_sigmask(test.0).map(|set| set.bits()), test.1);
+        }
+    }
+
+    #[test]
+    fn test_parse_mask() {
+        let tests: Vec<(&str, Option<u64>)> = vec![
+            ("0000000000004000", Some(0x4000)),
+            ("\t0000000000010002\n", Some(0x10002)),
+            ("0000000000000001 (vendor: 0x1)", Some(0x1)),
+            ("", None),
+            ("000000000000000g", None),
+        ];
+
+        for test in tests {
+            assert_eq!(parse_mask(test.0), test.1);
+        }
+        assert_eq!(
+            set_status_path("/data/proc/status"),
+            Err(SigError::InvalidTemplate(String::from("/data/proc/status"))),
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Reports: the signal bitmap of a process (and of its parent), as a
+//! value to render or inspect, rather than printed output.
+use crate::signals::{BitmapType, SigSet, SigSetDiff};
+use std::fmt;
+
+/// The signal bitmap of a process, and optionally of its parent.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{report::Report, BitmapType, SigSet, SigSetDiff};
+/// let report: Report = Report {
+///     pid: 42,
+///     map: BitmapType::SigBlk,
+///     bits: SigSet::from_bits(0x4002),
+///     parent: Some((1, SigSet::from_bits(0x4001))),
+///     warnings: vec![],
+///     status: None,
+///     shared: None,
+///     signalfd: None,
+/// };
+/// assert_eq!(
+///     report.delta(),
+///     Some(SigSetDiff {
+///         added: SigSet::from_bits(0x2),
+///         removed: SigSet::from_bits(0x1),
+///     }),
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// PID of the process.
+    pub pid: u32,
+
+    /// Type of the signal bitmap.
+    pub map: BitmapType,
+
+    /// Signal bitmap of the process.
+    pub bits: SigSet,
+
+    /// PID and signal bitmap (of the same type) of the parent, if it
+    /// was asked for and the process has a parent.
+    pub parent: Option<(u32, SigSet)>,
+
+    /// Problems with the data behind the report, for the caller to
+    /// surface as it sees fit.
+    pub warnings: Vec<Warning>,
+
+    /// Details of the process for extended reports, if asked for.
+    pub status: Option<Status>,
+
+    /// Signals pending for the whole process (`ShdPnd`), if the merged
+    /// view of pending signals was asked for; `bits` then holds those
+    /// pending for the thread (`SigPnd`).
+    pub shared: Option<SigSet>,
+
+    /// Signals the process reads through signalfd descriptors (the
+    /// masks of all of them), if asked for; such signals are blocked on
+    /// purpose, and handled without a signal handler.
+    pub signalfd: Option<SigSet>,
+}
+
+/// Details of a process shown next to its bitmap in extended reports,
+/// from `/proc/<pid>/status`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Status {
+    /// State of the process, e.g. `S (sleeping)`; for a stopped process,
+    /// it tells a job control stop (`T (stopped)`) from a stop under a
+    /// tracer (`t (tracing stop)`). A stopped process acts on no signal
+    /// but `KILL` until it is continued.
+    pub state: String,
+
+    /// File mode creation mask, if the kernel reports it (`Umask`,
+    /// since Linux 4.7).
+    pub umask: Option<u32>,
+
+    /// PID of the process tracing it (`TracerPid`), e.g. a debugger.
+    pub tracer: Option<u32>,
+}
+
+/// A problem with the data behind a report, which didn't stop it from
+/// being built.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// The bitmap of a process (PID) couldn't be read, e.g. since the
+    /// process doesn't exist; it is taken as empty.
+    Unreadable(u32),
+
+    /// The bitmap of a process (PID) isn't a hex mask (the text); it is
+    /// taken as empty.
+    Malformed(u32, String),
+
+    /// The parent was asked for, but the process has none.
+    NoParent,
+
+    /// The process is traced by another (PID), e.g. `gdb` or `strace`;
+    /// signals sent to it stop it and go to the tracer first, which may
+    /// hold them back or discard them.
+    Traced(u32),
+
+    /// The process (PID) runs a command (the first name) other than the
+    /// one it was picked by (the second), e.g. since the PID was reused.
+    CommMismatch(u32, String, String),
+
+    /// The file descriptors of a process (PID) couldn't be read, e.g.
+    /// those of another user without privileges.
+    FdsUnreadable(u32),
+
+    /// The bitmap (of the type) has signals (the set) that can't be in
+    /// it on a live system (see [`BitmapType::impossible`]), e.g. in a
+    /// corrupt capture or a dump from another kernel.
+    Impossible(BitmapType, SigSet),
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::Unreadable(pid) => {
+                write!(f, "can't read the bitmap of PID {}", pid)
+            }
+            Warning::Malformed(pid, text) => {
+                write!(f, "malformed bitmap of PID {}: {}", pid, text)
+            }
+            Warning::NoParent => write!(f, "no parent process"),
+            Warning::Traced(pid) => write!(
+                f,
+                "traced by PID {}; signals may be intercepted by the tracer",
+                pid
+            ),
+            Warning::CommMismatch(pid, name, want) => write!(
+                f,
+                "PID {} runs {}, not {}; the PID may have been reused",
+                pid, name, want
+            ),
+            Warning::FdsUnreadable(pid) => {
+                write!(f, "can't read the file descriptors of PID {}", pid)
+            }
+            Warning::Impossible(typ, set) => write!(
+                f,
+                "{} in {} can't be set on a live system; the data may be \
+                 corrupt or from a kernel with other signal numbers",
+                set.to_names().join(", "),
+                typ.to_string().trim_end_matches(':')
+            ),
+        }
+    }
+}
+
+impl Report {
+    /// Returns the signals the process added to and removed from the
+    /// bitmap of its parent, or `None` without a parent.
+    pub fn delta(&self) -> Option<SigSetDiff> {
+        self.parent.map(|(_, par)| self.bits.diff(&par))
+    }
+
+    /// Returns the signals of the report: the bitmap, merged with the
+    /// signals pending for the whole process in the merged view of
+    /// pending signals (see [`Report::shared`]).
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::{report::Report, BitmapType, SigSet};
+    /// let report: Report = Report {
+    ///     pid: 42,
+    ///     bits: SigSet::from_bits(0x1),
+    ///     shared: Some(SigSet::from_bits(0x4000)),
+    ///     signalfd: None,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(report.merged(), SigSet::from_bits(0x4001));
+    /// ```
+    pub fn merged(&self) -> SigSet {
+        match self.shared {
+            Some(shd) => self.bits.union(&shd),
+            None => self.bits,
+        }
+    }
+
+    /// Returns a warning for the signals in the bitmap that can't be in
+    /// it on a live system (see [`BitmapType::impossible`]), or `None`
+    /// if there are none.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::{report::{Report, Warning}, BitmapType, SigSet};
+    /// let report: Report = Report {
+    ///     map: BitmapType::SigBlk,
+    ///     bits: SigSet::from_bits(0x4100),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     report.impossible(),
+    ///     Some(Warning::Impossible(BitmapType::SigBlk, SigSet::from_bits(0x100))),
+    /// );
+    /// ```
+    pub fn impossible(&self) -> Option<Warning> {
+        let set: SigSet = self.bits.intersection(&self.map.impossible());
+
+        match set.is_empty() {
+            true => None,
+            false => Some(Warning::Impossible(self.map.clone(), set)),
+        }
+    }
+
+    /// Returns the ignored signals the process likely inherited from its
+    /// parent, for a report of ignored signals (`SigIgn`) with a parent,
+    /// or `None` otherwise. Ignored dispositions survive both fork and
+    /// exec, so ignores shared with the parent likely came from it; the
+    /// ones the process added (see [`Report::delta`]) were set by the
+    /// process itself, or by a wrapper it was exec'd from (e.g. `nohup`
+    /// ignoring `HUP`).
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::{report::Report, BitmapType, SigSet};
+    /// let report: Report = Report {
+    ///     pid: 42,
+    ///     map: BitmapType::SigIgn,
+    ///     bits: SigSet::from_bits(0x1001),
+    ///     parent: Some((1, SigSet::from_bits(0x1000))),
+    ///     warnings: vec![],
+    ///     status: None,
+    ///     shared: None,
+    ///     signalfd: None,
+    /// };
+    /// assert_eq!(report.inherited(), Some(SigSet::from_bits(0x1000)));
+    /// ```
+    pub fn inherited(&self) -> Option<SigSet> {
+        match self.map {
+            BitmapType::SigIgn => {
+                self.parent.map(|(_, par)| self.bits.intersection(&par))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Sampling: how often signals show up in a bitmap sampled over time,
+//! to catch signals that a single snapshot misses (e.g. pending ones
+//! that are delivered within milliseconds).
+use crate::signals::{SigSet, NR_SIGS};
+
+/// A tally of the signals seen in a series of samples of a bitmap.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{sample::Tally, SigSet};
+/// let mut tally: Tally = Tally::default();
+/// tally.add(&SigSet::from_bits(0x4000));
+/// tally.add(&SigSet::from_bits(0x4001));
+/// tally.add(&SigSet::empty());
+/// assert_eq!(tally.samples(), 3);
+/// assert_eq!(tally.seen(), vec![(15, 2), (1, 1)]);
+/// ````
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    samples: usize,
+    hits: Vec<usize>,
+}
+
+impl Tally {
+    /// Adds a sample.
+    pub fn add(&mut self, set: &SigSet) {
+        self.hits.resize(NR_SIGS as usize, 0);
+        self.samples += 1;
+
+        for idx in set.iter() {
+            self.hits[(idx as usize) - 1] += 1;
+        }
+    }
+
+    /// Returns the number of samples added.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Returns the signals seen in any sample, with the number of
+    /// samples each was seen in, the most often seen first (ties in
+    /// order of the signal numbers).
+    pub fn seen(&self) -> Vec<(u8, usize)> {
+        let mut seen: Vec<(u8, usize)> = self
+            .hits
+            .iter()
+            .enumerate()
+            .filter(|(_, hits)| **hits != 0)
+            .map(|(bit, hits)| ((bit as u8) + 1, *hits))
+            .collect();
+
+        seen.sort_by_key(|(idx, hits)| (std::cmp::Reverse(*hits), *idx));
+        seen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signals and the number of samples they were seen in.
+    type Seen = Vec<(u8, usize)>;
+
+    #[test]
+    fn test_tally() {
+        let tests: Vec<(Vec<u64>, usize, Seen)> = vec![
+            (vec![], 0, vec![]),
+            (vec![0x0, 0x0], 2, vec![]),
+            (
+                vec![0x1, 0x8000_0000_0000_4000, 0x4001, 0x0],
+                4,
+                vec![(1, 2), (15, 2), (64, 1)],
+            ),
+        ];
+
+        for test in tests {
+            let mut tally: Tally = Tally::default();
+
+            for bits in &test.0 {
+                tally.add(&SigSet::from_bits(*bits));
+            }
+
+            assert_eq!(tally.samples(), test.1);
+            assert_eq!(tally.seen(), test.2);
+        }
+    }
+}
@@ -0,0 +1,1147 @@
+//! Machine-wide reports: summaries of the signal bitmaps of many
+//! processes at once, e.g. from [`crate::procfs::scan_processes`].
+use crate::{
+    doctor::{diagnose, Finding, Health},
+    error::Error,
+    session::{json_fields, json_unescape},
+    signals::{
+        simulate, BitmapType, DefaultAction, Outcome, ProcessSignals, SigSet,
+    },
+};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+};
+
+// Versions of the structure of the saved scans read (as written by
+// `format_scan_json` and `format_scan_json_v1`).
+const SCHEMA_VERSIONS: [u64; 2] = [1, 2];
+
+// Signal number of `TERM`.
+const SIGTERM_IDX: u8 = 0x0f;
+
+// Signal numbers of the realtime signals (the first two are reserved by
+// glibc's threading, which moves `SIGRTMIN` past them).
+static RT_RANGE: std::ops::RangeInclusive<u8> = 0x20..=0x40;
+
+/// What a `TERM` sent to a process right now would do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Termination {
+    /// Delivered, and the default action ends the process.
+    Ends,
+
+    /// Delivered to a handler, which may or may not end the process.
+    Handled,
+
+    /// Blocked or ignored; ending the process takes a `KILL`.
+    NeedsKill,
+}
+
+/// The processes in a group (e.g. a cgroup), by what a `TERM` would
+/// do to them; PIDs are in ascending order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KillGroup {
+    /// Name of the group.
+    pub group: String,
+
+    /// Processes a `TERM` ends.
+    pub ends: Vec<u32>,
+
+    /// Processes that handle a `TERM`.
+    pub handled: Vec<u32>,
+
+    /// Processes that need a `KILL`.
+    pub needs_kill: Vec<u32>,
+}
+
+/// The processes sharing a signal bitmap; PIDs are in ascending order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MaskGroup {
+    /// The signal bitmap.
+    pub bits: SigSet,
+
+    /// Processes with the bitmap.
+    pub pids: Vec<u32>,
+}
+
+/// A summary of the processes in a group (e.g. a systemd unit).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GroupSummary {
+    /// Name of the group.
+    pub group: String,
+
+    /// Number of processes in the group.
+    pub count: usize,
+
+    /// The distinct signal bitmaps in the group; see [`dedupe`].
+    pub masks: Vec<MaskGroup>,
+
+    /// The findings of the checks on the signal bitmaps of the
+    /// processes (see [`diagnose`]), with the number of processes each
+    /// applies to, most severe first.
+    pub findings: Vec<(Finding, usize)>,
+}
+
+/// How many processes catch or block a realtime signal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RtUsage {
+    /// Signal number.
+    pub signal: u8,
+
+    /// Processes that catch the signal.
+    pub caught: usize,
+
+    /// Processes that block the signal.
+    pub blocked: usize,
+}
+
+/// The processes catching, blocking or ignoring a signal; PIDs are in
+/// ascending order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SignalUsers {
+    /// Signal number.
+    pub signal: u8,
+
+    /// Processes that catch the signal.
+    pub caught: Vec<u32>,
+
+    /// Processes that block the signal.
+    pub blocked: Vec<u32>,
+
+    /// Processes that ignore the signal.
+    pub ignored: Vec<u32>,
+}
+
+/// The findings that appeared and cleared between two scans, as records
+/// (see [`finding_records`]), in order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FindingDiff {
+    /// Findings in the new scan, but not in the old one.
+    pub added: Vec<String>,
+
+    /// Findings in the old scan, but not in the new one.
+    pub removed: Vec<String>,
+}
+
+impl FindingDiff {
+    /// Returns the findings that appeared and cleared from `old` to
+    /// `new`.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::scan::FindingDiff;
+    /// let old: Vec<String> = vec!["a".into(), "b".into()];
+    /// let new: Vec<String> = vec!["b".into(), "c".into()];
+    /// let diff: FindingDiff = FindingDiff::new(&old, &new);
+    /// assert_eq!((diff.added, diff.removed), (vec!["c".into()], vec!["a".into()]));
+    /// ````
+    pub fn new(old: &[String], new: &[String]) -> FindingDiff {
+        let only = |lhs: &[String], rhs: &[String]| -> Vec<String> {
+            let mut out: Vec<String> = lhs
+                .iter()
+                .filter(|rec| !rhs.contains(rec))
+                .cloned()
+                .collect();
+            out.sort();
+            out.dedup();
+            out
+        };
+
+        FindingDiff {
+            added: only(new, old),
+            removed: only(old, new),
+        }
+    }
+
+    /// Returns `true` if no findings appeared or cleared.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A process in a scan, as saved (see [`crate::format::format_scan_json`])
+/// to compare later scans against; see [`scan_diff`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// PID of the process.
+    pub pid: u32,
+
+    /// Start time of the process (in clock ticks after boot), to tell a
+    /// recycled PID from the same process.
+    pub start: u64,
+
+    /// Command name of the process.
+    pub comm: String,
+
+    /// The signal bitmaps of the process.
+    pub signals: ProcessSignals,
+}
+
+/// The processes whose bitmap changed, appeared and disappeared between
+/// two scans (see [`scan_diff`]), in ascending order of PID.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScanDiff {
+    /// Processes in both scans with a different bitmap, as in the old
+    /// scan and in the new one.
+    pub changed: Vec<(Snapshot, Snapshot)>,
+
+    /// Processes in the new scan, but not in the old one.
+    pub appeared: Vec<Snapshot>,
+
+    /// Processes in the old scan, but not in the new one.
+    pub disappeared: Vec<Snapshot>,
+}
+
+impl ScanDiff {
+    /// Returns `true` if no process changed, appeared or disappeared.
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+            && self.appeared.is_empty()
+            && self.disappeared.is_empty()
+    }
+}
+
+/// Processes whose findings are left out of summaries (see
+/// [`summarize_quiet`]), by patterns of their command name, or of their
+/// cgroup (see [`crate::procfs::proc_cgroup`]) for patterns starting
+/// with `/`. A pattern is matched against the whole name or path, with
+/// `*` matching any run of characters and `?` any one character.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IgnoreList {
+    /// Patterns of command names.
+    pub names: Vec<String>,
+
+    /// Patterns of cgroups.
+    pub cgroups: Vec<String>,
+}
+
+impl IgnoreList {
+    /// Returns the list in a text file with a pattern per line; blank
+    /// lines and comments (`#`) are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::scan::IgnoreList;
+    /// let list: IgnoreList =
+    ///     IgnoreList::parse("# known-good\nchronyd\n/system.slice/ssh*\n");
+    /// assert!(list.matches("chronyd", "/system.slice/chronyd.service"));
+    /// assert!(list.matches("sshd", "/system.slice/ssh.service"));
+    /// assert!(!list.matches("app", "/system.slice/app.service"));
+    /// ````
+    pub fn parse(text: &str) -> IgnoreList {
+        let mut list: IgnoreList = IgnoreList::default();
+
+        for line in text.lines() {
+            let line: &str = line.split('#').next().unwrap_or_default().trim();
+
+            match line {
+                "" => continue,
+                _ if line.starts_with('/') => list.cgroups.push(line.into()),
+                _ => list.names.push(line.into()),
+            }
+        }
+
+        list
+    }
+
+    /// Returns `true` if the list has no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.cgroups.is_empty()
+    }
+
+    /// Returns `true` if a process, given its command name and cgroup,
+    /// matches any of the patterns.
+    pub fn matches(&self, comm: &str, cgroup: &str) -> bool {
+        self.names.iter().any(|pat| glob(pat, comm))
+            || self.cgroups.iter().any(|pat| glob(pat, cgroup))
+    }
+}
+
+/// Returns what a `TERM` sent to a process with the given signal
+/// bitmaps would do right now; see [`simulate`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{scan::*, ProcessSignals, SigSet};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     blocked: SigSet::from_bits(0x4000),
+///     ..Default::default()
+/// };
+/// assert_eq!(termination(&sigs), Termination::NeedsKill);
+/// ````
+pub fn termination(sigs: &ProcessSignals) -> Termination {
+    match simulate(&SIGTERM_IDX, sigs) {
+        Outcome::Default(
+            DefaultAction::Terminate | DefaultAction::CoreDump,
+        ) => Termination::Ends,
+        Outcome::Handled => Termination::Handled,
+        _ => Termination::NeedsKill,
+    }
+}
+
+/// Returns which processes a `TERM` would end, grouped by the name
+/// given with each process (e.g. its cgroup), in order of the names.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{scan::*, ProcessSignals, SigSet};
+/// let caught: ProcessSignals = ProcessSignals {
+///     caught: SigSet::from_bits(0x4000),
+///     ..Default::default()
+/// };
+/// let groups: Vec<KillGroup> = killable(vec![
+///     (42, String::from("/app.service"), ProcessSignals::default()),
+///     (7, String::from("/app.service"), caught),
+/// ]);
+/// assert_eq!(groups[0].ends, vec![42]);
+/// assert_eq!(groups[0].handled, vec![7]);
+/// ````
+pub fn killable<I>(procs: I) -> Vec<KillGroup>
+where
+    I: IntoIterator<Item = (u32, String, ProcessSignals)>,
+{
+    let mut groups: BTreeMap<String, KillGroup> = BTreeMap::new();
+
+    for (pid, name, sigs) in procs {
+        let group: &mut KillGroup =
+            groups.entry(name).or_insert_with_key(|name| KillGroup {
+                group: name.clone(),
+                ..Default::default()
+            });
+
+        match termination(&sigs) {
+            Termination::Ends => group.ends.push(pid),
+            Termination::Handled => group.handled.push(pid),
+            Termination::NeedsKill => group.needs_kill.push(pid),
+        }
+    }
+
+    groups
+        .into_values()
+        .map(|mut group| {
+            group.ends.sort_unstable();
+            group.handled.sort_unstable();
+            group.needs_kill.sort_unstable();
+            group
+        })
+        .collect()
+}
+
+/// As [`summarize`], leaving out the findings of the processes `quiet`
+/// picks by PID, e.g. known-good daemons that block signals on purpose
+/// (see [`IgnoreList`]), so that recurring reports stay actionable;
+/// their bitmaps are still summarized.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{scan::*, BitmapType, ProcessSignals, SigSet};
+/// let ign: ProcessSignals = ProcessSignals {
+///     ignored: SigSet::from_bits(0x4),
+///     ..Default::default()
+/// };
+/// let groups: Vec<GroupSummary> = summarize_quiet(
+///     vec![(42, String::from("app"), ign), (43, String::from("app"), ign)],
+///     &BitmapType::SigIgn,
+///     |pid| *pid == 42,
+/// );
+/// assert_eq!(groups[0].count, 2);
+/// assert_eq!(groups[0].findings[0].1, 1);
+/// ````
+pub fn summarize_quiet<I, F>(
+    procs: I,
+    typ: &BitmapType,
+    quiet: F,
+) -> Vec<GroupSummary>
+where
+    I: IntoIterator<Item = (u32, String, ProcessSignals)>,
+    F: Fn(&u32) -> bool,
+{
+    let mut groups: BTreeMap<String, Vec<(u32, ProcessSignals)>> =
+        BTreeMap::new();
+
+    for (pid, name, sigs) in procs {
+        groups.entry(name).or_default().push((pid, sigs));
+    }
+
+    groups
+        .into_iter()
+        .map(|(group, procs)| {
+            let mut findings: Vec<(Finding, usize)> = Vec::new();
+
+            for (_, sigs) in procs.iter().filter(|(pid, _)| !quiet(pid)) {
+                let health: Health = Health {
+                    signals: *sigs,
+                    ..Default::default()
+                };
+
+                for fnd in diagnose(&health) {
+                    match findings.iter_mut().find(|(seen, _)| *seen == fnd) {
+                        Some((_, count)) => *count += 1,
+                        None => findings.push((fnd, 1)),
+                    }
+                }
+            }
+
+            findings.sort_by_key(|(fnd, count)| {
+                (Reverse(fnd.severity), Reverse(*count))
+            });
+
+            GroupSummary {
+                group,
+                count: procs.len(),
+                masks: dedupe(
+                    procs.iter().map(|(pid, sigs)| (*pid, sigs.bitmap(typ))),
+                ),
+                findings,
+            }
+        })
+        .collect()
+}
+
+/// Returns the processes grouped by identical signal bitmaps, the most
+/// common bitmap first (ties in order of the bitmaps), e.g. to fold
+/// hundreds of identical workers into a single entry.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{scan::*, SigSet};
+/// let groups: Vec<MaskGroup> = dedupe(vec![
+///     (42, SigSet::from_bits(0x4000)),
+///     (7, SigSet::empty()),
+///     (43, SigSet::from_bits(0x4000)),
+/// ]);
+/// assert_eq!(groups[0].pids, vec![42, 43]);
+/// assert_eq!(groups[1].pids, vec![7]);
+/// ````
+pub fn dedupe<I>(procs: I) -> Vec<MaskGroup>
+where
+    I: IntoIterator<Item = (u32, SigSet)>,
+{
+    let mut groups: HashMap<SigSet, Vec<u32>> = HashMap::new();
+
+    for (pid, bits) in procs {
+        groups.entry(bits).or_default().push(pid);
+    }
+
+    let mut out: Vec<MaskGroup> = groups
+        .into_iter()
+        .map(|(bits, mut pids)| {
+            pids.sort_unstable();
+            MaskGroup { bits, pids }
+        })
+        .collect();
+
+    out.sort_by_key(|group| (Reverse(group.pids.len()), group.bits.bits()));
+    out
+}
+
+/// Returns a summary of the processes per group, by the name given with
+/// each process (e.g. its systemd unit), in order of the names: the
+/// distinct bitmaps of the specified type, and the findings of the
+/// checks that need only the bitmaps (`lint`, and `stuck` for signals
+/// pending for the whole process).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{scan::*, BitmapType, ProcessSignals, SigSet};
+/// let ign: ProcessSignals = ProcessSignals {
+///     ignored: SigSet::from_bits(0x4000),
+///     ..Default::default()
+/// };
+/// let groups: Vec<GroupSummary> = summarize(
+///     vec![
+///         (42, String::from("app.service"), ign),
+///         (43, String::from("app.service"), ign),
+///     ],
+///     &BitmapType::SigIgn,
+/// );
+/// assert_eq!(groups[0].count, 2);
+/// assert_eq!(groups[0].masks.len(), 1);
+/// assert_eq!(groups[0].findings[0].1, 2);
+/// ````
+pub fn summarize<I>(procs: I, typ: &BitmapType) -> Vec<GroupSummary>
+where
+    I: IntoIterator<Item = (u32, String, ProcessSignals)>,
+{
+    summarize_quiet(procs, typ, |_| false)
+}
+
+/// Returns the processes catching, blocking or ignoring a signal, i.e.
+/// the reverse of the per-process view; a process blocking a signal it
+/// catches is listed under both.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{scan::*, ProcessSignals, SigSet};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     caught: SigSet::from_bits(0x200),
+///     ..Default::default()
+/// };
+/// let users: SignalUsers =
+///     who(&10, vec![(42, sigs), (7, ProcessSignals::default())]);
+/// assert_eq!(users.caught, vec![42]);
+/// assert!(users.blocked.is_empty());
+/// ````
+pub fn who<I>(idx: &u8, procs: I) -> SignalUsers
+where
+    I: IntoIterator<Item = (u32, ProcessSignals)>,
+{
+    let mut users: SignalUsers = SignalUsers {
+        signal: *idx,
+        ..Default::default()
+    };
+
+    for (pid, sigs) in procs {
+        for (set, pids) in [
+            (&sigs.caught, &mut users.caught),
+            (&sigs.blocked, &mut users.blocked),
+            (&sigs.ignored, &mut users.ignored),
+        ] {
+            if set.contains(idx) {
+                pids.push(pid);
+            }
+        }
+    }
+
+    users.caught.sort_unstable();
+    users.blocked.sort_unstable();
+    users.ignored.sort_unstable();
+    users
+}
+
+/// Returns the findings of summaries (see [`summarize`]) as one record
+/// per group and finding, e.g. to be kept between scans and compared
+/// (see [`FindingDiff`]): the group, severity, check and message,
+/// separated by tabs. The number of processes a finding applies to is
+/// left out, so that processes coming and going don't change it.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{scan::*, BitmapType, ProcessSignals, SigSet};
+/// let ign: ProcessSignals = ProcessSignals {
+///     ignored: SigSet::from_bits(0x4),
+///     ..Default::default()
+/// };
+/// let groups: Vec<GroupSummary> =
+///     summarize(vec![(42, String::from("app"), ign)], &BitmapType::SigIgn);
+/// assert_eq!(
+///     finding_records(&groups),
+///     vec!["app\tWARNING\tlint\ttermination signals ignored: QUIT"],
+/// );
+/// ````
+pub fn finding_records(groups: &[GroupSummary]) -> Vec<String> {
+    groups
+        .iter()
+        .flat_map(|group| {
+            group.findings.iter().map(|(fnd, _)| {
+                format!(
+                    "{}\t{}\t{}\t{}",
+                    group.group, fnd.severity, fnd.check, fnd.message
+                )
+            })
+        })
+        .collect()
+}
+
+/// Returns the process in a JSON object written by `scan --format json`
+/// or `json-v1` (see [`crate::format::format_scan_json`]); fields it
+/// doesn't know of are skipped.
+///
+/// # Errors
+///
+/// [`Error::InvalidSnapshot`] for a line that isn't such an object, or
+/// is of another version.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::scan::{parse_snapshot, Snapshot};
+/// let snap: Snapshot = parse_snapshot(
+///     "{\"schema_version\":2,\"pid\":42,\"start\":9000,\"comm\":\"app\",\
+///      \"pending\":\"0x0000000000000000\",\"shared_pending\":\"0x0000000000000000\",\
+///      \"blocked\":\"0x0000000000004000\",\"ignored\":\"0x0000000000000000\",\
+///      \"caught\":\"0x0000000000000000\"}",
+/// )
+/// .unwrap();
+/// assert_eq!((snap.pid, snap.comm.as_str()), (42, "app"));
+/// assert_eq!(snap.signals.blocked.to_names(), vec!["TERM"]);
+/// ````
+pub fn parse_snapshot(line: &str) -> Result<Snapshot, Error> {
+    let invalid = || Error::InvalidSnapshot(line.to_string());
+    let fields: HashMap<&str, &str> = json_fields(line).ok_or_else(invalid)?;
+    let num = |key: &str| -> Result<u64, Error> {
+        fields
+            .get(key)
+            .and_then(|val| val.parse::<u64>().ok())
+            .ok_or_else(invalid)
+    };
+    let bits = |key: &str| -> Result<SigSet, Error> {
+        fields
+            .get(key)
+            .and_then(|val| val.strip_prefix("\"0x")?.strip_suffix('"'))
+            .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+            .map(SigSet::from_bits)
+            .ok_or_else(invalid)
+    };
+
+    if !SCHEMA_VERSIONS.contains(&num("schema_version")?) {
+        return Err(invalid());
+    }
+
+    let pid: u32 = u32::try_from(num("pid")?).map_err(|_| invalid())?;
+
+    Ok(Snapshot {
+        pid,
+        start: num("start")?,
+        comm: fields
+            .get("comm")
+            .and_then(|val| json_unescape(val))
+            .ok_or_else(invalid)?,
+        signals: ProcessSignals {
+            pid,
+            pending: bits("pending")?,
+            shared_pending: bits("shared_pending")?,
+            blocked: bits("blocked")?,
+            ignored: bits("ignored")?,
+            caught: bits("caught")?,
+        },
+    })
+}
+
+/// Returns the processes whose specified type of signal bitmap changed,
+/// and those that appeared or disappeared, from the `old` scan to the
+/// `new` one, e.g. to audit a deployment against a scan saved before.
+/// Processes are told apart by PID and start time, so a recycled PID
+/// counts as a process disappearing and another appearing.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{scan::*, BitmapType, ProcessSignals, SigSet};
+/// let old: Snapshot = Snapshot {
+///     pid: 42,
+///     start: 9000,
+///     comm: String::from("app"),
+///     ..Default::default()
+/// };
+/// let new: Snapshot = Snapshot {
+///     signals: ProcessSignals {
+///         blocked: SigSet::from_bits(0x4000),
+///         ..Default::default()
+///     },
+///     ..old.clone()
+/// };
+/// let diff: ScanDiff = scan_diff(&[old.clone()], &[new.clone()], &BitmapType::SigBlk);
+/// assert_eq!(diff.changed, vec![(old, new)]);
+/// assert!(diff.appeared.is_empty() && diff.disappeared.is_empty());
+/// ````
+pub fn scan_diff(
+    old: &[Snapshot],
+    new: &[Snapshot],
+    typ: &BitmapType,
+) -> ScanDiff {
+    let key = |snap: &Snapshot| -> (u32, u64) { (snap.pid, snap.start) };
+    let olds: HashMap<(u32, u64), &Snapshot> =
+        old.iter().map(|snap| (key(snap), snap)).collect();
+    let news: HashMap<(u32, u64), &Snapshot> =
+        new.iter().map(|snap| (key(snap), snap)).collect();
+    let mut diff: ScanDiff = ScanDiff::default();
+
+    for snap in new {
+        match olds.get(&key(snap)) {
+            Some(prev)
+                if prev.signals.bitmap(typ) != snap.signals.bitmap(typ) =>
+            {
+                diff.changed.push(((*prev).clone(), snap.clone()))
+            }
+            Some(_) => continue,
+            None => diff.appeared.push(snap.clone()),
+        }
+    }
+
+    diff.disappeared = old
+        .iter()
+        .filter(|snap| !news.contains_key(&key(snap)))
+        .cloned()
+        .collect();
+
+    diff.changed.sort_by_key(|(_, snap)| key(snap));
+    diff.appeared.sort_by_key(key);
+    diff.disappeared.sort_by_key(key);
+    diff
+}
+
+/// Returns how many processes catch or block each realtime signal (32
+/// to 64), in order of the signal numbers, including unused ones, e.g.
+/// to pick a signal for a new IPC mechanism that nothing else handles.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{scan::*, ProcessSignals, SigSet};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     caught: SigSet::from_bits(0x2_0000_0000),
+///     ..Default::default()
+/// };
+/// let usage: Vec<RtUsage> = rt_usage(vec![sigs, ProcessSignals::default()]);
+/// assert_eq!(usage.len(), 33);
+/// assert_eq!((usage[2].signal, usage[2].caught), (34, 1));
+/// ````
+pub fn rt_usage<I>(procs: I) -> Vec<RtUsage>
+where
+    I: IntoIterator<Item = ProcessSignals>,
+{
+    let mut usage: Vec<RtUsage> = RT_RANGE
+        .clone()
+        .map(|signal| RtUsage {
+            signal,
+            ..Default::default()
+        })
+        .collect();
+
+    for sigs in procs {
+        for rt in usage.iter_mut() {
+            rt.caught += sigs.caught.contains(&rt.signal) as usize;
+            rt.blocked += sigs.blocked.contains(&rt.signal) as usize;
+        }
+    }
+
+    usage
+}
+
+// Return `true` if a name matches a pattern with `*` and `?` wildcards.
+pub(crate) fn glob(pattern: &str, name: &str) -> bool {
+    let pat: Vec<char> = pattern.chars().collect();
+    let txt: Vec<char> = name.chars().collect();
+    // Positions to resume from after the last `*` (in both), if any.
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut t): (usize, usize) = (0, 0);
+
+    while t < txt.len() {
+        match pat.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == txt[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((sp, st)) => {
+                    star = Some((sp, st + 1));
+                    p = sp;
+                    t = st + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pat[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doctor::Severity;
+
+    // Bitmaps and the PIDs sharing them.
+    type Groups = Vec<(u64, Vec<u32>)>;
+
+    // PID, start time, command name and blocked signals of a process.
+    type Parsed = Option<(u32, u64, &'static str, u64)>;
+
+    #[test]
+    fn test_termination() {
+        let tests: Vec<(ProcessSignals, Termination)> = vec![
+            (ProcessSignals::default(), Termination::Ends),
+            (
+                ProcessSignals {
+                    caught: SigSet::from_bits(0x4000),
+                    ..Default::default()
+                },
+                Termination::Handled,
+            ),
+            (
+                ProcessSignals {
+                    ignored: SigSet::from_bits(0x4000),
+                    ..Default::default()
+                },
+                Termination::NeedsKill,
+            ),
+            (
+                ProcessSignals {
+                    blocked: SigSet::from_bits(0x4000),
+                    caught: SigSet::from_bits(0x4000),
+                    ..Default::default()
+                },
+                Termination::NeedsKill,
+            ),
+            (
+                ProcessSignals {
+                    ignored: SigSet::from_bits(0x1),
+                    ..Default::default()
+                },
+                Termination::Ends,
+            ),
+        ];
+
+        for test in tests {
+            assert_eq!(termination(&test.0), test.1);
+        }
+    }
+
+    #[test]
+    fn test_killable() {
+        let ign: ProcessSignals = ProcessSignals {
+            ignored: SigSet::from_bits(0x4000),
+            ..Default::default()
+        };
+        let dfl: ProcessSignals = ProcessSignals::default();
+        let groups: Vec<KillGroup> = killable(vec![
+            (30, String::from("/b.service"), dfl),
+            (20, String::from("/a.service"), ign),
+            (10, String::from("/b.service"), dfl),
+        ]);
+
+        assert_eq!(
+            groups,
+            vec![
+                KillGroup {
+                    group: String::from("/a.service"),
+                    needs_kill: vec![20],
+                    ..Default::default()
+                },
+                KillGroup {
+                    group: String::from("/b.service"),
+                    ends: vec![10, 30],
+                    ..Default::default()
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        let ign: ProcessSignals = ProcessSignals {
+            ignored: SigSet::from_bits(0x10000),
+            ..Default::default()
+        };
+        let stk: ProcessSignals = ProcessSignals {
+            shared_pending: SigSet::from_bits(0x4000),
+            blocked: SigSet::from_bits(0x4000),
+            ..ign
+        };
+        let dfl: ProcessSignals = ProcessSignals::default();
+        let groups: Vec<GroupSummary> = summarize(
+            vec![
+                (1, String::from("b"), dfl),
+                (2, String::from("a"), ign),
+                (3, String::from("a"), stk),
+                (4, String::from("a"), ign),
+            ],
+            &BitmapType::SigIgn,
+        );
+        let findings: Vec<(Severity, usize)> = groups[0]
+            .findings
+            .iter()
+            .map(|(fnd, count)| (fnd.severity, *count))
+            .collect();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!((groups[0].group.as_str(), groups[0].count), ("a", 3));
+        assert_eq!(groups[0].masks.len(), 1);
+        assert_eq!(groups[0].masks[0].pids, vec![2, 3, 4]);
+        assert_eq!(
+            findings,
+            vec![(Severity::Critical, 1), (Severity::Info, 3)]
+        );
+        assert_eq!(groups[1].findings, vec![]);
+    }
+
+    #[test]
+    fn test_rt_usage() {
+        let sigs: ProcessSignals = ProcessSignals {
+            caught: SigSet::from_bits(0x8000_0003_0000_4000),
+            blocked: SigSet::from_bits(0x8000_0001_0000_0000),
+            ..Default::default()
+        };
+        let usage: Vec<(u8, usize, usize)> =
+            rt_usage(vec![sigs, sigs, ProcessSignals::default()])
+                .into_iter()
+                .filter(|rt| rt.caught != 0 || rt.blocked != 0)
+                .map(|rt| (rt.signal, rt.caught, rt.blocked))
+                .collect();
+
+        assert_eq!(rt_usage(vec![]).len(), 33);
+        assert_eq!(usage, vec![(33, 2, 2), (34, 2, 0), (64, 2, 2)]);
+    }
+
+    #[test]
+    fn test_who() {
+        let cgt: ProcessSignals = ProcessSignals {
+            caught: SigSet::from_bits(0x200),
+            blocked: SigSet::from_bits(0x200),
+            ..Default::default()
+        };
+        let ign: ProcessSignals = ProcessSignals {
+            ignored: SigSet::from_bits(0x600),
+            ..Default::default()
+        };
+        let procs: Vec<(u32, ProcessSignals)> = vec![
+            (30, ign),
+            (20, cgt),
+            (10, ign),
+            (5, ProcessSignals::default()),
+        ];
+
+        assert_eq!(
+            who(&10, procs.clone()),
+            SignalUsers {
+                signal: 10,
+                caught: vec![20],
+                blocked: vec![20],
+                ignored: vec![10, 30],
+            },
+        );
+        assert_eq!(
+            who(&1, procs),
+            SignalUsers {
+                signal: 1,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_finding_diff() {
+        let recs = |lst: &[&str]| -> Vec<String> {
+            lst.iter().map(|rec| rec.to_string()).collect()
+        };
+        let tests: Vec<(Vec<String>, Vec<String>, FindingDiff)> = vec![
+            (recs(&[]), recs(&[]), FindingDiff::default()),
+            (recs(&["a", "b"]), recs(&["b", "a"]), FindingDiff::default()),
+            (
+                recs(&[]),
+                recs(&["b", "a", "b"]),
+                FindingDiff {
+                    added: recs(&["a", "b"]),
+                    removed: recs(&[]),
+                },
+            ),
+            (
+                recs(&["a", "c"]),
+                recs(&["b", "c"]),
+                FindingDiff {
+                    added: recs(&["b"]),
+                    removed: recs(&["a"]),
+                },
+            ),
+        ];
+
+        for test in tests {
+            assert_eq!(FindingDiff::new(&test.0, &test.1), test.2);
+        }
+    }
+
+    #[test]
+    fn test_parse_snapshot() {
+        let tests: Vec<(&str, Parsed)> = vec![
+            (
+                "{\"schema_version\":1,\"pid\":42,\"start\":9000,\
+                 \"comm\":\"app\",\"pending\":\"0x0000000000000000\",\
+                 \"shared_pending\":\"0x0000000000000000\",\
+                 \"blocked\":\"0x0000000000004000\",\
+                 \"ignored\":\"0x0000000000000000\",\
+                 \"caught\":\"0x0000000000000000\"}",
+                Some((42, 9000, "app", 0x4000)),
+            ),
+            (
+                " { \"caught\": \"0x1\", \"ignored\": \"0x0\", \
+                 \"blocked\": \"0x2\", \"shared_pending\": \"0x0\", \
+                 \"pending\": \"0x0\", \"comm\": \"a, b\", \"user\": 0, \
+                 \"start\": 1, \"pid\": 7, \"schema_version\": 1 } ",
+                Some((7, 1, "a, b", 0x2)),
+            ),
+            (
+                "{\"schema_version\":2,\"pid\":42,\"start\":9000,\
+                 \"comm\":\"app\",\"pending\":\"0x0\",\
+                 \"shared_pending\":\"0x0\",\"blocked\":\"0x1\",\
+                 \"ignored\":\"0x0\",\"caught\":\"0x0\"}",
+                Some((42, 9000, "app", 0x1)),
+            ),
+            (
+                "{\"schema_version\":3,\"pid\":42,\"start\":9000,\
+                 \"comm\":\"app\",\"pending\":\"0x0\",\
+                 \"shared_pending\":\"0x0\",\"blocked\":\"0x0\",\
+                 \"ignored\":\"0x0\",\"caught\":\"0x0\"}",
+                None,
+            ),
+            (
+                "{\"schema_version\":1,\"pid\":42,\"start\":9000,\
+                 \"comm\":\"app\",\"pending\":\"0x0\",\
+                 \"shared_pending\":\"0x0\",\"blocked\":\"4000\",\
+                 \"ignored\":\"0x0\",\"caught\":\"0x0\"}",
+                None,
+            ),
+            (
+                "{\"schema_version\":1,\"pid\":42,\"start\":9000,\
+                 \"comm\":\"app\"}",
+                None,
+            ),
+            (
+                "{\"schema_version\":1,\"pid\":7,\"start\":1,\
+                 \"comm\":\"a\\\"b, c\\\\\\nd\\t\\u0001\",\
+                 \"pending\":\"0x0\",\"shared_pending\":\"0x0\",\
+                 \"blocked\":\"0x0\",\"ignored\":\"0x0\",\"caught\":\"0x0\"}",
+                Some((7, 1, "a\"b, c\\\nd\t\u{1}", 0x0)),
+            ),
+            (
+                "{\"schema_version\":1,\"pid\":7,\"start\":1,\
+                 \"comm\":\"\\u00e9\\ud83d\\ude00\",\
+                 \"pending\":\"0x0\",\"shared_pending\":\"0x0\",\
+                 \"blocked\":\"0x0\",\"ignored\":\"0x0\",\"caught\":\"0x0\"}",
+                Some((7, 1, "\u{e9}\u{1f600}", 0x0)),
+            ),
+            (
+                "{\"schema_version\":1,\"pid\":7,\"start\":1,\
+                 \"comm\":\"\\ud83d\",\
+                 \"pending\":\"0x0\",\"shared_pending\":\"0x0\",\
+                 \"blocked\":\"0x0\",\"ignored\":\"0x0\",\"caught\":\"0x0\"}",
+                None,
+            ),
+            (
+                "{\"schema_version\":1,\"pid\":7,\"start\":1,\
+                 \"comm\":\"a\\qb\",\
+                 \"pending\":\"0x0\",\"shared_pending\":\"0x0\",\
+                 \"blocked\":\"0x0\",\"ignored\":\"0x0\",\"caught\":\"0x0\"}",
+                None,
+            ),
+            ("PID: 42     SigBlk: 1  [0x0000000000004000]: TERM", None),
+            ("", None),
+        ];
+
+        for test in tests {
+            let snap = parse_snapshot(test.0).ok().map(|snap| {
+                (snap.pid, snap.start, snap.comm, snap.signals.blocked.bits())
+            });
+
+            assert_eq!(
+                snap,
+                test.1.map(|(pid, start, comm, bits)| {
+                    (pid, start, comm.to_string(), bits)
+                }),
+            );
+        }
+    }
+
+    #[test]
+    fn test_scan_diff() {
+        let snap = |pid: u32, start: u64, blocked: u64| Snapshot {
+            pid,
+            start,
+            comm: String::from("app"),
+            signals: ProcessSignals {
+                blocked: SigSet::from_bits(blocked),
+                ..Default::default()
+            },
+        };
+        let old: Vec<Snapshot> = vec![
+            snap(9, 90, 0x0),
+            snap(1, 10, 0x0),
+            snap(3, 30, 0x4000),
+            snap(7, 70, 0x1),
+        ];
+        let new: Vec<Snapshot> = vec![
+            snap(1, 10, 0x0),
+            snap(3, 30, 0x0),
+            snap(7, 75, 0x1),
+            snap(2, 20, 0x4000),
+        ];
+
+        assert_eq!(
+            scan_diff(&old, &new, &BitmapType::SigBlk),
+            ScanDiff {
+                changed: vec![(snap(3, 30, 0x4000), snap(3, 30, 0x0))],
+                appeared: vec![snap(2, 20, 0x4000), snap(7, 75, 0x1)],
+                disappeared: vec![snap(7, 70, 0x1), snap(9, 90, 0x0)],
+            },
+        );
+        assert!(scan_diff(&old, &new, &BitmapType::SigIgn)
+            .changed
+            .is_empty());
+        assert!(scan_diff(&new, &new, &BitmapType::SigBlk).is_empty());
+    }
+
+    #[test]
+    fn test_ignore_list() {
+        let list: IgnoreList = IgnoreList::parse(
+            "# known-good\n\n  chronyd  \nphp-worker-?\n/system.slice/*\n\
+             /user.slice # comment\n",
+        );
+        let tests: Vec<(&str, &str, bool)> = vec![
+            ("chronyd", "/", true),
+            ("chronyd2", "/", false),
+            ("php-worker-7", "/", true),
+            ("php-worker-17", "/", false),
+            ("app", "/system.slice/app.service", true),
+            ("app", "/user.slice", true),
+            ("app", "/user.slice/user-1000.slice", false),
+            ("/system.slice/app.service", "/", false),
+            ("app", "?", false),
+        ];
+
+        assert_eq!(list.names, vec!["chronyd", "php-worker-?"]);
+        assert_eq!(list.cgroups, vec!["/system.slice/*", "/user.slice"]);
+
+        for test in tests {
+            assert_eq!(list.matches(test.0, test.1), test.2);
+        }
+
+        assert!(!IgnoreList::default().matches("app", "/"));
+    }
+
+    #[test]
+    fn test_glob() {
+        let tests: Vec<(&str, &str, bool)> = vec![
+            ("nginx", "nginx", true),
+            ("nginx", "nginx: worker", false),
+            ("nginx*", "nginx: worker", true),
+            ("*worker", "nginx: worker", true),
+            ("*work*", "php-worker-7", true),
+            ("php-worker-?", "php-worker-7", true),
+            ("php-worker-?", "php-worker-17", false),
+            ("a*b*c", "axxbyyc", true),
+            ("a*b*c", "axxbyy", false),
+            ("*", "", true),
+            ("?", "", false),
+            ("", "", true),
+        ];
+
+        for test in tests {
+            assert_eq!(glob(test.0, test.1), test.2, "{} {}", test.0, test.1);
+        }
+    }
+
+    #[test]
+    fn test_dedupe() {
+        let tests: Vec<(Vec<(u32, u64)>, Groups)> = vec![
+            (vec![], vec![]),
+            (
+                vec![(3, 0x4000), (1, 0x1), (2, 0x4000), (4, 0x0)],
+                vec![(0x4000, vec![2, 3]), (0x0, vec![4]), (0x1, vec![1])],
+            ),
+        ];
+
+        for test in tests {
+            let groups: Groups = dedupe(
+                test.0
+                    .into_iter()
+                    .map(|(pid, bits)| (pid, SigSet::from_bits(bits))),
+            )
+            .into_iter()
+            .map(|group| (group.bits.bits(), group.pids))
+            .collect();
+
+            assert_eq!(groups, test.1);
+        }
+    }
+}
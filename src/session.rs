@@ -0,0 +1,364 @@
+//! Recorded watch sessions: the reports written while watching processes,
+//! as JSON lines, so that a session (e.g. a mask flapping during an
+//! incident) can be shared and replayed after the fact.
+use crate::{
+    error::Error,
+    report::{Report, Status},
+    signals::{BitmapType, SigSet},
+};
+use std::collections::HashMap;
+
+// Version of the structure of the records, bumped when fields are
+// changed or removed (not when added).
+const SCHEMA_VERSION: u64 = 1;
+
+// Bitmap types that can be recorded.
+static MAP_TYPES: &[BitmapType] = &[
+    BitmapType::SigPnd,
+    BitmapType::ShdPnd,
+    BitmapType::SigBlk,
+    BitmapType::SigIgn,
+    BitmapType::SigCgt,
+];
+
+/// A report written while watching, with when it was written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// Milliseconds since the session started.
+    pub elapsed: u64,
+
+    /// Start time of the process (in clock ticks after boot), to tell a
+    /// recycled PID from a change of the bitmap.
+    pub start: u64,
+
+    /// The report; its warnings aren't recorded.
+    pub report: Report,
+}
+
+// Return the fields of a JSON object on a single line without nested
+// objects (as written by `Frame::to_json`), by key; values are left as
+// written (e.g. quoted, or arrays in brackets).
+pub(crate) fn json_fields(line: &str) -> Option<HashMap<&str, &str>> {
+    let body: &str = line.trim().strip_prefix('{')?.strip_suffix('}')?;
+    let mut fields: HashMap<&str, &str> = HashMap::new();
+    let mut quoted: bool = false;
+    let mut escaped: bool = false;
+    let mut nested: usize = 0;
+    let mut from: usize = 0;
+
+    for (pos, c) in body.char_indices().chain([(body.len(), ',')]) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '[' if !quoted => nested += 1,
+            ']' if !quoted => nested = nested.checked_sub(1)?,
+            ',' if !quoted && nested == 0 => {
+                let (key, val): (&str, &str) =
+                    body[from..pos].split_once(':')?;
+                let key: &str =
+                    key.trim().strip_prefix('"')?.strip_suffix('"')?;
+                fields.insert(key, val.trim());
+                from = pos + 1;
+            }
+            _ => continue,
+        }
+    }
+
+    Some(fields)
+}
+
+// Return a string escaped to be written between the quotes of a JSON
+// string: quotes, backslashes and control characters are escaped.
+pub(crate) fn json_escape(text: &str) -> String {
+    let mut out: String = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                out.push_str(&format!("\\u{:04x}", u32::from(c)))
+            }
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+// Return the string a quoted JSON string value (as returned by
+// `json_fields`) stands for, with its escapes resolved; `None` if it
+// isn't quoted, or holds an invalid escape.
+pub(crate) fn json_unescape(val: &str) -> Option<String> {
+    let body: &str = val.strip_prefix('"')?.strip_suffix('"')?;
+    let mut chars = body.chars();
+    let mut out: String = String::with_capacity(body.len());
+    // Return the code unit of a `\uXXXX` escape, past the `\u`.
+    let unit = |chars: &mut std::str::Chars| -> Option<u32> {
+        let hex: String = chars.by_ref().take(4).collect();
+        match hex.len() {
+            4 => u32::from_str_radix(&hex, 16).ok(),
+            _ => None,
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next()? {
+            '"' => '"',
+            '\\' => '\\',
+            '/' => '/',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => match unit(&mut chars)? {
+                high @ 0xd800..=0xdbff => {
+                    if (chars.next()?, chars.next()?) != ('\\', 'u') {
+                        return None;
+                    }
+                    let low: u32 = unit(&mut chars)?;
+                    if !(0xdc00..=0xdfff).contains(&low) {
+                        return None;
+                    }
+                    char::from_u32(
+                        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00),
+                    )?
+                }
+                code => char::from_u32(code)?,
+            },
+            _ => return None,
+        });
+    }
+
+    Some(out)
+}
+
+impl Frame {
+    /// Returns the record of the frame: a JSON object on a single line,
+    /// carrying the version of its structure (`schema_version`).
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::{session::Frame, BitmapType, Report, SigSet};
+    /// let frame: Frame = Frame {
+    ///     elapsed: 1500,
+    ///     start: 9000,
+    ///     report: Report {
+    ///         pid: 42,
+    ///         map: BitmapType::SigBlk,
+    ///         bits: SigSet::from_bits(0x4000),
+    ///         parent: None,
+    ///         warnings: vec![],
+    ///         status: None,
+    ///         shared: None,
+    ///         signalfd: None,
+    ///     },
+    /// };
+    /// assert_eq!(
+    ///     frame.to_json(),
+    ///     "{\"schema_version\":1,\"elapsed_ms\":1500,\"pid\":42,\"start\":9000,\
+    ///      \"map\":\"SigBlk\",\"bits\":\"0x0000000000004000\",\"shared\":null,\
+    ///      \"ppid\":null,\"parent_bits\":null,\"state\":null,\"umask\":null,\
+    ///      \"tracer\":null}",
+    /// );
+    /// assert_eq!(Frame::parse(&frame.to_json()), Ok(frame));
+    /// ````
+    pub fn to_json(&self) -> String {
+        let bits = |set: &SigSet| format!("\"0x{:016x}\"", set.bits());
+        let null = |val: Option<String>| val.unwrap_or(String::from("null"));
+        let report: &Report = &self.report;
+        let status: Option<&Status> = report.status.as_ref();
+
+        format!(
+            "{{\"schema_version\":{},\"elapsed_ms\":{},\"pid\":{},\"start\":{},\
+             \"map\":\"{}\",\"bits\":{},\"shared\":{},\"ppid\":{},\
+             \"parent_bits\":{},\"state\":{},\"umask\":{},\"tracer\":{}}}",
+            SCHEMA_VERSION,
+            self.elapsed,
+            report.pid,
+            self.start,
+            report.map.to_string().trim_end_matches(':'),
+            bits(&report.bits),
+            null(report.shared.as_ref().map(bits)),
+            null(report.parent.map(|(ppid, _)| ppid.to_string())),
+            null(report.parent.map(|(_, par)| bits(&par))),
+            null(status.map(|st| format!("\"{}\"", st.state.replace('"', "")))),
+            null(status.and_then(|st| st.umask).map(|um| um.to_string())),
+            null(status.and_then(|st| st.tracer).map(|tp| tp.to_string())),
+        )
+    }
+
+    /// Returns the frame in a record written by [`Frame::to_json`];
+    /// fields it doesn't know of are skipped.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidRecord`] for a line that isn't such a record, or
+    /// is of another version.
+    pub fn parse(line: &str) -> Result<Frame, Error> {
+        let invalid = || Error::InvalidRecord(line.to_string());
+        let fields: HashMap<&str, &str> =
+            json_fields(line).ok_or_else(invalid)?;
+        // Return the value of a field, unquoted, or `None` if it is
+        // missing or null.
+        let field = |key: &str| -> Option<&str> {
+            fields
+                .get(key)
+                .filter(|val| **val != "null")
+                .map(|val| val.trim_matches('"'))
+        };
+        let num = |key: &str| -> Result<Option<u64>, Error> {
+            field(key)
+                .map(|val| val.parse::<u64>().map_err(|_| invalid()))
+                .transpose()
+        };
+        let id = |key: &str| -> Result<Option<u32>, Error> {
+            num(key)?
+                .map(|val| u32::try_from(val).map_err(|_| invalid()))
+                .transpose()
+        };
+        let bits = |key: &str| -> Result<Option<SigSet>, Error> {
+            field(key)
+                .map(|val| {
+                    val.strip_prefix("0x")
+                        .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+                        .map(SigSet::from_bits)
+                        .ok_or_else(invalid)
+                })
+                .transpose()
+        };
+
+        if num("schema_version")? != Some(SCHEMA_VERSION) {
+            return Err(invalid());
+        }
+
+        let map: BitmapType = MAP_TYPES
+            .iter()
+            .find(|typ| {
+                Some(typ.to_string().trim_end_matches(':')) == field("map")
+            })
+            .cloned()
+            .ok_or_else(invalid)?;
+        let parent: Option<(u32, SigSet)> =
+            match (id("ppid")?, bits("parent_bits")?) {
+                (Some(ppid), Some(par)) => Some((ppid, par)),
+                _ => None,
+            };
+        let status: Option<Status> = match field("state") {
+            Some(state) => Some(Status {
+                state: state.to_string(),
+                umask: id("umask")?,
+                tracer: id("tracer")?,
+            }),
+            None => None,
+        };
+
+        Ok(Frame {
+            elapsed: num("elapsed_ms")?.ok_or_else(invalid)?,
+            start: num("start")?.ok_or_else(invalid)?,
+            report: Report {
+                pid: id("pid")?.ok_or_else(invalid)?,
+                map,
+                bits: bits("bits")?.ok_or_else(invalid)?,
+                parent,
+                warnings: vec![],
+                status,
+                shared: bits("shared")?,
+                signalfd: None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Elapsed time, PID, bitmap type and bitmap of a frame.
+    type Parsed = Option<(u64, u32, BitmapType, u64)>;
+
+    #[test]
+    fn test_frame_round_trip() {
+        let frame: Frame = Frame {
+            elapsed: 0,
+            start: 1,
+            report: Report {
+                pid: 42,
+                map: BitmapType::SigPnd,
+                bits: SigSet::from_bits(0x1),
+                parent: Some((1, SigSet::from_bits(0x8000_0000_0000_0000))),
+                warnings: vec![],
+                status: Some(Status {
+                    state: String::from("t (tracing stop)"),
+                    umask: Some(0o022),
+                    tracer: Some(7),
+                }),
+                shared: Some(SigSet::from_bits(0x4000)),
+                signalfd: None,
+            },
+        };
+
+        assert_eq!(Frame::parse(&frame.to_json()), Ok(frame));
+    }
+
+    #[test]
+    fn test_frame_parse() {
+        let tests: Vec<(&str, Parsed)> = vec![
+            (
+                "{\"schema_version\":1,\"elapsed_ms\":20,\"pid\":7,\"start\":3,\
+                 \"map\":\"SigIgn\",\"bits\":\"0x0000000000001000\"}",
+                Some((20, 7, BitmapType::SigIgn, 0x1000)),
+            ),
+            (
+                " { \"map\": \"SigCgt\", \"bits\": \"0x2\", \"pid\": 8, \
+                 \"host\": \"a,b:c\", \"start\": 3, \"elapsed_ms\": 0, \
+                 \"schema_version\": 1 } ",
+                Some((0, 8, BitmapType::SigCgt, 0x2)),
+            ),
+            (
+                "{\"schema_version\":2,\"elapsed_ms\":20,\"pid\":7,\"start\":3,\
+                 \"map\":\"SigIgn\",\"bits\":\"0x0000000000001000\"}",
+                None,
+            ),
+            (
+                "{\"schema_version\":1,\"elapsed_ms\":20,\"pid\":7,\"start\":3,\
+                 \"map\":\"SigQ\",\"bits\":\"0x0000000000001000\"}",
+                None,
+            ),
+            (
+                "{\"schema_version\":1,\"elapsed_ms\":20,\"pid\":7,\"start\":3,\
+                 \"map\":\"SigIgn\",\"bits\":\"1000\"}",
+                None,
+            ),
+            (
+                "{\"schema_version\":1,\"elapsed_ms\":20,\"pid\":7,\
+                 \"map\":\"SigIgn\",\"bits\":\"0x0000000000001000\"}",
+                None,
+            ),
+            ("PID: 42     SigPnd: 0  [0x0000000000000000]: NONE", None),
+            ("", None),
+        ];
+
+        for test in tests {
+            let frame: Parsed = Frame::parse(test.0).ok().map(|frame| {
+                (
+                    frame.elapsed,
+                    frame.report.pid,
+                    frame.report.map,
+                    frame.report.bits.bits(),
+                )
+            });
+
+            assert_eq!(frame, test.1);
+        }
+    }
+}
@@ -0,0 +1,1679 @@
+//! Signal tables and types: signal names, default actions and the
+//! kernel's delivery rules, independent of where a bitmap came from.
+use crate::error::Error;
+use std::{cmp::Ordering, fmt};
+
+// Total number of signals.
+pub(crate) const NR_SIGS: u8 = 64;
+
+// Realtime signals (min and max).
+const SIGRTMIN_STR: &str = "RTMIN";
+const SIGRTMAX_STR: &str = "RTMAX";
+
+// Index of RT{MIN,MAX} signals (relative to the table).
+const SIGRTMIN_IDX: u8 = 0x22;
+const SIGRTMAX_IDX: u8 = 0x40;
+
+// A table of string representation of signals.
+static SIG_TAB: &[&str; 31] = &[
+    "HUP", "INT", "QUIT", "ILL", "TRAP", "ABRT", "BUS", "FPE", "KILL", "USR1",
+    "SEGV", "USR2", "PIPE", "ALRM", "TERM", "STKFLT", "CHLD", "CONT", "STOP",
+    "TSTP", "TTIN", "TTOU", "URG", "XCPU", "XFSZ", "VTALRM", "PROF", "WINCH",
+    "IO", "PWR", "SYS",
+];
+
+// A table of default actions of signals (aligned with `SIG_TAB`).
+static DFL_TAB: &[DefaultAction; 31] = &[
+    DefaultAction::Terminate, // HUP
+    DefaultAction::Terminate, // INT
+    DefaultAction::CoreDump,  // QUIT
+    DefaultAction::CoreDump,  // ILL
+    DefaultAction::CoreDump,  // TRAP
+    DefaultAction::CoreDump,  // ABRT
+    DefaultAction::CoreDump,  // BUS
+    DefaultAction::CoreDump,  // FPE
+    DefaultAction::Terminate, // KILL
+    DefaultAction::Terminate, // USR1
+    DefaultAction::CoreDump,  // SEGV
+    DefaultAction::Terminate, // USR2
+    DefaultAction::Terminate, // PIPE
+    DefaultAction::Terminate, // ALRM
+    DefaultAction::Terminate, // TERM
+    DefaultAction::Terminate, // STKFLT
+    DefaultAction::Ignore,    // CHLD
+    DefaultAction::Continue,  // CONT
+    DefaultAction::Stop,      // STOP
+    DefaultAction::Stop,      // TSTP
+    DefaultAction::Stop,      // TTIN
+    DefaultAction::Stop,      // TTOU
+    DefaultAction::Ignore,    // URG
+    DefaultAction::CoreDump,  // XCPU
+    DefaultAction::CoreDump,  // XFSZ
+    DefaultAction::Terminate, // VTALRM
+    DefaultAction::Terminate, // PROF
+    DefaultAction::Ignore,    // WINCH
+    DefaultAction::Terminate, // IO
+    DefaultAction::Terminate, // PWR
+    DefaultAction::CoreDump,  // SYS
+];
+
+// A table of categories of signals (aligned with `SIG_TAB`).
+static CAT_TAB: &[SignalCategory; 31] = &[
+    SignalCategory::Termination, // HUP
+    SignalCategory::Termination, // INT
+    SignalCategory::Termination, // QUIT
+    SignalCategory::Fault,       // ILL
+    SignalCategory::Fault,       // TRAP
+    SignalCategory::Fault,       // ABRT
+    SignalCategory::Fault,       // BUS
+    SignalCategory::Fault,       // FPE
+    SignalCategory::Termination, // KILL
+    SignalCategory::Misc,        // USR1
+    SignalCategory::Fault,       // SEGV
+    SignalCategory::Misc,        // USR2
+    SignalCategory::IO,          // PIPE
+    SignalCategory::Timer,       // ALRM
+    SignalCategory::Termination, // TERM
+    SignalCategory::Fault,       // STKFLT
+    SignalCategory::JobControl,  // CHLD
+    SignalCategory::JobControl,  // CONT
+    SignalCategory::JobControl,  // STOP
+    SignalCategory::JobControl,  // TSTP
+    SignalCategory::JobControl,  // TTIN
+    SignalCategory::JobControl,  // TTOU
+    SignalCategory::IO,          // URG
+    SignalCategory::Misc,        // XCPU
+    SignalCategory::Misc,        // XFSZ
+    SignalCategory::Timer,       // VTALRM
+    SignalCategory::Timer,       // PROF
+    SignalCategory::Misc,        // WINCH
+    SignalCategory::IO,          // IO
+    SignalCategory::Misc,        // PWR
+    SignalCategory::Fault,       // SYS
+];
+
+// Signals that can't be caught, blocked or ignored.
+const SIGKILL_IDX: u8 = 0x09;
+const SIGSTOP_IDX: u8 = 0x13;
+
+// Named presets for composing signal sets (besides the ranges).
+static PRESET_TAB: &[(&str, &[&str])] = &[
+    ("termination", &["HUP", "INT", "QUIT", "TERM"]),
+    ("job-control", &["CONT", "STOP", "TSTP", "TTIN", "TTOU"]),
+];
+
+// Other names of signals, as defined by the C library or other tools,
+// and the canonical names they stand for.
+static ALIAS_TAB: &[(&str, &str)] =
+    &[("IOT", "ABRT"), ("CLD", "CHLD"), ("POLL", "IO")];
+
+/// Names of the presets accepted by [`preset`] and [`SigSet::compose`].
+pub static PRESETS: &[&str] =
+    &["all", "standard", "all-rt", "termination", "job-control"];
+
+/// Number of bitmaps below which [`interpret_many_par`] doesn't spawn
+/// threads, as starting them costs more than interpreting the bitmaps.
+pub const PAR_MIN_MAPS: usize = 16_384;
+
+// Bits outside the two highest bytes of a bitmap, which hold the
+// standard signals of a bitmap read in the wrong byte order.
+const SWAP_MASK: u64 = 0x0000_ffff_ffff_ffff;
+
+// Range values for signals.
+static POSIX_RANGE: std::ops::Range<u8> = 0x01..0x20;
+pub(crate) static RTMIN_RANGE: std::ops::Range<u8> = 0x20..0x32;
+static RTMAX_RANGE: std::ops::Range<u8> = 0x32..0x41;
+
+/// The type of signal bitmap.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum BitmapType {
+    /// Pending signals (thread).
+    #[default]
+    SigPnd,
+
+    /// Pending signals (shared between threads in a process).
+    ShdPnd,
+
+    /// Blocked signals.
+    SigBlk,
+
+    /// Ignored signals.
+    SigIgn,
+
+    /// Caught signals.
+    SigCgt,
+}
+
+/// The byte order of a raw `sigset_t`, e.g. as dumped by a debugger.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Endian {
+    /// Least significant byte first (e.g. x86, ARM).
+    #[default]
+    Little,
+
+    /// Most significant byte first (e.g. s390x, big-endian MIPS).
+    Big,
+}
+
+/// The order of the 32-bit words of a raw `sigset_t` stored as two
+/// words, e.g. on 32-bit systems.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum WordOrder {
+    /// The word with signals 1 to 32 first (the kernel's layout).
+    #[default]
+    LowFirst,
+
+    /// The word with signals 33 to 64 first.
+    HighFirst,
+}
+
+/// How realtime signals are named.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[non_exhaustive]
+pub enum RtStyle {
+    /// Relative to the nearest of `RTMIN` and `RTMAX`, as `kill -l`
+    /// does (e.g. `RTMIN+2`, `RTMAX-1`).
+    #[default]
+    Relative,
+
+    /// Relative to `RTMIN` (e.g. `RTMIN+29` rather than `RTMAX-1`).
+    Min,
+
+    /// The signal number (e.g. `63`).
+    Number,
+}
+
+/// How signals are named in output: with or without the `SIG` prefix,
+/// how realtime signals are named, and in which case. The default is
+/// the canonical names, as returned by [`SigSet::to_names`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{NameStyle, RtStyle, SigSet};
+/// let style: NameStyle = NameStyle {
+///     prefix: true,
+///     rt: RtStyle::Min,
+///     lowercase: true,
+/// };
+/// assert_eq!(
+///     style.names(&SigSet::from_bits(0x4000_0000_0000_4000)),
+///     vec!["sigterm", "sigrtmin+29"],
+/// );
+/// ````
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NameStyle {
+    /// Whether names start with `SIG` (e.g. `SIGTERM`); signal numbers
+    /// never do.
+    pub prefix: bool,
+
+    /// How realtime signals are named.
+    pub rt: RtStyle,
+
+    /// Whether names are in lowercase (e.g. `term`).
+    pub lowercase: bool,
+}
+
+/// The default action the kernel takes for a signal (see `signal(7)`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DefaultAction {
+    /// Terminate the process.
+    Terminate,
+
+    /// Terminate the process and dump core.
+    CoreDump,
+
+    /// Ignore the signal.
+    Ignore,
+
+    /// Stop the process.
+    Stop,
+
+    /// Continue the process if it is stopped.
+    Continue,
+}
+
+/// What a signal is about, to group signals by in reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum SignalCategory {
+    /// Asks the process to end (e.g. `TERM`, `HUP`, `KILL`).
+    Termination,
+
+    /// Reports a fault of the process (e.g. `SEGV`, `ABRT`, `SYS`).
+    Fault,
+
+    /// Stops, continues or reports on processes (e.g. `TSTP`, `CHLD`).
+    JobControl,
+
+    /// Reports a timer expiring (e.g. `ALRM`, `PROF`).
+    Timer,
+
+    /// Reports an event on a file or socket (e.g. `PIPE`, `IO`).
+    IO,
+
+    /// A realtime signal.
+    Realtime,
+
+    /// Any other signal (e.g. `USR1`, `WINCH`, `XCPU`).
+    Misc,
+}
+
+/// What happens to a signal sent to a process right now.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The signal is blocked; it stays pending until it is unblocked.
+    Queued,
+
+    /// The signal is blocked and an instance of it is already pending;
+    /// standard signals don't queue, so this one is dropped.
+    Merged,
+
+    /// The signal is ignored and discarded on arrival.
+    Discarded,
+
+    /// The signal is delivered to a handler installed by the process.
+    Handled,
+
+    /// The signal is delivered and the kernel takes the default action.
+    Default(DefaultAction),
+}
+
+/// A signal, by its number (`1` to `64`); shown by its canonical name.
+/// Parsed from a name (or number) in any of the forms accepted by
+/// [`signal_number`], or converted from its number.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::Signal;
+/// let sig: Signal = "SIGIOT".parse().unwrap();
+/// assert_eq!((sig.number(), sig.to_string()), (6, String::from("ABRT")));
+/// assert_eq!(Signal::try_from(6), Ok(sig));
+/// assert!(Signal::try_from(65).is_err());
+/// ````
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Signal(u8);
+
+/// A set of signals, stored as a bitmap in the kernel's layout: bit
+/// `n - 1` is set when signal `n` is a member of the set.
+///
+/// Converting a set to signal names and back is lossless: for every
+/// set `s`, `SigSet::from_names(s.to_names())` returns `s`, since each
+/// of the 64 bits maps to a distinct (canonical) signal name.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::SigSet;
+/// let set: SigSet = SigSet::from_names(["HUP", "SIGTERM"]).unwrap();
+/// assert_eq!(set.bits(), 0x4001);
+/// assert_eq!(set.to_names(), vec!["HUP", "TERM"]);
+/// assert_eq!(SigSet::from_names(set.to_names()), Ok(set));
+/// ````
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SigSet(u64);
+
+/// The signals added to and removed from a set, relative to another
+/// (e.g. an earlier sample, or the set of the parent); see
+/// [`SigSet::diff`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SigSetDiff {
+    /// Signals in the set, but not in the other.
+    pub added: SigSet,
+
+    /// Signals in the other set, but not in the set.
+    pub removed: SigSet,
+}
+
+/// The signal bitmaps of a process, read from `/proc/<pid>/status`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProcessSignals {
+    /// PID of the process the bitmaps were read from (0 if they weren't
+    /// read from a process).
+    pub pid: u32,
+
+    /// Pending signals (thread).
+    pub pending: SigSet,
+
+    /// Pending signals (shared between threads in a process).
+    pub shared_pending: SigSet,
+
+    /// Blocked signals.
+    pub blocked: SigSet,
+
+    /// Ignored signals.
+    pub ignored: SigSet,
+
+    /// Caught signals.
+    pub caught: SigSet,
+}
+
+/// A condition on the signal bitmaps of a process: whether a signal is
+/// (or isn't) in a bitmap; see [`Condition::parse`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Condition {
+    /// Type of the signal bitmap.
+    pub map: BitmapType,
+
+    /// Signal number.
+    pub signal: u8,
+
+    /// Whether the signal must be in the bitmap (or not).
+    pub set: bool,
+}
+
+// String representation (line prefix in `/proc<pid>/status`)
+// of a signal bitmap type.
+impl fmt::Display for BitmapType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BitmapType::SigPnd => write!(f, "SigPnd:"),
+            BitmapType::ShdPnd => write!(f, "ShdPnd:"),
+            BitmapType::SigBlk => write!(f, "SigBlk:"),
+            BitmapType::SigIgn => write!(f, "SigIgn:"),
+            BitmapType::SigCgt => write!(f, "SigCgt:"),
+        }
+    }
+}
+
+impl BitmapType {
+    /// Returns the signals that can't be in a bitmap of this type on a
+    /// live system: `KILL` and `STOP` can't be blocked, ignored or
+    /// caught (the kernel drops them from such masks), so finding them
+    /// there means the data is corrupt or from a kernel with other
+    /// signal numbers (e.g. a dump from another architecture).
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::BitmapType;
+    /// assert_eq!(BitmapType::SigIgn.impossible().to_names(), ["KILL", "STOP"]);
+    /// assert!(BitmapType::SigPnd.impossible().is_empty());
+    /// ````
+    pub fn impossible(&self) -> SigSet {
+        match self {
+            BitmapType::SigPnd | BitmapType::ShdPnd => SigSet::empty(),
+            BitmapType::SigBlk | BitmapType::SigIgn | BitmapType::SigCgt => {
+                SigSet::from_bits(
+                    (1 << (SIGKILL_IDX - 1)) | (1 << (SIGSTOP_IDX - 1)),
+                )
+            }
+        }
+    }
+}
+
+// Return the string representation of a signal number.
+// This is specifically used for RT{MIN,MAX}+/-N.
+fn fmt_range(idx: &u8, off: &u8, tmpl: &str) -> String {
+    let diff: i8 = (*idx as i8) - (*off as i8);
+    match diff.cmp(&0) {
+        Ordering::Equal => tmpl.to_string(),
+        _ => format!("{}{:+}", tmpl, diff),
+    }
+}
+
+impl fmt::Display for DefaultAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DefaultAction::Terminate => write!(f, "terminate"),
+            DefaultAction::CoreDump => write!(f, "terminate and dump core"),
+            DefaultAction::Ignore => write!(f, "ignore"),
+            DefaultAction::Stop => write!(f, "stop"),
+            DefaultAction::Continue => write!(f, "continue"),
+        }
+    }
+}
+
+impl fmt::Display for SignalCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignalCategory::Termination => write!(f, "termination"),
+            SignalCategory::Fault => write!(f, "fault"),
+            SignalCategory::JobControl => write!(f, "job control"),
+            SignalCategory::Timer => write!(f, "timer"),
+            SignalCategory::IO => write!(f, "I/O"),
+            SignalCategory::Realtime => write!(f, "realtime"),
+            SignalCategory::Misc => write!(f, "misc"),
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Queued => write!(f, "queued (blocked)"),
+            Outcome::Merged => {
+                write!(f, "discarded (blocked and already pending)")
+            }
+            Outcome::Discarded => write!(f, "discarded (ignored)"),
+            Outcome::Handled => write!(f, "handled (caught)"),
+            Outcome::Default(DefaultAction::Ignore) => {
+                write!(f, "discarded (ignored by default)")
+            }
+            Outcome::Default(act) => {
+                write!(f, "delivered now (default action: {})", act)
+            }
+        }
+    }
+}
+
+// Return a string describing the signal number
+// index passed in the argument `idx`.
+pub(crate) fn sigabbrev_np(idx: &u8) -> String {
+    if POSIX_RANGE.contains(idx) {
+        return SIG_TAB[(*idx as usize) - 1].to_string();
+    }
+
+    if RTMIN_RANGE.contains(idx) {
+        return fmt_range(idx, &SIGRTMIN_IDX, SIGRTMIN_STR);
+    }
+
+    if RTMAX_RANGE.contains(idx) {
+        return fmt_range(idx, &SIGRTMAX_IDX, SIGRTMAX_STR);
+    }
+
+    String::from("INVL")
+}
+
+impl NameStyle {
+    /// Returns the name of a signal in the style.
+    pub fn name(&self, idx: &u8) -> String {
+        let name: String = match self.rt {
+            RtStyle::Min if !POSIX_RANGE.contains(idx) && *idx <= NR_SIGS => {
+                fmt_range(idx, &SIGRTMIN_IDX, SIGRTMIN_STR)
+            }
+            RtStyle::Number
+                if !POSIX_RANGE.contains(idx) && *idx <= NR_SIGS =>
+            {
+                return idx.to_string();
+            }
+            _ => sigabbrev_np(idx),
+        };
+        let name: String = match self.prefix {
+            true => format!("SIG{}", name),
+            false => name,
+        };
+
+        match self.lowercase {
+            true => name.to_ascii_lowercase(),
+            false => name,
+        }
+    }
+
+    /// Returns the names of the signals in a set in the style, in
+    /// ascending order of signal number.
+    pub fn names(&self, set: &SigSet) -> Vec<String> {
+        set.iter().map(|idx| self.name(&idx)).collect()
+    }
+}
+
+/// Returns the signal number for a signal name such as `TERM`, `SIGTERM`,
+/// `RTMIN+3` or a plain number such as `15`; names are case-insensitive,
+/// whitespace in them is skipped (e.g. `SIGRTMIN + 3`), and aliases are
+/// accepted (`IOT` for `ABRT`, `CLD` for `CHLD`, `POLL` for `IO`).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::signal_number;
+/// assert_eq!(signal_number("sigterm"), Some(15));
+/// assert_eq!(signal_number("RTMAX-1"), Some(63));
+/// assert_eq!(signal_number("SIGIOT"), Some(6));
+/// assert_eq!(signal_number("BOGUS"), None);
+/// ````
+pub fn signal_number(name: &str) -> Option<u8> {
+    let name: String = name
+        .split_whitespace()
+        .collect::<String>()
+        .to_ascii_uppercase();
+    let name: &str = name.strip_prefix("SIG").unwrap_or(&name);
+    let name: &str = ALIAS_TAB
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, canon)| canon);
+
+    if let Ok(idx) = name.parse::<u8>() {
+        return (0x1..=NR_SIGS).contains(&idx).then_some(idx);
+    }
+
+    if let Some(idx) = (0x1..=NR_SIGS).find(|idx| sigabbrev_np(idx) == name) {
+        return Some(idx);
+    }
+
+    // Non-canonical forms, e.g. `RTMIN+20` instead of `RTMAX-10`.
+    let (off, diff): (u8, &str) = match name {
+        _ if name.starts_with(SIGRTMIN_STR) => {
+            (SIGRTMIN_IDX, &name[SIGRTMIN_STR.len()..])
+        }
+        _ if name.starts_with(SIGRTMAX_STR) => {
+            (SIGRTMAX_IDX, &name[SIGRTMAX_STR.len()..])
+        }
+        _ => return None,
+    };
+    let idx: i16 = (off as i16) + diff.parse::<i16>().ok()?;
+
+    (RTMIN_RANGE.start as i16..=NR_SIGS as i16)
+        .contains(&idx)
+        .then_some(idx as u8)
+}
+
+/// Returns the signal named (or numbered) in any of the forms accepted
+/// by [`signal_number`], e.g. to compare signal names read from the
+/// configuration files of other tools; aliases resolve to the signal
+/// they stand for, shown by its canonical name.
+///
+/// # Errors
+///
+/// [`Error::UnknownSignal`] if the name doesn't match a signal.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{normalize_signal_name, Signal};
+/// let sig: Signal = normalize_signal_name("SIGIOT").unwrap();
+/// assert_eq!((sig.number(), sig.name().as_str()), (6, "ABRT"));
+/// assert_eq!(normalize_signal_name("sigrtmin+20").unwrap().name(), "RTMAX-10");
+/// ````
+pub fn normalize_signal_name(name: &str) -> Result<Signal, Error> {
+    signal_number(name)
+        .map(Signal)
+        .ok_or_else(|| Error::UnknownSignal(name.to_string()))
+}
+
+/// Returns the set of signals for a preset name (without the leading
+/// `@`); see [`PRESETS`] for the names:
+///
+/// * `all` - Every signal.
+/// * `standard` - The standard (non-realtime) signals, `HUP` to `SYS`.
+/// * `all-rt` - The realtime signals, `RTMIN-2` to `RTMAX`.
+/// * `termination` - Signals asking a process to terminate.
+/// * `job-control` - Signals stopping and continuing a process.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::signals::preset;
+/// assert_eq!(preset("termination").unwrap().bits(), 0x4007);
+/// ```
+pub fn preset(name: &str) -> Option<SigSet> {
+    let range: std::ops::RangeInclusive<u8> = match name {
+        "all" => 0x1..=NR_SIGS,
+        "standard" => POSIX_RANGE.start..=(POSIX_RANGE.end - 1),
+        "all-rt" => RTMIN_RANGE.start..=NR_SIGS,
+        _ => {
+            let (_, names) = PRESET_TAB.iter().find(|(pre, _)| *pre == name)?;
+            return SigSet::from_names(*names).ok();
+        }
+    };
+
+    let mut set: SigSet = SigSet::empty();
+    range.for_each(|idx| set.insert(&idx));
+    Some(set)
+}
+
+/// Returns the default action of a signal.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{default_action, DefaultAction};
+/// assert_eq!(default_action(&0x11), DefaultAction::Ignore);
+/// ````
+pub fn default_action(idx: &u8) -> DefaultAction {
+    match POSIX_RANGE.contains(idx) {
+        true => DFL_TAB[(*idx as usize) - 1],
+        false => DefaultAction::Terminate,
+    }
+}
+
+/// Returns the category of a signal; signal numbers past the standard
+/// signals are realtime, and invalid ones are [`SignalCategory::Misc`].
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{category, SignalCategory};
+/// assert_eq!(category(&0x0b), SignalCategory::Fault);
+/// assert_eq!(category(&0x22), SignalCategory::Realtime);
+/// ````
+pub fn category(idx: &u8) -> SignalCategory {
+    match idx {
+        _ if POSIX_RANGE.contains(idx) => CAT_TAB[(*idx as usize) - 1],
+        _ if (POSIX_RANGE.end..=NR_SIGS).contains(idx) => {
+            SignalCategory::Realtime
+        }
+        _ => SignalCategory::Misc,
+    }
+}
+
+/// Returns what would happen to a signal if it were sent to a process
+/// with the specified signal bitmaps right now, following the kernel's
+/// delivery rules: `KILL` and `STOP` always take their default action;
+/// blocked signals are kept pending (even if ignored, since the
+/// disposition may change before they are unblocked), unless it is a
+/// standard signal which is already pending; otherwise the signal is
+/// discarded if ignored, delivered to the handler if caught, and acted
+/// upon by default if neither.
+///
+/// # Arguments
+/// * `idx`  - Signal number.
+/// * `sigs` - Signal bitmaps of the process.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{simulate, Outcome, ProcessSignals, SigSet};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     blocked: SigSet::from_bits(0x4000),
+///     ..Default::default()
+/// };
+/// assert_eq!(simulate(&15, &sigs), Outcome::Queued);
+/// ````
+pub fn simulate(idx: &u8, sigs: &ProcessSignals) -> Outcome {
+    if *idx == SIGKILL_IDX || *idx == SIGSTOP_IDX {
+        return Outcome::Default(default_action(idx));
+    }
+
+    if sigs.blocked.contains(idx) {
+        let pending: bool =
+            sigs.pending.contains(idx) || sigs.shared_pending.contains(idx);
+        return match POSIX_RANGE.contains(idx) && pending {
+            true => Outcome::Merged,
+            false => Outcome::Queued,
+        };
+    }
+
+    if sigs.ignored.contains(idx) {
+        return Outcome::Discarded;
+    }
+
+    if sigs.caught.contains(idx) {
+        return Outcome::Handled;
+    }
+
+    Outcome::Default(default_action(idx))
+}
+
+impl Condition {
+    /// Returns the condition in a text such as `blk:TERM` (`TERM` is
+    /// blocked) or `blk:!TERM` (`TERM` isn't blocked): a bitmap type
+    /// (`pnd`, `shd`, `blk`, `ign` or `cgt`), a `:`, and a signal name
+    /// or number (see [`signal_number`]), negated with a leading `!`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidCondition`] for a text that isn't a bitmap type
+    /// and a signal, and [`Error::UnknownSignal`] for an unknown signal.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::signals::Condition;
+    /// use sig_bitmap::{BitmapType, ProcessSignals};
+    /// let cond: Condition = Condition::parse("blk:!TERM").unwrap();
+    /// assert_eq!(cond.map, BitmapType::SigBlk);
+    /// assert!(cond.holds(&ProcessSignals::default()));
+    /// ````
+    pub fn parse(text: &str) -> Result<Condition, Error> {
+        let invalid = || Error::InvalidCondition(text.to_string());
+        let (map, sig): (&str, &str) =
+            text.split_once(':').ok_or_else(invalid)?;
+        let map: BitmapType = match map.trim().to_ascii_lowercase().as_str() {
+            "pnd" => BitmapType::SigPnd,
+            "shd" => BitmapType::ShdPnd,
+            "blk" => BitmapType::SigBlk,
+            "ign" => BitmapType::SigIgn,
+            "cgt" => BitmapType::SigCgt,
+            _ => return Err(invalid()),
+        };
+        let (set, sig): (bool, &str) = match sig.trim().strip_prefix('!') {
+            Some(sig) => (false, sig),
+            None => (true, sig.trim()),
+        };
+        let signal: u8 = signal_number(sig)
+            .ok_or_else(|| Error::UnknownSignal(sig.to_string()))?;
+
+        Ok(Condition { map, signal, set })
+    }
+
+    /// Returns `true` if the condition holds for the signal bitmaps of a
+    /// process.
+    pub fn holds(&self, sigs: &ProcessSignals) -> bool {
+        sigs.bitmap(&self.map).contains(&self.signal) == self.set
+    }
+}
+
+/// Returns the bytes in a textual dump, such as `00 00 40 01` or the
+/// output of gdb's `x/8xb` (`0x7ffe...: 0x00 0x00 ...`); bytes are hex,
+/// with an optional `0x` prefix, and anything up to a `:` on each line
+/// (the address) is skipped.
+///
+/// # Errors
+///
+/// [`Error::InvalidByte`] for the first token that isn't a hex byte.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::signals::parse_bytes;
+/// assert_eq!(parse_bytes("0x1000: 0x40\t0x01"), Ok(vec![0x40, 0x01]));
+/// ```
+pub fn parse_bytes(text: &str) -> Result<Vec<u8>, Error> {
+    text.lines()
+        .flat_map(|line| {
+            line.rsplit(':').next().unwrap_or(line).split_whitespace()
+        })
+        .map(|tok| {
+            let hex: &str = tok
+                .strip_prefix("0x")
+                .or_else(|| tok.strip_prefix("0X"))
+                .unwrap_or(tok);
+            match hex.len() {
+                1 | 2 => u8::from_str_radix(hex, 16)
+                    .map_err(|_| Error::InvalidByte(tok.to_string())),
+                _ => Err(Error::InvalidByte(tok.to_string())),
+            }
+        })
+        .collect()
+}
+
+/// Returns a list of signals interpreted from the specified bitmap.
+/// # Arguments
+/// * `map` - Reference to an unsigned 64-bit integer holding
+///   the bitmap as its contents.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::interpret;
+/// let bit_map: u64 = 0xdead;
+/// let sig_lst: Vec<String> = interpret(&bit_map);
+/// let sig_exp: Vec<&str> = vec![
+///     "HUP", "QUIT", "ILL", "ABRT", "FPE","USR1",
+///     "SEGV", "USR2", "PIPE", "TERM", "STKFLT",
+/// ];
+/// assert_eq!(sig_lst, sig_exp);
+/// ````
+pub fn interpret(map: &u64) -> Vec<String> {
+    SigSet::from_bits(*map).to_names()
+}
+
+/// Returns the sets of signals in many bitmaps at once (e.g. the raw
+/// masks in a telemetry stream), in the same order; the sets are written
+/// to a single buffer, and names are only looked up on demand (e.g. with
+/// [`SigSet::to_names`]).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{interpret_many, SigSet};
+/// let sets: Vec<SigSet> = interpret_many(&[0x4000, 0x0, 0x1001]);
+/// assert_eq!(sets[0].to_names(), vec!["TERM"]);
+/// assert!(sets[1].is_empty());
+/// assert_eq!(sets[2].len(), 2);
+/// ````
+pub fn interpret_many(maps: &[u64]) -> Vec<SigSet> {
+    let mut out: Vec<SigSet> = Vec::with_capacity(maps.len());
+    out.extend(maps.iter().map(|map| SigSet::from_bits(*map)));
+    out
+}
+
+/// Returns what [`interpret_many`] does, splitting the bitmaps across
+/// the available threads; slices shorter than [`PAR_MIN_MAPS`] are
+/// interpreted on the calling thread.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::signals::{interpret_many, interpret_many_par};
+/// let maps: Vec<u64> = (0..100_000).collect();
+/// assert_eq!(interpret_many_par(&maps), interpret_many(&maps));
+/// ````
+pub fn interpret_many_par(maps: &[u64]) -> Vec<SigSet> {
+    let threads: usize = std::thread::available_parallelism()
+        .map(|num| num.get())
+        .unwrap_or(1);
+
+    if threads < 2 || maps.len() < PAR_MIN_MAPS {
+        return interpret_many(maps);
+    }
+
+    let chunk: usize = maps.len().div_ceil(threads);
+    let mut out: Vec<SigSet> = vec![SigSet::empty(); maps.len()];
+
+    std::thread::scope(|scope| {
+        for (dst, src) in out.chunks_mut(chunk).zip(maps.chunks(chunk)) {
+            scope.spawn(move || {
+                for (set, map) in dst.iter_mut().zip(src) {
+                    *set = SigSet::from_bits(*map);
+                }
+            });
+        }
+    });
+
+    out
+}
+
+impl SigSet {
+    /// Returns an empty set.
+    pub const fn empty() -> SigSet {
+        SigSet(0x0)
+    }
+
+    /// Returns the set of signals in a bitmap.
+    pub const fn from_bits(bits: u64) -> SigSet {
+        SigSet(bits)
+    }
+
+    /// Returns the bitmap of the set.
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of signals in the set.
+    pub const fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Returns `true` if the set holds no signals.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0x0
+    }
+
+    /// Returns `true` if the signal number `idx` is in the set.
+    pub fn contains(&self, idx: &u8) -> bool {
+        (0x1..=NR_SIGS).contains(idx) && (self.0 & (0x1_u64 << (idx - 1))) != 0
+    }
+
+    /// Adds the signal number `idx` to the set; invalid signal
+    /// numbers are ignored.
+    pub fn insert(&mut self, idx: &u8) {
+        if (0x1..=NR_SIGS).contains(idx) {
+            self.0 |= 0x1_u64 << (idx - 1);
+        }
+    }
+
+    /// Removes the signal number `idx` from the set.
+    pub fn remove(&mut self, idx: &u8) {
+        if (0x1..=NR_SIGS).contains(idx) {
+            self.0 &= !(0x1_u64 << (idx - 1));
+        }
+    }
+
+    /// Returns the signals in either set.
+    pub const fn union(&self, other: &SigSet) -> SigSet {
+        SigSet(self.0 | other.0)
+    }
+
+    /// Returns the signals in both sets.
+    pub const fn intersection(&self, other: &SigSet) -> SigSet {
+        SigSet(self.0 & other.0)
+    }
+
+    /// Returns the signals in this set but not in `other`.
+    pub const fn difference(&self, other: &SigSet) -> SigSet {
+        SigSet(self.0 & !other.0)
+    }
+
+    /// Returns `true` if every signal in this set is in `other`.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::SigSet;
+    /// let set: SigSet = SigSet::from_bits(0x4001);
+    /// assert!(SigSet::from_bits(0x4000).is_subset(&set));
+    /// assert!(set.is_superset(&SigSet::empty()));
+    /// assert!(!set.intersects(&SigSet::from_bits(0x2)));
+    /// ````
+    pub const fn is_subset(&self, other: &SigSet) -> bool {
+        self.0 & !other.0 == 0x0
+    }
+
+    /// Returns `true` if every signal in `other` is in this set.
+    pub const fn is_superset(&self, other: &SigSet) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns `true` if the sets have a signal in common.
+    pub const fn intersects(&self, other: &SigSet) -> bool {
+        self.0 & other.0 != 0x0
+    }
+
+    /// Returns the set with the bytes of its bitmap reversed, as if it
+    /// had been read in the other byte order.
+    pub const fn swapped(&self) -> SigSet {
+        SigSet(self.0.swap_bytes())
+    }
+
+    /// Returns `true` if the set looks like a bitmap read in the wrong
+    /// byte order (e.g. copied from a memory dump): it only holds the
+    /// highest realtime signals (`RTMAX-15` to `RTMAX`), which programs
+    /// rarely use, and so only standard signals once swapped.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::SigSet;
+    /// let set: SigSet = SigSet::from_bits(0x0140_0000_0000_0000);
+    /// assert!(set.looks_swapped());
+    /// assert_eq!(set.swapped().to_names(), vec!["HUP", "TERM"]);
+    /// assert!(!set.swapped().looks_swapped());
+    /// ````
+    pub const fn looks_swapped(&self) -> bool {
+        self.0 != 0x0 && self.0 & SWAP_MASK == 0x0
+    }
+
+    /// Returns the signals added to and removed from `old` to get this
+    /// set.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::{SigSet, SigSetDiff};
+    /// let old: SigSet = SigSet::from_bits(0x4001);
+    /// let diff: SigSetDiff = SigSet::from_bits(0x4002).diff(&old);
+    /// assert_eq!(diff.added, SigSet::from_bits(0x2));
+    /// assert_eq!(diff.removed, SigSet::from_bits(0x1));
+    /// ````
+    pub const fn diff(&self, old: &SigSet) -> SigSetDiff {
+        SigSetDiff {
+            added: self.difference(old),
+            removed: old.difference(self),
+        }
+    }
+
+    /// Returns an iterator over the signal numbers in the set, in
+    /// ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0x1..=NR_SIGS).filter(|idx| self.contains(idx))
+    }
+
+    /// Returns the canonical names of the signals in the set, in
+    /// ascending order of signal number.
+    pub fn to_names(&self) -> Vec<String> {
+        self.iter().map(|idx| sigabbrev_np(&idx)).collect()
+    }
+
+    /// Returns the set of signals named in `names`; each name is
+    /// anything accepted by [`signal_number`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::UnknownSignal`] for the first name that doesn't match
+    /// a signal.
+    pub fn from_names<I, S>(names: I) -> Result<SigSet, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut set: SigSet = SigSet::empty();
+
+        for name in names {
+            let name: &str = name.as_ref();
+            match signal_number(name) {
+                Some(idx) => set.insert(&idx),
+                None => return Err(Error::UnknownSignal(name.to_string())),
+            }
+        }
+
+        Ok(set)
+    }
+
+    /// Returns the set of signals in a raw `sigset_t` (8 bytes) stored
+    /// in the byte order `endian`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidLength`] if there aren't exactly 8 bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::{Endian, SigSet};
+    /// let bytes: [u8; 8] = [0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x40, 0x01];
+    /// let set: SigSet = SigSet::from_bytes(&bytes, &Endian::Big).unwrap();
+    /// assert_eq!(set.to_names(), vec!["HUP", "TERM"]);
+    /// ```
+    pub fn from_bytes(bytes: &[u8], endian: &Endian) -> Result<SigSet, Error> {
+        let bytes: [u8; 8] = bytes
+            .try_into()
+            .map_err(|_| Error::InvalidLength(bytes.len()))?;
+
+        Ok(SigSet(match endian {
+            Endian::Little => u64::from_le_bytes(bytes),
+            Endian::Big => u64::from_be_bytes(bytes),
+        }))
+    }
+
+    /// Returns the set of signals in a raw `sigset_t` (8 bytes) stored
+    /// as two 32-bit words in the order `order`, each in the byte order
+    /// `endian`; e.g. a capture from a 32-bit big-endian system, which
+    /// decodes to the wrong signals when read as a single 64-bit word.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidLength`] if there aren't exactly 8 bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::{Endian, SigSet, WordOrder};
+    /// let bytes: [u8; 8] = [0x0, 0x0, 0x40, 0x01, 0x0, 0x0, 0x0, 0x0];
+    /// let set: SigSet =
+    ///     SigSet::from_words(&bytes, &Endian::Big, &WordOrder::LowFirst)
+    ///         .unwrap();
+    /// assert_eq!(set.to_names(), vec!["HUP", "TERM"]);
+    /// ```
+    pub fn from_words(
+        bytes: &[u8],
+        endian: &Endian,
+        order: &WordOrder,
+    ) -> Result<SigSet, Error> {
+        let bytes: [u8; 8] = bytes
+            .try_into()
+            .map_err(|_| Error::InvalidLength(bytes.len()))?;
+        let word = |half: [u8; 4]| -> u64 {
+            match endian {
+                Endian::Little => u32::from_le_bytes(half) as u64,
+                Endian::Big => u32::from_be_bytes(half) as u64,
+            }
+        };
+        let fst: u64 = word([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let snd: u64 = word([bytes[4], bytes[5], bytes[6], bytes[7]]);
+
+        Ok(SigSet(match order {
+            WordOrder::LowFirst => (snd << 32) | fst,
+            WordOrder::HighFirst => (fst << 32) | snd,
+        }))
+    }
+}
+
+impl SigSet {
+    /// Returns a set composed from a list of specifications, applied
+    /// left to right: a signal name (or `+NAME`) adds the signal, a
+    /// preset (`@NAME`, see [`preset`]) adds all of its signals, and a
+    /// leading `-` (`-NAME`, `-@NAME`) removes them instead.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::UnknownSignal`] or [`Error::UnknownPreset`] for the
+    /// first specification that doesn't match a signal or a preset.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::SigSet;
+    /// let set: SigSet = SigSet::compose(["@all", "-KILL", "-STOP"]).unwrap();
+    /// assert_eq!(set.bits(), 0xfffffffffffbfeff);
+    /// ```
+    pub fn compose<I, S>(specs: I) -> Result<SigSet, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut set: SigSet = SigSet::empty();
+
+        for spec in specs {
+            let spec: &str = spec.as_ref().trim();
+            let (add, name): (bool, &str) = match spec.strip_prefix('-') {
+                Some(name) => (false, name),
+                None => (true, spec.strip_prefix('+').unwrap_or(spec)),
+            };
+            let part: SigSet = match name.strip_prefix('@') {
+                Some(name) => preset(name)
+                    .ok_or_else(|| Error::UnknownPreset(name.to_string()))?,
+                None => SigSet::from_names([name])?,
+            };
+
+            set = match add {
+                true => set.union(&part),
+                false => set.difference(&part),
+            };
+        }
+
+        Ok(set)
+    }
+}
+
+impl ProcessSignals {
+    /// Returns the signal bitmap of the specified type.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::{BitmapType, ProcessSignals, SigSet};
+    /// let sigs: ProcessSignals = ProcessSignals {
+    ///     blocked: SigSet::from_bits(0x4000),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(sigs.bitmap(&BitmapType::SigBlk).bits(), 0x4000);
+    /// ````
+    pub fn bitmap(&self, typ: &BitmapType) -> SigSet {
+        match typ {
+            BitmapType::SigPnd => self.pending,
+            BitmapType::ShdPnd => self.shared_pending,
+            BitmapType::SigBlk => self.blocked,
+            BitmapType::SigIgn => self.ignored,
+            BitmapType::SigCgt => self.caught,
+        }
+    }
+
+    /// Returns the signals that are neither caught nor ignored, i.e.
+    /// those the kernel takes the default action for (see
+    /// [`default_action`]) when they are delivered.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::{ProcessSignals, SigSet};
+    /// let sigs: ProcessSignals = ProcessSignals {
+    ///     ignored: SigSet::from_bits(0x1000),
+    ///     caught: SigSet::from_bits(0x4000),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(sigs.default_disposition().bits(), !0x5000);
+    /// ```
+    pub fn default_disposition(&self) -> SigSet {
+        SigSet::from_bits(u64::MAX)
+            .difference(&self.caught)
+            .difference(&self.ignored)
+    }
+}
+
+impl SigSetDiff {
+    /// Returns `true` if no signals were added or removed.
+    pub const fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl Signal {
+    /// Returns the number of the signal.
+    pub const fn number(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns the canonical name of the signal, without the `SIG`
+    /// prefix (see [`sigabbrev_np`]).
+    pub fn name(&self) -> String {
+        sigabbrev_np(&self.0)
+    }
+
+    /// Returns the category of the signal (see [`category`]).
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::{Signal, SignalCategory};
+    /// let sig: Signal = "SEGV".parse().unwrap();
+    /// assert_eq!(sig.category(), SignalCategory::Fault);
+    /// assert_eq!(Signal::try_from(34).unwrap().category(), SignalCategory::Realtime);
+    /// ````
+    pub fn category(&self) -> SignalCategory {
+        category(&self.0)
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for Signal {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Signal, Error> {
+        normalize_signal_name(name)
+    }
+}
+
+impl TryFrom<u8> for Signal {
+    type Error = Error;
+
+    fn try_from(idx: u8) -> Result<Signal, Error> {
+        match (0x1..=NR_SIGS).contains(&idx) {
+            true => Ok(Signal(idx)),
+            false => Err(Error::UnknownSignal(idx.to_string())),
+        }
+    }
+}
+
+impl From<Signal> for u8 {
+    fn from(sig: Signal) -> u8 {
+        sig.0
+    }
+}
+
+impl From<u64> for SigSet {
+    fn from(bits: u64) -> SigSet {
+        SigSet::from_bits(bits)
+    }
+}
+
+impl From<SigSet> for u64 {
+    fn from(set: SigSet) -> u64 {
+        set.bits()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sigabbrev_np() {
+        let tests: Vec<(&str, u8)> = Vec::<(&str, u8)>::from([
+            ("KILL", 0x09),
+            ("WINCH", 0x1c),
+            ("IO", 0x1d),
+            ("PWR", 0x1e),
+            ("SYS", 0x1f),
+            ("RTMIN", 0x22),
+            ("RTMIN+2", 0x24),
+            ("RTMAX", 0x40),
+            ("RTMAX-2", 0x3e),
+            ("INVL", 0x00),
+        ]);
+
+        for test in tests {
+            assert_eq!(test.0, sigabbrev_np(&test.1));
+        }
+    }
+
+    #[test]
+    fn test_name_style() {
+        let tests: Vec<(NameStyle, u8, &str)> = vec![
+            (NameStyle::default(), 15, "TERM"),
+            (NameStyle::default(), 63, "RTMAX-1"),
+            (
+                NameStyle {
+                    prefix: true,
+                    ..NameStyle::default()
+                },
+                15,
+                "SIGTERM",
+            ),
+            (
+                NameStyle {
+                    lowercase: true,
+                    ..NameStyle::default()
+                },
+                36,
+                "rtmin+2",
+            ),
+            (
+                NameStyle {
+                    rt: RtStyle::Min,
+                    ..NameStyle::default()
+                },
+                63,
+                "RTMIN+29",
+            ),
+            (
+                NameStyle {
+                    rt: RtStyle::Min,
+                    ..NameStyle::default()
+                },
+                32,
+                "RTMIN-2",
+            ),
+            (
+                NameStyle {
+                    prefix: true,
+                    rt: RtStyle::Number,
+                    lowercase: true,
+                },
+                63,
+                "63",
+            ),
+            (
+                NameStyle {
+                    prefix: true,
+                    rt: RtStyle::Number,
+                    lowercase: true,
+                },
+                9,
+                "sigkill",
+            ),
+        ];
+
+        for test in tests {
+            let name: String = test.0.name(&test.1);
+
+            assert_eq!(name, test.2);
+            assert_eq!(signal_number(&name), Some(test.1));
+        }
+    }
+
+    #[test]
+    fn test_signal_number() {
+        let tests: Vec<(&str, Option<u8>)> = Vec::<(&str, Option<u8>)>::from([
+            ("KILL", Some(0x09)),
+            ("sigusr1", Some(0x0a)),
+            ("15", Some(0x0f)),
+            ("RTMIN-2", Some(0x20)),
+            ("RTMIN+20", Some(0x36)),
+            ("RTMAX", Some(0x40)),
+            ("RTMAX+1", None),
+            ("0", None),
+            ("BOGUS", None),
+            ("SIGIOT", Some(0x06)),
+            ("cld", Some(0x11)),
+            (" SIGRTMIN + 3\t", Some(0x25)),
+            ("SIG IOT", Some(0x06)),
+            ("SIGSIGIOT", None),
+        ]);
+
+        for test in tests {
+            assert_eq!(signal_number(test.0), test.1);
+        }
+    }
+
+    #[test]
+    fn test_normalize_signal_name() {
+        let tests: Vec<(&str, Result<&str, Error>)> = vec![
+            ("sigterm", Ok("TERM")),
+            ("SIGIOT", Ok("ABRT")),
+            ("CLD", Ok("CHLD")),
+            ("SIGPOLL", Ok("IO")),
+            ("SIGSYS", Ok("SYS")),
+            ("31", Ok("SYS")),
+            ("34", Ok("RTMIN")),
+            ("SIGRTMIN+20", Ok("RTMAX-10")),
+            ("", Err(Error::UnknownSignal(String::new()))),
+            (
+                "SIGINFO",
+                Err(Error::UnknownSignal(String::from("SIGINFO"))),
+            ),
+        ];
+
+        for test in tests {
+            assert_eq!(
+                normalize_signal_name(test.0).map(|sig| sig.name()),
+                test.1.map(String::from),
+            );
+        }
+    }
+
+    #[test]
+    fn test_simulate() {
+        let sigs: ProcessSignals = ProcessSignals {
+            pid: 42,
+            pending: SigSet::from_bits(0x1),
+            shared_pending: SigSet::empty(),
+            blocked: SigSet::from_bits(0x1 | 0x100 | 0x200000000),
+            ignored: SigSet::from_bits(0x1000 | 0x200000000),
+            caught: SigSet::from_bits(0x2),
+        };
+        let tests: Vec<(u8, Outcome)> = Vec::<(u8, Outcome)>::from([
+            (0x01, Outcome::Merged),
+            (0x02, Outcome::Handled),
+            (0x09, Outcome::Default(DefaultAction::Terminate)),
+            (0x0d, Outcome::Discarded),
+            (0x0f, Outcome::Default(DefaultAction::Terminate)),
+            (0x11, Outcome::Default(DefaultAction::Ignore)),
+            (0x14, Outcome::Default(DefaultAction::Stop)),
+            (0x1f, Outcome::Default(DefaultAction::CoreDump)),
+            (0x22, Outcome::Queued),
+        ]);
+
+        for test in tests {
+            assert_eq!(simulate(&test.0, &sigs), test.1);
+        }
+    }
+
+    #[test]
+    fn test_category() {
+        let tests: Vec<(u8, SignalCategory)> = vec![
+            (0x00, SignalCategory::Misc),
+            (0x01, SignalCategory::Termination),
+            (0x06, SignalCategory::Fault),
+            (0x0a, SignalCategory::Misc),
+            (0x0d, SignalCategory::IO),
+            (0x0e, SignalCategory::Timer),
+            (0x14, SignalCategory::JobControl),
+            (0x1e, SignalCategory::Misc),
+            (0x1f, SignalCategory::Fault),
+            (0x20, SignalCategory::Realtime),
+            (0x40, SignalCategory::Realtime),
+            (0x41, SignalCategory::Misc),
+        ];
+
+        for test in tests {
+            assert_eq!(category(&test.0), test.1);
+        }
+    }
+
+    #[test]
+    fn test_bit_map_type_str() {
+        let tests: Vec<(BitmapType, &str)> = Vec::<(BitmapType, &str)>::from([
+            (BitmapType::SigPnd, "SigPnd"),
+            (BitmapType::ShdPnd, "ShdPnd"),
+            (BitmapType::SigBlk, "SigBlk"),
+            (BitmapType::SigIgn, "SigIgn"),
+            (BitmapType::SigCgt, "SigCgt"),
+        ]);
+
+        for test in tests {
+            assert!(test.0.to_string().contains(test.1));
+        }
+    }
+    #[test]
+    fn test_condition_parse() {
+        let tests: Vec<(&str, Result<Condition, Error>)> = vec![
+            (
+                "blk:!TERM",
+                Ok(Condition {
+                    map: BitmapType::SigBlk,
+                    signal: 15,
+                    set: false,
+                }),
+            ),
+            (
+                "IGN: sigpipe",
+                Ok(Condition {
+                    map: BitmapType::SigIgn,
+                    signal: 13,
+                    set: true,
+                }),
+            ),
+            (
+                "pnd:10",
+                Ok(Condition {
+                    map: BitmapType::SigPnd,
+                    signal: 10,
+                    set: true,
+                }),
+            ),
+            (
+                "blk:BOGUS",
+                Err(Error::UnknownSignal(String::from("BOGUS"))),
+            ),
+            (
+                "mask:TERM",
+                Err(Error::InvalidCondition(String::from("mask:TERM"))),
+            ),
+            ("TERM", Err(Error::InvalidCondition(String::from("TERM")))),
+        ];
+
+        for test in tests {
+            assert_eq!(Condition::parse(test.0), test.1);
+        }
+    }
+
+    #[test]
+    fn test_interpret() {
+        let bit_map: u64 = 0xbadc0ffee;
+        let sig_chk: Vec<&str> = vec![
+            "INT", "QUIT", "ILL", "ABRT", "BUS", "FPE", "KILL", "USR1", "SEGV",
+            "USR2", "PIPE", "ALRM", "TERM", "STKFLT", "URG", "XCPU", "XFSZ",
+            "PROF", "WINCH", "PWR", "RTMIN-2", "RTMIN-1", "RTMIN", "RTMIN+2",
+        ];
+        let sig_ret: Vec<String> = interpret(&bit_map);
+        assert_eq!(sig_ret, sig_chk);
+    }
+
+    #[test]
+    fn test_interpret_many() {
+        let tests: Vec<Vec<u64>> = vec![
+            vec![],
+            vec![0x0, 0x4000, 0xbadc0ffee, u64::MAX],
+            (0..PAR_MIN_MAPS as u64 * 3 + 7)
+                .map(|map| map << 17)
+                .collect(),
+        ];
+
+        for test in tests {
+            let sets: Vec<SigSet> = interpret_many(&test);
+            let bits: Vec<u64> = sets.iter().map(SigSet::bits).collect();
+
+            assert_eq!(bits, test);
+            assert_eq!(interpret_many_par(&test), sets);
+        }
+    }
+
+    #[test]
+    fn test_sig_set_predicates() {
+        let tests: Vec<(u64, u64, bool, bool, bool)> = vec![
+            (0x0, 0x0, true, true, false),
+            (0x0, 0x4001, true, false, false),
+            (0x4000, 0x4001, true, false, true),
+            (0x4001, 0x4001, true, true, true),
+            (0x4001, 0x4000, false, true, true),
+            (0x4001, 0x2, false, false, false),
+            (u64::MAX, 0x8000_0000_0000_0000, false, true, true),
+        ];
+
+        for test in tests {
+            let set: SigSet = SigSet::from_bits(test.0);
+            let other: SigSet = SigSet::from_bits(test.1);
+
+            assert_eq!(set.is_subset(&other), test.2);
+            assert_eq!(set.is_superset(&other), test.3);
+            assert_eq!(set.intersects(&other), test.4);
+        }
+    }
+
+    #[test]
+    fn test_sig_set_looks_swapped() {
+        let tests: Vec<(u64, bool)> = vec![
+            (0x0, false),
+            (0x4001, false),
+            (0x0140_0000_0000_0000, true),
+            (0x8000_0000_0000_0000, true),
+            (0x0001_0000_0000_0000, true),
+            (0x0000_8000_0000_0000, false),
+            (0x0140_0000_0000_4001, false),
+            (0x0000_0002_0000_0000, false),
+        ];
+
+        for test in tests {
+            assert_eq!(SigSet::from_bits(test.0).looks_swapped(), test.1);
+        }
+    }
+
+    #[test]
+    fn test_sig_set_round_trip() {
+        // Every single bit, the edges, and a pseudo-random sample
+        // (xorshift64) of masks must survive a trip through names.
+        let mut masks: Vec<u64> = (0..64).map(|bit| 0x1_u64 << bit).collect();
+        let mut state: u64 = 0x5eed_cafe_f00d_beef;
+
+        masks.extend([0x0, u64::MAX]);
+        for _ in 0..4096 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            masks.push(state);
+        }
+
+        for mask in masks {
+            let set: SigSet = SigSet::from_bits(mask);
+            let names: Vec<String> = set.to_names();
+            assert_eq!(names.len(), set.len());
+            assert_eq!(SigSet::from_names(&names), Ok(set));
+        }
+
+        // Signals 29 to 31 carry the kernel's names.
+        let set: SigSet = SigSet::from_bits(0x7000_0000);
+        assert_eq!(set.to_names(), ["IO", "PWR", "SYS"]);
+        assert_eq!(SigSet::from_names(["IO", "PWR", "SYS"]), Ok(set));
+    }
+
+    #[test]
+    fn test_sig_set_from_names() {
+        assert_eq!(
+            SigSet::from_names(["1", "sigint", "RTMIN+20"]),
+            Ok(SigSet::from_bits(0x3 | (0x1 << 0x35))),
+        );
+        assert_eq!(
+            SigSet::from_names(["HUP", "BOGUS"]),
+            Err(Error::UnknownSignal(String::from("BOGUS"))),
+        );
+    }
+
+    #[test]
+    fn test_sig_set_compose() {
+        let tests: Vec<(Vec<&str>, Result<u64, Error>)> = vec![
+            (vec!["HUP", "+TERM"], Ok(0x4001)),
+            (vec!["@job-control", "-STOP"], Ok(0x3a0000)),
+            (vec!["@all-rt", "-@all-rt", "INT"], Ok(0x2)),
+            (vec!["@standard"], Ok(0x7fffffff)),
+            (vec!["@all", "-@standard"], Ok(0xffffffff80000000)),
+            (
+                vec!["@bogus"],
+                Err(Error::UnknownPreset(String::from("bogus"))),
+            ),
+            (
+                vec!["-BOGUS"],
+                Err(Error::UnknownSignal(String::from("BOGUS"))),
+            ),
+        ];
+
+        for test in tests {
+            assert_eq!(SigSet::compose(test.0).map(|set| set.bits()), test.1);
+        }
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        let tests: Vec<(&str, Result<Vec<u8>, Error>)> = vec![
+            ("00 00 40 01", Ok(vec![0x0, 0x0, 0x40, 0x1])),
+            (
+                "0x7fffffffe0f0:\t0x00\t0x40\n0x7fffffffe0f2:\t0x01",
+                Ok(vec![0x0, 0x40, 0x1]),
+            ),
+            ("0x4010 <set>:\t0xff\t0xA", Ok(vec![0xff, 0xa])),
+            ("", Ok(vec![])),
+            ("00 100", Err(Error::InvalidByte(String::from("100")))),
+            ("0xzz", Err(Error::InvalidByte(String::from("0xzz")))),
+        ];
+
+        for test in tests {
+            assert_eq!(parse_bytes(test.0), test.1);
+        }
+    }
+
+    #[test]
+    fn test_sig_set_from_bytes() {
+        let bytes: [u8; 8] = [0x01, 0x40, 0x0, 0x0, 0x0, 0x0, 0x0, 0x80];
+
+        assert_eq!(
+            SigSet::from_bytes(&bytes, &Endian::Little),
+            Ok(SigSet::from_bits(0x8000000000004001)),
+        );
+        assert_eq!(
+            SigSet::from_bytes(&bytes, &Endian::Big),
+            Ok(SigSet::from_bits(0x0140000000000080)),
+        );
+        assert_eq!(
+            SigSet::from_bytes(&bytes[..4], &Endian::Little),
+            Err(Error::InvalidLength(4)),
+        );
+    }
+
+    #[test]
+    fn test_sig_set_from_words() {
+        let bytes: [u8; 8] = [0x01, 0x40, 0x0, 0x0, 0x0, 0x0, 0x0, 0x80];
+        let tests: Vec<(Endian, WordOrder, u64)> = vec![
+            (Endian::Little, WordOrder::LowFirst, 0x8000000000004001),
+            (Endian::Little, WordOrder::HighFirst, 0x0000400180000000),
+            (Endian::Big, WordOrder::LowFirst, 0x0000008001400000),
+            (Endian::Big, WordOrder::HighFirst, 0x0140000000000080),
+        ];
+
+        for test in tests {
+            assert_eq!(
+                SigSet::from_words(&bytes, &test.0, &test.1),
+                Ok(SigSet::from_bits(test.2)),
+            );
+        }
+        assert_eq!(
+            SigSet::from_words(&[0x0; 16], &Endian::Big, &WordOrder::LowFirst),
+            Err(Error::InvalidLength(16)),
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Output sinks: where rendered reports and records are written.
+use crate::session::json_escape;
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Error, ErrorKind, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::Path,
+    time::Duration,
+};
+
+// Socket of the local syslog daemon.
+#[cfg(unix)]
+const DEV_LOG: &str = "/dev/log";
+
+// Priority of the messages sent to syslog: facility `user` (1), severity
+// `info` (6).
+#[cfg(unix)]
+const SYSLOG_PRI: u8 = (1 << 3) | 6;
+
+// Time allowed to connect to a webhook, and for each read or write.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A destination for rendered output, e.g. from [`crate::format`].
+pub trait OutputSink {
+    /// Writes rendered text to the sink as-is (it carries its own line
+    /// or record terminators), making it visible right away.
+    fn emit(&mut self, text: &str) -> Result<(), Error>;
+}
+
+/// A sink writing to the standard output.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdoutSink;
+
+/// A sink appending to a file.
+#[derive(Debug)]
+pub struct FileSink {
+    file: File,
+}
+
+/// A sink sending each line to the local syslog daemon (through
+/// `/dev/log`), tagged `sig-bitmap` with the PID, at the `user.info`
+/// priority; empty lines are skipped.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct SyslogSink {
+    socket: UnixDatagram,
+}
+
+/// A sink posting the output to an HTTP webhook, a request per write:
+/// JSON output (e.g. `scan --format json`) is posted as is (several
+/// objects as an array), and text is wrapped as `{"text": ...}`, the
+/// payload of Slack-compatible incoming webhooks. Only `http://` URLs
+/// are supported; for HTTPS, post through a local relay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WebhookSink {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl OutputSink for StdoutSink {
+    fn emit(&mut self, text: &str) -> Result<(), Error> {
+        let mut out: io::StdoutLock = io::stdout().lock();
+
+        out.write_all(text.as_bytes())?;
+        out.flush()
+    }
+}
+
+impl FileSink {
+    /// Returns a sink appending to the file at `path`, creating it if
+    /// it doesn't exist.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::sink::{FileSink, OutputSink};
+    /// use std::path::PathBuf;
+    /// let path: PathBuf = std::env::temp_dir().join("sig-bitmap.log");
+    /// let mut sink: FileSink = FileSink::append(&path).unwrap();
+    /// sink.emit("PID: 42     SigPnd: 0  [0x0000000000000000]: NONE\n")
+    ///     .unwrap();
+    /// ```
+    pub fn append(path: &Path) -> Result<FileSink, Error> {
+        Ok(FileSink {
+            file: OpenOptions::new().create(true).append(true).open(path)?,
+        })
+    }
+}
+
+impl OutputSink for FileSink {
+    fn emit(&mut self, text: &str) -> Result<(), Error> {
+        self.file.write_all(text.as_bytes())?;
+        self.file.flush()
+    }
+}
+
+#[cfg(unix)]
+impl SyslogSink {
+    /// Returns a sink sending to the local syslog daemon.
+    ///
+    /// # Returns
+    ///
+    /// An error if `/dev/log` can't be connected to.
+    pub fn open() -> Result<SyslogSink, Error> {
+        SyslogSink::connect(Path::new(DEV_LOG))
+    }
+
+    /// Returns a sink sending to the syslog socket at `path` instead of
+    /// `/dev/log`.
+    pub fn connect(path: &Path) -> Result<SyslogSink, Error> {
+        let socket: UnixDatagram = UnixDatagram::unbound()?;
+
+        socket.connect(path).map_err(|err| {
+            Error::new(
+                err.kind(),
+                format!("can't connect to {}: {}", path.display(), err),
+            )
+        })?;
+        Ok(SyslogSink { socket })
+    }
+}
+
+#[cfg(unix)]
+impl OutputSink for SyslogSink {
+    fn emit(&mut self, text: &str) -> Result<(), Error> {
+        for line in text.split(['\n', '\0']).filter(|ln| !ln.is_empty()) {
+            self.socket.send(
+                format!(
+                    "<{}>sig-bitmap[{}]: {}",
+                    SYSLOG_PRI,
+                    std::process::id(),
+                    line
+                )
+                .as_bytes(),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl WebhookSink {
+    /// Returns a sink posting to `url` (`http://host[:port][/path]`).
+    ///
+    /// # Returns
+    ///
+    /// An error if the URL isn't an `http://` one.
+    ///
+    /// # Example
+    /// ```
+    /// use sig_bitmap::sink::WebhookSink;
+    /// assert!(WebhookSink::new("http://alerts.local:9093/hook").is_ok());
+    /// assert!(WebhookSink::new("https://hooks.slack.com/x").is_err());
+    /// ```
+    pub fn new(url: &str) -> Result<WebhookSink, Error> {
+        let invalid = |why: &str| {
+            Error::new(ErrorKind::InvalidInput, format!("{}: {}", why, url))
+        };
+        let rest: &str = match url.split_once("://") {
+            Some((scheme, rest)) if scheme.eq_ignore_ascii_case("http") => rest,
+            Some(_) => {
+                return Err(invalid("only http:// webhooks are supported"))
+            }
+            None => return Err(invalid("invalid webhook URL")),
+        };
+        let (auth, path): (&str, &str) = match rest.find('/') {
+            Some(pos) => rest.split_at(pos),
+            None => (rest, "/"),
+        };
+        let (host, port): (&str, u16) = match auth.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (
+                host,
+                port.parse::<u16>()
+                    .map_err(|_| invalid("invalid webhook port"))?,
+            ),
+            _ => (auth, 80),
+        };
+
+        if host.is_empty() {
+            return Err(invalid("invalid webhook URL"));
+        }
+
+        Ok(WebhookSink {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    // Return the request posting `text`, as described for the sink.
+    fn request(&self, text: &str) -> String {
+        let lines: Vec<&str> = text
+            .split(['\n', '\0'])
+            .map(str::trim)
+            .filter(|ln| !ln.is_empty())
+            .collect();
+        let body: String = match lines.as_slice() {
+            [obj] if obj.starts_with('{') => obj.to_string(),
+            objs if objs.iter().all(|ln| ln.starts_with('{')) => {
+                format!("[{}]", objs.join(","))
+            }
+            _ => format!("{{\"text\":\"{}\"}}", json_escape(text.trim_end())),
+        };
+
+        format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: sig-bitmap/{}\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            self.path,
+            match self.port {
+                80 => self.host.clone(),
+                port => format!("{}:{}", self.host, port),
+            },
+            env!("CARGO_PKG_VERSION"),
+            body.len(),
+            body,
+        )
+    }
+}
+
+impl OutputSink for WebhookSink {
+    fn emit(&mut self, text: &str) -> Result<(), Error> {
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+
+        let addr = (self.host.trim_matches(['[', ']']), self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| {
+                Error::new(ErrorKind::NotFound, self.host.clone())
+            })?;
+        let mut conn: TcpStream =
+            TcpStream::connect_timeout(&addr, WEBHOOK_TIMEOUT)?;
+
+        conn.set_read_timeout(Some(WEBHOOK_TIMEOUT))?;
+        conn.set_write_timeout(Some(WEBHOOK_TIMEOUT))?;
+        conn.write_all(self.request(text).as_bytes())?;
+
+        let mut status: String = String::new();
+        BufReader::new(conn).read_line(&mut status)?;
+
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(()),
+            _ => Err(Error::other(format!(
+                "webhook answered: {}",
+                status.trim_end()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, io::Read, net::TcpListener, path::PathBuf, thread};
+
+    #[test]
+    fn test_file_sink_append() {
+        let path: PathBuf = std::env::temp_dir()
+            .join(format!("sig-bitmap-sink-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        for text in ["first\n", "second\0"] {
+            FileSink::append(&path).unwrap().emit(text).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\0");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_syslog_sink() {
+        let path: PathBuf = std::env::temp_dir()
+            .join(format!("sig-bitmap-syslog-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let log: UnixDatagram = UnixDatagram::bind(&path).unwrap();
+
+        SyslogSink::connect(&path)
+            .unwrap()
+            .emit("PID: 42     SigBlk: 1\n\nPID: 43     SigBlk: 0\n")
+            .unwrap();
+
+        for want in ["PID: 42     SigBlk: 1", "PID: 43     SigBlk: 0"] {
+            let mut buf: [u8; 128] = [0; 128];
+            let len: usize = log.recv(&mut buf).unwrap();
+            assert_eq!(
+                String::from_utf8_lossy(&buf[..len]),
+                format!("<14>sig-bitmap[{}]: {}", std::process::id(), want),
+            );
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_webhook_sink_new() {
+        // Host, port and path of a webhook.
+        type Parsed = Option<(&'static str, u16, &'static str)>;
+
+        let tests: Vec<(&str, Parsed)> = vec![
+            ("http://alerts.local", Some(("alerts.local", 80, "/"))),
+            (
+                "HTTP://10.0.0.1:9093/a/b?c=d",
+                Some(("10.0.0.1", 9093, "/a/b?c=d")),
+            ),
+            ("http://[::1]:8080/hook", Some(("[::1]", 8080, "/hook"))),
+            ("http://[::1]/hook", Some(("[::1]", 80, "/hook"))),
+            ("https://hooks.slack.com/services/x", None),
+            ("http://alerts.local:http/", None),
+            ("http:///hook", None),
+            ("alerts.local/hook", None),
+        ];
+
+        for test in tests {
+            let sink = WebhookSink::new(test.0).ok();
+
+            assert_eq!(
+                sink,
+                test.1.map(|(host, port, path)| WebhookSink {
+                    host: host.to_string(),
+                    port,
+                    path: path.to_string(),
+                }),
+            );
+        }
+    }
+
+    #[test]
+    fn test_webhook_sink_request() {
+        let sink: WebhookSink = WebhookSink::new("http://h:8080/x").unwrap();
+        let tests: Vec<(&str, &str)> = vec![
+            ("{\"a\":1}\n", "{\"a\":1}"),
+            ("{\"a\":1}\n{\"a\":2}\n", "[{\"a\":1},{\"a\":2}]"),
+            (
+                "PID: 42     \"TERM\"\n\tKILL\n",
+                "{\"text\":\"PID: 42     \\\"TERM\\\"\\n\\tKILL\"}",
+            ),
+        ];
+
+        for test in tests {
+            let req: String = sink.request(test.0);
+            let (head, body) = req.split_once("\r\n\r\n").unwrap();
+
+            assert!(head.starts_with("POST /x HTTP/1.1\r\nHost: h:8080\r\n"));
+            assert!(head.contains(&format!("Content-Length: {}", body.len())));
+            assert_eq!(body, test.1);
+        }
+    }
+
+    #[test]
+    fn test_webhook_sink_emit() {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url: String =
+            format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let mut got: Vec<String> = Vec::new();
+
+            for status in ["204 No Content", "500 Internal Server Error"] {
+                let (mut conn, _) = listener.accept().unwrap();
+                let mut req: String = String::new();
+
+                // The request ends with its body, as the client closes
+                // the connection once it has read the response.
+                while !req.ends_with('}') {
+                    let mut buf: [u8; 512] = [0; 512];
+                    let len: usize = conn.read(&mut buf).unwrap();
+                    req.push_str(&String::from_utf8_lossy(&buf[..len]));
+                }
+                write!(conn, "HTTP/1.1 {}\r\n\r\n", status).unwrap();
+                got.push(req);
+            }
+
+            got
+        });
+        let mut sink: WebhookSink = WebhookSink::new(&url).unwrap();
+
+        assert!(sink.emit("{\"pid\":42}\n").is_ok());
+        assert!(sink.emit("{\"pid\":43}\n").is_err());
+
+        let got: Vec<String> = server.join().unwrap();
+        assert!(got[0].starts_with("POST /hook HTTP/1.1\r\n"));
+        assert!(got[0].ends_with("\r\n\r\n{\"pid\":42}"));
+        assert!(got[1].ends_with("\r\n\r\n{\"pid\":43}"));
+    }
+}
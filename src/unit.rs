@@ -0,0 +1,318 @@
+//! Signal settings of systemd units (`systemd.kill(5)`, `systemd.exec(5)`),
+//! cross-checked against the signal bitmaps of the live process.
+use crate::{
+    doctor::{Check, Finding, Severity},
+    error::Error,
+    signals::{
+        sigabbrev_np, signal_number, simulate, DefaultAction, Outcome,
+        ProcessSignals,
+    },
+};
+
+// Signal numbers of `PIPE`, `KILL` and `TERM`.
+const SIGPIPE_IDX: u8 = 0x0d;
+const SIGKILL_IDX: u8 = 0x09;
+const SIGTERM_IDX: u8 = 0x0f;
+
+/// The signal settings of a systemd unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnitSignals {
+    /// `IgnoreSIGPIPE=`: start the process with `PIPE` ignored.
+    pub ignore_sigpipe: bool,
+
+    /// `KillSignal=`: the signal to stop the process with.
+    pub kill_signal: u8,
+
+    /// `RestartKillSignal=`: the signal to stop the process with on a
+    /// restart, `KillSignal=` if unset.
+    pub restart_kill_signal: Option<u8>,
+
+    /// `FinalKillSignal=`: the signal sent when stopping times out.
+    pub final_kill_signal: u8,
+}
+
+// The systemd defaults.
+impl Default for UnitSignals {
+    fn default() -> UnitSignals {
+        UnitSignals {
+            ignore_sigpipe: true,
+            kill_signal: SIGTERM_IDX,
+            restart_kill_signal: None,
+            final_kill_signal: SIGKILL_IDX,
+        }
+    }
+}
+
+// Parse a systemd boolean, or `None` if it isn't one.
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.to_ascii_lowercase().as_str() {
+        "1" | "yes" | "y" | "true" | "t" | "on" => Some(true),
+        "0" | "no" | "n" | "false" | "f" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Returns the signal settings in the contents of a systemd unit file;
+/// settings that aren't in the file keep their defaults, and an empty
+/// value resets a setting to its default, as with systemd. Invalid
+/// booleans are ignored (systemd logs and skips them).
+///
+/// # Errors
+///
+/// [`Error::UnknownSignal`] for a signal setting that doesn't name a
+/// signal.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::unit::{parse_unit, UnitSignals};
+/// let unit: UnitSignals =
+///     parse_unit("[Service]\nExecStart=/bin/app\nKillSignal=SIGINT\n")
+///         .unwrap();
+/// assert_eq!(unit.kill_signal, 2);
+/// ```
+pub fn parse_unit(text: &str) -> Result<UnitSignals, Error> {
+    let mut unit: UnitSignals = UnitSignals::default();
+    let dfl: UnitSignals = UnitSignals::default();
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with(['#', ';', '[']) {
+            continue;
+        }
+
+        let (key, val): (&str, &str) = match line.split_once('=') {
+            Some((key, val)) => (key.trim(), val.trim()),
+            None => continue,
+        };
+        let sig = |dfl: u8| -> Result<u8, Error> {
+            match val.is_empty() {
+                true => Ok(dfl),
+                false => signal_number(val)
+                    .ok_or_else(|| Error::UnknownSignal(val.to_string())),
+            }
+        };
+
+        match key {
+            "IgnoreSIGPIPE" if val.is_empty() => {
+                unit.ignore_sigpipe = dfl.ignore_sigpipe
+            }
+            "IgnoreSIGPIPE" => {
+                unit.ignore_sigpipe =
+                    parse_bool(val).unwrap_or(unit.ignore_sigpipe)
+            }
+            "KillSignal" => unit.kill_signal = sig(dfl.kill_signal)?,
+            "RestartKillSignal" if val.is_empty() => {
+                unit.restart_kill_signal = None
+            }
+            "RestartKillSignal" => unit.restart_kill_signal = Some(sig(0)?),
+            "FinalKillSignal" => {
+                unit.final_kill_signal = sig(dfl.final_kill_signal)?
+            }
+            _ => continue,
+        }
+    }
+
+    Ok(unit)
+}
+
+/// Returns the name of the systemd unit from the cgroup path of a
+/// process (see [`crate::procfs::proc_cgroup`]): the innermost service
+/// or scope, or `None` if the process isn't in one.
+///
+/// # Example
+/// ```
+/// use sig_bitmap::unit::unit_name;
+/// assert_eq!(
+///     unit_name("/system.slice/nginx.service"),
+///     Some("nginx.service"),
+/// );
+/// ````
+pub fn unit_name(cgroup: &str) -> Option<&str> {
+    cgroup
+        .rsplit('/')
+        .find(|name| name.ends_with(".service") || name.ends_with(".scope"))
+}
+
+// Return why a signal sent to a process won't stop it right away, or
+// `None` if it is acted upon (by default or by a handler).
+fn obstacle(idx: &u8, sigs: &ProcessSignals) -> Option<&'static str> {
+    match simulate(idx, sigs) {
+        Outcome::Queued | Outcome::Merged => Some("blocked by the process"),
+        Outcome::Discarded => Some("ignored by the process"),
+        Outcome::Default(DefaultAction::Ignore) => Some("ignored by default"),
+        Outcome::Default(DefaultAction::Stop) => Some("stops (not ends) it"),
+        Outcome::Default(DefaultAction::Continue) => Some("only continues it"),
+        Outcome::Handled | Outcome::Default(_) => None,
+    }
+}
+
+/// Returns the findings of cross-checking the signal settings of a
+/// systemd unit against the signal bitmaps of its (main) process:
+///
+/// * `KillSignal=` and `RestartKillSignal=` that are blocked or ignored
+///   (or do nothing by default), so stopping or restarting the unit
+///   waits for `TimeoutStopSec=` and ends with `FinalKillSignal=`.
+/// * `FinalKillSignal=` (if not `KILL`) that can't end the process
+///   either, leaving it behind (critical).
+/// * `IgnoreSIGPIPE=` disagreeing with whether `PIPE` is ignored, i.e.
+///   the process changed it after starting (info).
+///
+/// # Example
+/// ```
+/// use sig_bitmap::{doctor::Severity, unit::*, ProcessSignals, SigSet};
+/// let sigs: ProcessSignals = ProcessSignals {
+///     blocked: SigSet::from_bits(0x4000),
+///     ignored: SigSet::from_bits(0x1000),
+///     ..Default::default()
+/// };
+/// let findings = check_unit(&UnitSignals::default(), &sigs);
+/// assert_eq!(findings.len(), 1);
+/// assert_eq!(findings[0].severity, Severity::Warning);
+/// ```
+pub fn check_unit(unit: &UnitSignals, sigs: &ProcessSignals) -> Vec<Finding> {
+    let mut out: Vec<Finding> = Vec::new();
+    let mut kill = |key: &str, idx: &u8, severity: Severity| {
+        if let Some(why) = obstacle(idx, sigs) {
+            out.push(Finding::new(
+                severity,
+                Check::Unit,
+                format!("{}={} is {}", key, sigabbrev_np(idx), why),
+            ));
+        }
+    };
+
+    kill("KillSignal", &unit.kill_signal, Severity::Warning);
+
+    if let Some(idx) = unit.restart_kill_signal {
+        kill("RestartKillSignal", &idx, Severity::Warning);
+    }
+
+    kill(
+        "FinalKillSignal",
+        &unit.final_kill_signal,
+        Severity::Critical,
+    );
+
+    if unit.ignore_sigpipe != sigs.ignored.contains(&SIGPIPE_IDX) {
+        let (set, does): (&str, &str) = match unit.ignore_sigpipe {
+            true => ("yes", "doesn't ignore"),
+            false => ("no", "ignores"),
+        };
+        out.push(Finding::new(
+            Severity::Info,
+            Check::Unit,
+            format!("IgnoreSIGPIPE={}, but the process {} PIPE", set, does),
+        ));
+    }
+
+    out.sort_by_key(|fnd| std::cmp::Reverse(fnd.severity));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::SigSet;
+
+    #[test]
+    fn test_parse_unit() {
+        let tests: Vec<(&str, Result<UnitSignals, Error>)> = vec![
+            (
+                "[Service]\nExecStart=/bin/true\n",
+                Ok(UnitSignals::default()),
+            ),
+            (
+                "[Service]\nKillSignal=SIGINT\nKillSignal = 3\n\
+                 # FinalKillSignal=HUP\nRestartKillSignal=SIGHUP\n\
+                 IgnoreSIGPIPE=no\nIgnoreSIGPIPE=maybe\n",
+                Ok(UnitSignals {
+                    ignore_sigpipe: false,
+                    kill_signal: 0x03,
+                    restart_kill_signal: Some(0x01),
+                    ..Default::default()
+                }),
+            ),
+            (
+                "KillSignal=INT\nKillSignal=\nRestartKillSignal=HUP\n\
+                 RestartKillSignal=\n",
+                Ok(UnitSignals::default()),
+            ),
+            (
+                "FinalKillSignal=SIGBOGUS\n",
+                Err(Error::UnknownSignal(String::from("SIGBOGUS"))),
+            ),
+        ];
+
+        for test in tests {
+            assert_eq!(parse_unit(test.0), test.1);
+        }
+    }
+
+    #[test]
+    fn test_unit_name() {
+        let tests: Vec<(&str, Option<&str>)> = vec![
+            ("/system.slice/cron.service", Some("cron.service")),
+            (
+                "/user.slice/user-1000.slice/user@1000.service/app.slice/\
+                 app-term.scope",
+                Some("app-term.scope"),
+            ),
+            (
+                "/system.slice/docker.service/payload/worker",
+                Some("docker.service"),
+            ),
+            ("/user.slice/user-1000.slice", None),
+            ("/", None),
+        ];
+
+        for test in tests {
+            assert_eq!(unit_name(test.0), test.1);
+        }
+    }
+
+    #[test]
+    fn test_check_unit() {
+        let unit: UnitSignals = UnitSignals {
+            ignore_sigpipe: false,
+            kill_signal: 0x02,
+            restart_kill_signal: Some(0x1c),
+            final_kill_signal: 0x0f,
+        };
+        let sigs: ProcessSignals = ProcessSignals {
+            blocked: SigSet::from_bits(0x4000),
+            ignored: SigSet::from_bits(0x2 | 0x1000),
+            ..Default::default()
+        };
+        let findings: Vec<(Severity, String)> = check_unit(&unit, &sigs)
+            .into_iter()
+            .map(|fnd| (fnd.severity, fnd.message))
+            .collect();
+
+        assert_eq!(
+            findings,
+            vec![
+                (
+                    Severity::Critical,
+                    String::from(
+                        "FinalKillSignal=TERM is blocked by the process"
+                    ),
+                ),
+                (
+                    Severity::Warning,
+                    String::from("KillSignal=INT is ignored by the process"),
+                ),
+                (
+                    Severity::Warning,
+                    String::from(
+                        "RestartKillSignal=WINCH is ignored by default"
+                    ),
+                ),
+                (
+                    Severity::Info,
+                    String::from(
+                        "IgnoreSIGPIPE=no, but the process ignores PIPE"
+                    ),
+                ),
+            ],
+        );
+    }
+}